@@ -0,0 +1,166 @@
+use crate::types::validate_metadata;
+use crate::{FunctionDefinition, ObjectType, OpenAiError, Serializable};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A tool an assistant can use.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AssistantTool {
+    CodeInterpreter,
+    FileSearch,
+    Function { function: FunctionDefinition },
+}
+
+/// Files available to the code interpreter tool.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CodeInterpreterResources {
+    #[serde(default)]
+    pub file_ids: Vec<String>,
+}
+
+/// Vector stores searched by the file search tool.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FileSearchResources {
+    #[serde(default)]
+    pub vector_store_ids: Vec<String>,
+}
+
+/// Resources attached to the tools of an assistant.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ToolResources {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_interpreter: Option<CodeInterpreterResources>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_search: Option<FileSearchResources>,
+}
+
+/// Json data required for creating an assistant.
+#[derive(Serialize, Deserialize, Builder, Debug, Clone, PartialEq)]
+#[builder(
+    setter(strip_option, into),
+    build_fn(validate = "Self::validate", error = "OpenAiError")
+)]
+pub struct CreateAssistantRequest {
+    pub model: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<AssistantTool>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_resources: Option<ToolResources>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl Serializable for CreateAssistantRequest {}
+
+impl CreateAssistantRequestBuilder {
+    fn validate(&self) -> Result<(), OpenAiError> {
+        validate_metadata(self.metadata.as_ref().and_then(Option::as_ref))
+    }
+}
+
+/// Json data for modifying an assistant. Only set fields are changed.
+#[derive(Serialize, Deserialize, Builder, Debug, Default, Clone, PartialEq)]
+#[builder(
+    setter(strip_option, into),
+    build_fn(validate = "Self::validate", error = "OpenAiError")
+)]
+pub struct ModifyAssistantRequest {
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<AssistantTool>>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_resources: Option<ToolResources>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl Serializable for ModifyAssistantRequest {}
+
+impl ModifyAssistantRequestBuilder {
+    fn validate(&self) -> Result<(), OpenAiError> {
+        validate_metadata(self.metadata.as_ref().and_then(Option::as_ref))
+    }
+}
+
+/// An assistant that can call the model and use tools.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Assistant {
+    pub id: String,
+    pub object: ObjectType,
+    pub created_at: i64,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub model: String,
+    pub instructions: Option<String>,
+    #[serde(default)]
+    pub tools: Vec<AssistantTool>,
+    #[serde(default)]
+    pub tool_resources: Option<ToolResources>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+#[cfg(test)]
+mod assistant_tool {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_serialize_tools() {
+        let tools = vec![
+            AssistantTool::CodeInterpreter,
+            AssistantTool::FileSearch,
+            AssistantTool::Function {
+                function: FunctionDefinition {
+                    name: "get_weather".to_string(),
+                    description: Some("Returns the weather".to_string()),
+                    parameters: Some(json!({"type": "object", "properties": {}})),
+                },
+            },
+        ];
+        let value = serde_json::to_value(&tools).unwrap();
+        assert_eq!(
+            value,
+            json!([
+                {"type": "code_interpreter"},
+                {"type": "file_search"},
+                {"type": "function", "function": {
+                    "name": "get_weather",
+                    "description": "Returns the weather",
+                    "parameters": {"type": "object", "properties": {}}
+                }}
+            ])
+        );
+        assert_eq!(
+            serde_json::from_value::<Vec<AssistantTool>>(value).unwrap(),
+            tools
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use crate::multipart::FormExt;
+use crate::types::{invalid_parameter, validate_range};
+use crate::{OpenAiResult, Serializable};
+use reqwest::multipart::Form;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of characters of a speech input.
+const MAX_SPEECH_INPUT_CHARS: usize = 4096;
+
+/// Voice used for generating speech.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Voice {
+    Alloy,
+    Echo,
+    Fable,
+    Onyx,
+    Nova,
+    Shimmer,
+}
+
+/// Format of generated audio.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Mp3,
+    Opus,
+    Aac,
+    Flac,
+    Wav,
+    Pcm,
+}
+
+impl AudioFormat {
+    /// Returns the mime type of the format.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Opus => "audio/opus",
+            AudioFormat::Aac => "audio/aac",
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Pcm => "audio/pcm",
+        }
+    }
+}
+
+/// Json data required for generating speech from text with tts-1 or
+/// tts-1-hd.
+#[derive(Serialize, Deserialize, Builder, Debug, Clone, PartialEq)]
+#[builder(setter(strip_option, into))]
+pub struct SpeechRequest {
+    pub model: String,
+    pub input: String,
+    pub voice: Voice,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<AudioFormat>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f64>,
+}
+
+impl Serializable for SpeechRequest {}
+
+impl SpeechRequest {
+    /// Validates the input length and the speed range.
+    pub fn validate(&self) -> OpenAiResult<()> {
+        if self.input.chars().count() > MAX_SPEECH_INPUT_CHARS {
+            return Err(invalid_parameter(
+                "input",
+                &format!(
+                    "input must not exceed {} characters",
+                    MAX_SPEECH_INPUT_CHARS
+                ),
+            ));
+        }
+        validate_range("speed", self.speed, 0.25, 4.0)
+    }
+}
+
+/// Format of a transcription response.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionFormat {
+    Json,
+    Text,
+    Srt,
+    VerboseJson,
+    Vtt,
+}
+
+impl TranscriptionFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TranscriptionFormat::Json => "json",
+            TranscriptionFormat::Text => "text",
+            TranscriptionFormat::Srt => "srt",
+            TranscriptionFormat::VerboseJson => "verbose_json",
+            TranscriptionFormat::Vtt => "vtt",
+        }
+    }
+
+    /// Returns true if the Api responds with plain text instead of json.
+    pub fn is_plain_text(&self) -> bool {
+        matches!(
+            self,
+            TranscriptionFormat::Text | TranscriptionFormat::Srt | TranscriptionFormat::Vtt
+        )
+    }
+}
+
+/// Data required for transcribing an audio file. It is sent as multipart
+/// form.
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(strip_option, into))]
+pub struct TranscriptionRequest {
+    pub file_bytes: Vec<u8>,
+    pub file_name: String,
+    pub model: String,
+    /// Language of the audio in ISO-639-1 format.
+    #[builder(default)]
+    pub language: Option<String>,
+    #[builder(default)]
+    pub prompt: Option<String>,
+    #[builder(default)]
+    pub response_format: Option<TranscriptionFormat>,
+    #[builder(default)]
+    pub temperature: Option<f64>,
+}
+
+impl TranscriptionRequest {
+    pub fn validate(&self) -> OpenAiResult<()> {
+        validate_range("temperature", self.temperature, 0.0, 1.0)
+    }
+
+    pub(crate) fn form(&self) -> Form {
+        audio_form(
+            &self.file_bytes,
+            &self.file_name,
+            &self.model,
+            self.prompt.as_deref(),
+            self.response_format,
+            self.temperature,
+        )
+        .optional_text("language", self.language.as_deref())
+    }
+}
+
+/// Data required for translating an audio file into English. It is sent as
+/// multipart form.
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(strip_option, into))]
+pub struct TranslationRequest {
+    pub file_bytes: Vec<u8>,
+    pub file_name: String,
+    pub model: String,
+    #[builder(default)]
+    pub prompt: Option<String>,
+    #[builder(default)]
+    pub response_format: Option<TranscriptionFormat>,
+    #[builder(default)]
+    pub temperature: Option<f64>,
+}
+
+impl TranslationRequest {
+    pub fn validate(&self) -> OpenAiResult<()> {
+        validate_range("temperature", self.temperature, 0.0, 1.0)
+    }
+
+    pub(crate) fn form(&self) -> Form {
+        audio_form(
+            &self.file_bytes,
+            &self.file_name,
+            &self.model,
+            self.prompt.as_deref(),
+            self.response_format,
+            self.temperature,
+        )
+    }
+}
+
+fn audio_form(
+    file_bytes: &[u8],
+    file_name: &str,
+    model: &str,
+    prompt: Option<&str>,
+    response_format: Option<TranscriptionFormat>,
+    temperature: Option<f64>,
+) -> Form {
+    Form::new()
+        .file_bytes("file", file_name, file_bytes)
+        .text("model", model.to_string())
+        .optional_text("prompt", prompt)
+        .optional_text("response_format", response_format.map(|f| f.as_str()))
+        .optional_text("temperature", temperature.map(|t| t.to_string()))
+}
+
+/// Response of a translation, which has the same shape as a transcription.
+pub type TranslationResponse = TranscriptionResponse;
+
+/// A transcribed word with its timestamps in seconds.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TranscriptionWord {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// A transcribed segment of the audio.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TranscriptionSegment {
+    pub id: i64,
+    pub seek: i64,
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    #[serde(default)]
+    pub tokens: Vec<i64>,
+    pub temperature: f64,
+    pub avg_logprob: f64,
+    pub compression_ratio: f64,
+    pub no_speech_prob: f64,
+}
+
+/// Response of a transcription. Everything but the text is only returned for
+/// [TranscriptionFormat::VerboseJson].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TranscriptionResponse {
+    pub text: String,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub words: Option<Vec<TranscriptionWord>>,
+    #[serde(default)]
+    pub segments: Option<Vec<TranscriptionSegment>>,
+}
+
+impl TranscriptionResponse {
+    /// Creates a response from a plain text body.
+    pub fn from_text(text: String) -> Self {
+        TranscriptionResponse {
+            text,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod speech {
+    use super::*;
+    use crate::OpenAiError;
+    use serde_json::json;
+
+    fn request(input: &str, speed: f64) -> SpeechRequest {
+        SpeechRequestBuilder::default()
+            .model("tts-1")
+            .input(input)
+            .voice(Voice::Nova)
+            .response_format(AudioFormat::Opus)
+            .speed(speed)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_serialize_speech_request() {
+        assert_eq!(
+            serde_json::to_value(request("Hello", 1.5)).unwrap(),
+            json!({
+                "model": "tts-1",
+                "input": "Hello",
+                "voice": "nova",
+                "response_format": "opus",
+                "speed": 1.5
+            })
+        );
+    }
+
+    #[test]
+    fn should_validate_speech_request() {
+        assert!(request("Hello", 4.0).validate().is_ok());
+        assert!(matches!(
+            request("Hello", 0.1).validate(),
+            Err(OpenAiError::InvalidRequestParameter { name, .. }) if name == "speed"
+        ));
+        assert!(matches!(
+            request(&"a".repeat(4097), 1.0).validate(),
+            Err(OpenAiError::InvalidRequestParameter { name, .. }) if name == "input"
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{AuthScheme, OpenAiConfig};
+use std::fmt;
+
+const API_VERSION_PARAM: &str = "api-version";
+
+/// Configuration for an Azure OpenAI deployment. Converts into an
+/// [OpenAiConfig], so it can be used wherever the client accepts one.
+/// Completions, embeddings, images and audio are sent to the deployment, all
+/// other endpoints to the resource. An empty api key fails requests with
+/// [crate::OpenAiError::MissingTokenError], it never falls back to env
+/// OPENAI_API_KEY.
+///
+/// ```rust,no_run
+/// # use openai_client::*;
+/// let config = AzureOpenAiConfig::new("my-resource", "gpt-35-turbo", "2024-02-01", "<API_KEY>");
+/// let client = OpenAiClient::new(config);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct AzureOpenAiConfig {
+    pub resource_name: String,
+    pub deployment_name: String,
+    pub api_version: String,
+    pub api_key: String,
+    /// Overrides the `https://{resource_name}.openai.azure.com` endpoint,
+    /// e.g. for proxies or custom domains.
+    pub endpoint: Option<String>,
+}
+
+impl AzureOpenAiConfig {
+    pub fn new(
+        resource_name: &str,
+        deployment_name: &str,
+        api_version: &str,
+        api_key: &str,
+    ) -> Self {
+        AzureOpenAiConfig {
+            resource_name: resource_name.to_string(),
+            deployment_name: deployment_name.to_string(),
+            api_version: api_version.to_string(),
+            api_key: api_key.to_string(),
+            endpoint: None,
+        }
+    }
+
+    /// Set a custom endpoint of the Azure resource.
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.to_string());
+        self
+    }
+
+    /// Returns the base url of the resource.
+    pub fn resource_url(&self) -> String {
+        let endpoint = self
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://{}.openai.azure.com", self.resource_name));
+        format!("{}/openai", endpoint.trim_end_matches('/'))
+    }
+
+    /// Returns the base url of the deployment.
+    pub fn deployment_url(&self) -> String {
+        format!("{}/{}", self.resource_url(), self.deployment_path())
+    }
+
+    fn deployment_path(&self) -> String {
+        format!("deployments/{}", self.deployment_name)
+    }
+}
+
+/// Redacts the api key.
+impl fmt::Debug for AzureOpenAiConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AzureOpenAiConfig")
+            .field("resource_name", &self.resource_name)
+            .field("deployment_name", &self.deployment_name)
+            .field("api_version", &self.api_version)
+            .field("api_key", &"<redacted>")
+            .field("endpoint", &self.endpoint)
+            .finish()
+    }
+}
+
+/// Azure paths have no version segment, the version is sent as query param.
+impl From<AzureOpenAiConfig> for OpenAiConfig {
+    fn from(value: AzureOpenAiConfig) -> Self {
+        OpenAiConfig::create(&value.resource_url(), "", &value.api_key)
+            .deployment_paths(&value.deployment_path())
+            .token_env(None)
+            .auth_scheme(AuthScheme::ApiKey)
+            .with_query_param(API_VERSION_PARAM, &value.api_version)
+    }
+}
+
+#[cfg(test)]
+mod azure_config {
+    use super::*;
+
+    #[test]
+    fn should_build_deployment_urls() {
+        let config: OpenAiConfig =
+            AzureOpenAiConfig::new("my-resource", "gpt-35-turbo", "2024-02-01", "key").into();
+        assert_eq!(
+            config.api_url(&config.get_chat_completion_path()),
+            "https://my-resource.openai.azure.com/openai/deployments/gpt-35-turbo/chat/completions"
+        );
+        assert_eq!(
+            config.api_url(&config.get_embedding_path()),
+            "https://my-resource.openai.azure.com/openai/deployments/gpt-35-turbo/embeddings"
+        );
+        assert_eq!(
+            config.api_url(&config.get_create_image_path()),
+            "https://my-resource.openai.azure.com/openai/deployments/gpt-35-turbo/images/generations"
+        );
+        assert_eq!(
+            config.api_url(&config.get_transcription_path()),
+            "https://my-resource.openai.azure.com/openai/deployments/gpt-35-turbo/audio/transcriptions"
+        );
+        assert_eq!(
+            config.api_url(&config.get_files_path()),
+            "https://my-resource.openai.azure.com/openai/files"
+        );
+        assert_eq!(
+            config.api_url(&config.get_batches_path()),
+            "https://my-resource.openai.azure.com/openai/batches"
+        );
+        assert_eq!(
+            config.api_url(&config.get_assistants_path()),
+            "https://my-resource.openai.azure.com/openai/assistants"
+        );
+        assert_eq!(config.get_auth_scheme(), AuthScheme::ApiKey);
+        assert_eq!(
+            config.get_query_params(),
+            &[("api-version".to_string(), "2024-02-01".to_string())]
+        );
+        assert_eq!(config.get_access_token().unwrap(), "key");
+    }
+
+    #[test]
+    fn should_use_custom_endpoint() {
+        let config = AzureOpenAiConfig::new("my-resource", "gpt-4", "2024-02-01", "key")
+            .endpoint("https://proxy.example.com/");
+        assert_eq!(
+            config.deployment_url(),
+            "https://proxy.example.com/openai/deployments/gpt-4"
+        );
+    }
+
+    #[test]
+    fn should_not_fall_back_to_env_token() {
+        let config: OpenAiConfig =
+            AzureOpenAiConfig::new("my-resource", "gpt-4", "2024-02-01", "").into();
+        assert!(matches!(
+            config.get_access_token(),
+            Err(crate::OpenAiError::MissingTokenError)
+        ));
+    }
+
+    #[test]
+    fn should_redact_api_key_in_debug() {
+        let config = AzureOpenAiConfig::new("my-resource", "gpt-4", "2024-02-01", "secret-key");
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("secret-key"));
+        assert!(debug.contains("my-resource"));
+    }
+}
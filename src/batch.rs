@@ -0,0 +1,314 @@
+use crate::types::validate_metadata;
+use crate::{
+    ChatCompletionRequest, ObjectType, OpenAiError, OpenAiErrorDetails, OpenAiErrorResponse,
+    OpenAiResult, Serializable,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Endpoint all requests of a batch are sent to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchEndpoint {
+    #[serde(rename = "/v1/chat/completions")]
+    ChatCompletions,
+    #[serde(rename = "/v1/completions")]
+    Completions,
+    #[serde(rename = "/v1/embeddings")]
+    Embeddings,
+}
+
+impl BatchEndpoint {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BatchEndpoint::ChatCompletions => "/v1/chat/completions",
+            BatchEndpoint::Completions => "/v1/completions",
+            BatchEndpoint::Embeddings => "/v1/embeddings",
+        }
+    }
+}
+
+/// Json data required for creating a batch from an uploaded input file.
+#[derive(Serialize, Deserialize, Builder, Debug, Clone, PartialEq)]
+#[builder(
+    setter(strip_option, into),
+    build_fn(validate = "Self::validate", error = "OpenAiError")
+)]
+pub struct CreateBatchRequest {
+    pub input_file_id: String,
+    pub endpoint: BatchEndpoint,
+    /// Time frame the batch is processed in, currently only `24h`.
+    #[builder(default = "\"24h\".to_string()")]
+    pub completion_window: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl Serializable for CreateBatchRequest {}
+
+impl CreateBatchRequestBuilder {
+    fn validate(&self) -> Result<(), OpenAiError> {
+        validate_metadata(self.metadata.as_ref().and_then(Option::as_ref))
+    }
+}
+
+/// Status of a batch.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Validating,
+    Failed,
+    InProgress,
+    Finalizing,
+    Completed,
+    Expired,
+    Cancelling,
+    Cancelled,
+}
+
+impl BatchStatus {
+    /// Returns true if the batch will not change anymore.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            BatchStatus::Failed
+                | BatchStatus::Completed
+                | BatchStatus::Expired
+                | BatchStatus::Cancelled
+        )
+    }
+}
+
+/// Number of requests of a batch per state.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchRequestCounts {
+    pub total: u64,
+    pub completed: u64,
+    pub failed: u64,
+}
+
+/// Validation error of a batch input file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchValidationError {
+    pub code: Option<String>,
+    pub message: Option<String>,
+    #[serde(default)]
+    pub param: Option<String>,
+    #[serde(default)]
+    pub line: Option<u64>,
+}
+
+/// Validation errors of a batch.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchErrors {
+    pub object: ObjectType,
+    #[serde(default)]
+    pub data: Vec<BatchValidationError>,
+}
+
+/// A batch of requests processed asynchronously.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Batch {
+    pub id: String,
+    pub object: ObjectType,
+    pub endpoint: String,
+    #[serde(default)]
+    pub errors: Option<BatchErrors>,
+    pub input_file_id: String,
+    pub completion_window: String,
+    pub status: BatchStatus,
+    #[serde(default)]
+    pub output_file_id: Option<String>,
+    #[serde(default)]
+    pub error_file_id: Option<String>,
+    pub created_at: i64,
+    #[serde(default)]
+    pub in_progress_at: Option<i64>,
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+    #[serde(default)]
+    pub finalizing_at: Option<i64>,
+    #[serde(default)]
+    pub completed_at: Option<i64>,
+    #[serde(default)]
+    pub failed_at: Option<i64>,
+    #[serde(default)]
+    pub expired_at: Option<i64>,
+    #[serde(default)]
+    pub cancelling_at: Option<i64>,
+    #[serde(default)]
+    pub cancelled_at: Option<i64>,
+    #[serde(default)]
+    pub request_counts: BatchRequestCounts,
+    #[serde(default)]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+/// A single line of a Batch API input file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchInputLine<T> {
+    pub custom_id: String,
+    pub method: String,
+    pub url: String,
+    pub body: T,
+}
+
+/// Serializes a chat completion request into a line of a Batch API input
+/// file, without the trailing new line.
+pub fn build_batch_input_line(
+    custom_id: &str,
+    request: &ChatCompletionRequest,
+) -> OpenAiResult<String> {
+    let line = BatchInputLine {
+        custom_id: custom_id.to_string(),
+        method: "POST".to_string(),
+        url: BatchEndpoint::ChatCompletions.as_str().to_string(),
+        body: request,
+    };
+    Ok(serde_json::to_string(&line)?)
+}
+
+/// A single line of a Batch API output file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchOutputLine<T> {
+    pub custom_id: String,
+    #[serde(default)]
+    pub response: Option<BatchLineResponse<T>>,
+    #[serde(default)]
+    pub error: Option<BatchLineError>,
+}
+
+/// The http response of a single batch request.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchLineResponse<T> {
+    pub status_code: u16,
+    pub body: T,
+}
+
+/// Error of a batch request that could not be executed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BatchLineError {
+    pub code: String,
+    pub message: String,
+}
+
+impl From<BatchLineError> for OpenAiError {
+    fn from(error: BatchLineError) -> Self {
+        OpenAiError::ApiErrorResponse(OpenAiErrorDetails {
+            code: Some(error.code),
+            message: error.message,
+            param: None,
+            error_type: None,
+        })
+    }
+}
+
+/// Parses a Batch API output file in JSONL format. Every non empty line yields
+/// the `custom_id` and the response body, or an error for lines that failed,
+/// returned a non 200 status or could not be parsed.
+pub fn parse_batch_output<T: DeserializeOwned>(bytes: &[u8]) -> Vec<OpenAiResult<(String, T)>> {
+    bytes
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .map(parse_batch_line)
+        .collect()
+}
+
+fn parse_batch_line<T: DeserializeOwned>(line: &[u8]) -> OpenAiResult<(String, T)> {
+    let line: BatchOutputLine<Value> = serde_json::from_slice(line)?;
+    if let Some(error) = line.error {
+        return Err(error.into());
+    }
+    let response = line.response.ok_or(OpenAiError::UnexpectedApiResponse)?;
+    if response.status_code != 200 {
+        return Err(
+            match serde_json::from_value::<OpenAiErrorResponse>(response.body.clone()) {
+                Ok(body) => OpenAiError::ApiErrorResponse(body.error),
+                Err(_) => OpenAiError::HttpStatus {
+                    status: response.status_code,
+                    body: Some(response.body.to_string()),
+                },
+            },
+        );
+    }
+    Ok((line.custom_id, serde_json::from_value(response.body)?))
+}
+
+#[cfg(test)]
+mod batch_output {
+    use super::*;
+    use crate::TextResult;
+
+    const OUTPUT: &str = r#"{"id":"batch_req_1","custom_id":"request-1","response":{"status_code":200,"request_id":"req_1","body":{"id":"cmpl-1","object":"text_completion","created":1669646641,"model":"text-davinci-003","choices":[{"text":"Hello","index":0,"logprobs":null,"finish_reason":"stop"}],"usage":{"prompt_tokens":1,"completion_tokens":1,"total_tokens":2}}},"error":null}
+{"id":"batch_req_2","custom_id":"request-2","response":null,"error":{"code":"batch_expired","message":"This request could not be executed before the completion window expired."}}
+
+{"id":"batch_req_3","custom_id":"request-3","response":{"status_code":400,"request_id":"req_3","body":{"error":{"message":"Invalid model","type":"invalid_request_error","param":"model","code":null}}},"error":null}
+{"id":"batch_req_4","custom_id":"request-4","response":{"status_code":500,"request_id":"req_4","body":"internal error"},"error":null}
+not json
+"#;
+
+    #[test]
+    fn should_parse_mixed_batch_output() {
+        let results = parse_batch_output::<TextResult>(OUTPUT.as_bytes());
+        assert_eq!(results.len(), 5);
+
+        let (id, result) = results[0].as_ref().unwrap();
+        assert_eq!(id, "request-1");
+        assert_eq!(result.best_text(), Some("Hello"));
+
+        match &results[1] {
+            Err(OpenAiError::ApiErrorResponse(details)) => {
+                assert_eq!(details.code.as_deref(), Some("batch_expired"));
+            }
+            other => panic!("expected batch line error, got {:?}", other),
+        }
+
+        match &results[2] {
+            Err(OpenAiError::ApiErrorResponse(details)) => {
+                assert_eq!(details.message, "Invalid model");
+                assert_eq!(details.param.as_deref(), Some("model"));
+            }
+            other => panic!("expected api error, got {:?}", other),
+        }
+
+        match &results[3] {
+            Err(OpenAiError::HttpStatus { status, body }) => {
+                assert_eq!(*status, 500);
+                assert_eq!(body.as_deref(), Some("\"internal error\""));
+            }
+            other => panic!("expected http status error, got {:?}", other),
+        }
+
+        assert!(matches!(results[4], Err(OpenAiError::JsonEncodeError(_))));
+    }
+
+    #[test]
+    fn should_build_chat_completion_input_line() {
+        let request = crate::ChatCompletionRequestBuilder::default()
+            .model("gpt-3.5-turbo")
+            .messages(vec![crate::ChatMessage::user("Hello!")])
+            .build()
+            .unwrap();
+        let line = build_batch_input_line("request-1", &request).unwrap();
+        assert!(!line.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<Value>(&line).unwrap(),
+            serde_json::json!({
+                "custom_id": "request-1",
+                "method": "POST",
+                "url": "/v1/chat/completions",
+                "body": {
+                    "model": "gpt-3.5-turbo",
+                    "messages": [{"role": "user", "content": "Hello!"}]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn should_return_empty_result_for_empty_output() {
+        assert!(parse_batch_output::<Value>(b"\n\n").is_empty());
+    }
+}
@@ -0,0 +1,804 @@
+use crate::types::{invalid_parameter, validate_sampling};
+use crate::{
+    FinishReason, ObjectType, OpenAiError, OpenAiResult, Serializable, StringOrListParam, Usage,
+};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+
+/// The author of a chat message.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+/// Resolution an image is processed with by the model.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageDetail {
+    Auto,
+    Low,
+    High,
+}
+
+/// An image referenced by url or as base64 data url.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ImageUrlContent {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<ImageDetail>,
+}
+
+impl ImageUrlContent {
+    pub fn new(url: &str) -> Self {
+        ImageUrlContent {
+            url: url.to_string(),
+            detail: None,
+        }
+    }
+
+    /// Creates a data url from base64 encoded image data of the given mime
+    /// type, e.g. `image/png`.
+    pub fn base64(mime_type: &str, data: &str) -> Self {
+        ImageUrlContent::new(&format!("data:{};base64,{}", mime_type, data))
+    }
+}
+
+/// A single part of a multi part message content.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrlContent },
+}
+
+/// Content of a chat message, either a plain text or a list of parts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl MessageContent {
+    /// Returns the text of the content. Texts of multiple parts are joined
+    /// by a new line, image parts are skipped.
+    pub fn text(&self) -> String {
+        match self {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    ContentPart::ImageUrl { .. } => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        MessageContent::Text(text.to_string())
+    }
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        MessageContent::Text(text)
+    }
+}
+
+impl From<Vec<ContentPart>> for MessageContent {
+    fn from(parts: Vec<ContentPart>) -> Self {
+        MessageContent::Parts(parts)
+    }
+}
+
+/// A function the model may call. The parameters are described as JSON
+/// schema.
+#[derive(Serialize, Deserialize, Builder, Debug, Clone, PartialEq)]
+#[builder(setter(strip_option, into))]
+pub struct FunctionDefinition {
+    pub name: String,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+}
+
+/// A tool the model may use during a chat completion.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "function", rename_all = "snake_case")]
+pub enum Tool {
+    Function(FunctionDefinition),
+}
+
+/// Name of the function the model is forced to call.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ToolChoiceFunction {
+    pub name: String,
+}
+
+/// Controls which tool, if any, is called by the model. Modes are sent as
+/// plain string, a specific function as object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolChoice {
+    None,
+    Auto,
+    Required,
+    Specific(ToolChoiceFunction),
+}
+
+impl ToolChoice {
+    /// Forces the model to call the function with the given name.
+    pub fn function(name: &str) -> Self {
+        ToolChoice::Specific(ToolChoiceFunction {
+            name: name.to_string(),
+        })
+    }
+}
+
+impl Serialize for ToolChoice {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ToolChoice::None => serializer.serialize_str("none"),
+            ToolChoice::Auto => serializer.serialize_str("auto"),
+            ToolChoice::Required => serializer.serialize_str("required"),
+            ToolChoice::Specific(function) => {
+                let mut choice = serializer.serialize_struct("ToolChoice", 2)?;
+                choice.serialize_field("type", "function")?;
+                choice.serialize_field("function", function)?;
+                choice.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ToolChoice {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Mode(String),
+            Specific { function: ToolChoiceFunction },
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Mode(mode) => match mode.as_str() {
+                "none" => Ok(ToolChoice::None),
+                "auto" => Ok(ToolChoice::Auto),
+                "required" => Ok(ToolChoice::Required),
+                _ => Err(serde::de::Error::unknown_variant(
+                    &mode,
+                    &["none", "auto", "required"],
+                )),
+            },
+            Repr::Specific { function } => Ok(ToolChoice::Specific(function)),
+        }
+    }
+}
+
+/// A function call requested by the model. The arguments are a JSON
+/// encoded object.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FunctionCall {
+    pub name: String,
+    pub arguments: String,
+}
+
+/// A tool call requested by the model.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    pub function: FunctionCall,
+}
+
+/// Format the model must produce its output in. `JsonSchema` enables
+/// structured outputs following the given JSON schema.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "ResponseFormatRepr", into = "ResponseFormatRepr")]
+pub enum ResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema {
+        name: String,
+        description: Option<String>,
+        schema: serde_json::Value,
+        strict: Option<bool>,
+    },
+}
+
+/// Wire format of [ResponseFormat], nesting the schema in `json_schema`.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ResponseFormatRepr {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: JsonSchemaRepr },
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonSchemaRepr {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    schema: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    strict: Option<bool>,
+}
+
+impl From<ResponseFormat> for ResponseFormatRepr {
+    fn from(format: ResponseFormat) -> Self {
+        match format {
+            ResponseFormat::Text => ResponseFormatRepr::Text,
+            ResponseFormat::JsonObject => ResponseFormatRepr::JsonObject,
+            ResponseFormat::JsonSchema {
+                name,
+                description,
+                schema,
+                strict,
+            } => ResponseFormatRepr::JsonSchema {
+                json_schema: JsonSchemaRepr {
+                    name,
+                    description,
+                    schema,
+                    strict,
+                },
+            },
+        }
+    }
+}
+
+impl From<ResponseFormatRepr> for ResponseFormat {
+    fn from(format: ResponseFormatRepr) -> Self {
+        match format {
+            ResponseFormatRepr::Text => ResponseFormat::Text,
+            ResponseFormatRepr::JsonObject => ResponseFormat::JsonObject,
+            ResponseFormatRepr::JsonSchema { json_schema } => ResponseFormat::JsonSchema {
+                name: json_schema.name,
+                description: json_schema.description,
+                schema: json_schema.schema,
+                strict: json_schema.strict,
+            },
+        }
+    }
+}
+
+/// A message of a chat conversation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    #[serde(default)]
+    pub content: Option<MessageContent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Tool calls requested by the model in an assistant message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// Id of the tool call a tool message responds to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn new(role: ChatRole, content: &str) -> Self {
+        ChatMessage {
+            role,
+            content: Some(MessageContent::Text(content.to_string())),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    pub fn system(content: &str) -> Self {
+        ChatMessage::new(ChatRole::System, content)
+    }
+
+    pub fn user(content: &str) -> Self {
+        ChatMessage::new(ChatRole::User, content)
+    }
+
+    pub fn assistant(content: &str) -> Self {
+        ChatMessage::new(ChatRole::Assistant, content)
+    }
+
+    /// Creates a message with the output of the given tool call.
+    pub fn tool(tool_call_id: &str, content: &str) -> Self {
+        ChatMessage {
+            tool_call_id: Some(tool_call_id.to_string()),
+            ..ChatMessage::new(ChatRole::Tool, content)
+        }
+    }
+
+    /// Returns the text content of the message.
+    pub fn text(&self) -> Option<String> {
+        self.content.as_ref().map(MessageContent::text)
+    }
+}
+
+/// Json data required for doing chat completion requests.
+#[derive(Serialize, Deserialize, Builder, Debug, Default, Clone, PartialEq)]
+#[builder(
+    setter(strip_option, into),
+    build_fn(validate = "Self::validate", error = "OpenAiError")
+)]
+pub struct ChatCompletionRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<i64>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<i64>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<StringOrListParam>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logit_bias: Option<HashMap<String, i64>>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    /// Makes sampling mostly deterministic for equal requests.
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<bool>,
+    /// Number of most likely tokens returned per position, requires
+    /// `logprobs` to be enabled.
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<u8>,
+}
+
+const MAX_TOP_LOGPROBS: u8 = 5;
+
+impl ChatCompletionRequestBuilder {
+    fn validate(&self) -> Result<(), OpenAiError> {
+        let Some(Some(top_logprobs)) = self.top_logprobs else {
+            return Ok(());
+        };
+        if top_logprobs > MAX_TOP_LOGPROBS {
+            return Err(invalid_parameter(
+                "top_logprobs",
+                &format!("top_logprobs must not exceed {}", MAX_TOP_LOGPROBS),
+            ));
+        }
+        if self.logprobs != Some(Some(true)) {
+            return Err(invalid_parameter(
+                "top_logprobs",
+                "top_logprobs requires logprobs to be enabled",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Serializable for ChatCompletionRequest {}
+
+impl ChatCompletionRequest {
+    /// Validates that the sampling parameters are within the documented
+    /// ranges.
+    pub fn validate(&self) -> OpenAiResult<()> {
+        validate_sampling(
+            self.temperature,
+            self.top_p,
+            self.presence_penalty,
+            self.frequency_penalty,
+        )
+    }
+}
+
+/// One of the most likely tokens at a position.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f64,
+    #[serde(default)]
+    pub bytes: Option<Vec<u8>>,
+}
+
+/// Log probability of a generated token.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatCompletionTokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+    #[serde(default)]
+    pub bytes: Option<Vec<u8>>,
+    #[serde(default)]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+/// Log probabilities of the content tokens of a choice.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct LogprobsContent {
+    #[serde(default)]
+    pub content: Vec<ChatCompletionTokenLogprob>,
+}
+
+/// A choice of a chat completion.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatChoice {
+    pub index: i64,
+    pub message: ChatMessage,
+    pub finish_reason: Option<FinishReason>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<LogprobsContent>,
+}
+
+/// Response of a chat completion request.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatCompletionResponse {
+    pub id: Option<String>,
+    pub object: ObjectType,
+    pub created: i64,
+    pub model: Option<String>,
+    /// Identifies the backend configuration the model ran with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_fingerprint: Option<String>,
+    pub choices: Vec<ChatChoice>,
+    #[serde(default)]
+    pub usage: Usage,
+}
+
+impl ChatCompletionResponse {
+    /// Returns the choice with the lowest index.
+    pub fn best_choice(&self) -> Option<&ChatChoice> {
+        self.choices.iter().min_by_key(|c| c.index)
+    }
+
+    /// Returns the text content of the best choice.
+    pub fn best_text(&self) -> Option<String> {
+        self.best_choice().and_then(|c| c.message.text())
+    }
+}
+
+/// Partial message of a streamed chat completion choice.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ChatMessageDelta {
+    #[serde(default)]
+    pub role: Option<ChatRole>,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+/// Partial tool call of a streamed chat completion. The first delta of a
+/// call carries its id and name, later ones only argument fragments.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ToolCallDelta {
+    pub index: usize,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default, rename = "type")]
+    pub tool_type: Option<String>,
+    #[serde(default)]
+    pub function: Option<FunctionCallDelta>,
+}
+
+/// Partial function call of a streamed tool call.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FunctionCallDelta {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub arguments: Option<String>,
+}
+
+/// A choice of a streamed chat completion chunk.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatStreamChoice {
+    pub index: i64,
+    pub delta: ChatMessageDelta,
+    pub finish_reason: Option<FinishReason>,
+}
+
+/// A partial chat completion sent per event of a streamed chat completion.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatStreamChunk {
+    pub id: Option<String>,
+    pub object: ObjectType,
+    pub created: i64,
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_fingerprint: Option<String>,
+    pub choices: Vec<ChatStreamChoice>,
+}
+
+#[cfg(test)]
+mod chat_completion {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_serialize_chat_request() {
+        let request = ChatCompletionRequestBuilder::default()
+            .model("gpt-3.5-turbo")
+            .messages(vec![
+                ChatMessage::system("You are a helpful assistant."),
+                ChatMessage::user("Hello!"),
+            ])
+            .temperature(0.7)
+            .build()
+            .unwrap();
+        assert_eq!(request.seed, None);
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "model": "gpt-3.5-turbo",
+                "messages": [
+                    {"role": "system", "content": "You are a helpful assistant."},
+                    {"role": "user", "content": "Hello!"}
+                ],
+                "temperature": 0.7
+            })
+        );
+    }
+
+    #[test]
+    fn should_serialize_tools_and_tool_choice() {
+        let function = FunctionDefinitionBuilder::default()
+            .name("get_weather")
+            .parameters(json!({"type": "object", "properties": {}}))
+            .build()
+            .unwrap();
+        let request = ChatCompletionRequestBuilder::default()
+            .model("gpt-3.5-turbo")
+            .messages(vec![ChatMessage::user("Weather in Berlin?")])
+            .tools(vec![Tool::Function(function)])
+            .tool_choice(ToolChoice::function("get_weather"))
+            .build()
+            .unwrap();
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            value["tools"],
+            json!([{
+                "type": "function",
+                "function": {
+                    "name": "get_weather",
+                    "parameters": {"type": "object", "properties": {}}
+                }
+            }])
+        );
+        assert_eq!(
+            value["tool_choice"],
+            json!({"type": "function", "function": {"name": "get_weather"}})
+        );
+        assert_eq!(
+            serde_json::to_value(ToolChoice::None).unwrap(),
+            json!("none")
+        );
+        assert_eq!(
+            serde_json::to_value(ToolChoice::Required).unwrap(),
+            json!("required")
+        );
+    }
+
+    #[test]
+    fn should_deserialize_tool_choice() {
+        let choice: ToolChoice = serde_json::from_value(json!("auto")).unwrap();
+        assert_eq!(choice, ToolChoice::Auto);
+        let choice: ToolChoice = serde_json::from_value(
+            json!({"type": "function", "function": {"name": "get_weather"}}),
+        )
+        .unwrap();
+        assert_eq!(choice, ToolChoice::function("get_weather"));
+        assert!(serde_json::from_value::<ToolChoice>(json!("sometimes")).is_err());
+    }
+
+    #[test]
+    fn should_deserialize_tool_calls() {
+        let message: ChatMessage = serde_json::from_value(json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "id": "call_abc123",
+                "type": "function",
+                "function": {"name": "get_weather", "arguments": "{\"city\":\"Berlin\"}"}
+            }]
+        }))
+        .unwrap();
+        let calls = message.tool_calls.unwrap();
+        assert_eq!(calls[0].function.name, "get_weather");
+        assert_eq!(
+            serde_json::to_value(ChatMessage::tool("call_abc123", "sunny")).unwrap(),
+            json!({"role": "tool", "content": "sunny", "tool_call_id": "call_abc123"})
+        );
+    }
+
+    #[test]
+    fn should_round_trip_text_content() {
+        let content = MessageContent::from("Hello");
+        let value = serde_json::to_value(&content).unwrap();
+        assert_eq!(value, json!("Hello"));
+        assert_eq!(
+            serde_json::from_value::<MessageContent>(value).unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn should_round_trip_image_content() {
+        let mut image = ImageUrlContent::new("https://example.com/otter.png");
+        image.detail = Some(ImageDetail::Low);
+        let content = MessageContent::from(vec![
+            ContentPart::Text {
+                text: "What is in this image?".to_string(),
+            },
+            ContentPart::ImageUrl { image_url: image },
+            ContentPart::ImageUrl {
+                image_url: ImageUrlContent::base64("image/png", "aGVsbG8="),
+            },
+        ]);
+        let value = serde_json::to_value(&content).unwrap();
+        assert_eq!(
+            value,
+            json!([
+                {"type": "text", "text": "What is in this image?"},
+                {"type": "image_url", "image_url": {"url": "https://example.com/otter.png", "detail": "low"}},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,aGVsbG8="}}
+            ])
+        );
+        assert_eq!(
+            serde_json::from_value::<MessageContent>(value).unwrap(),
+            content
+        );
+        assert_eq!(content.text(), "What is in this image?");
+    }
+
+    #[test]
+    fn should_serialize_response_formats() {
+        assert_eq!(
+            serde_json::to_value(ResponseFormat::Text).unwrap(),
+            json!({"type": "text"})
+        );
+        assert_eq!(
+            serde_json::to_value(ResponseFormat::JsonObject).unwrap(),
+            json!({"type": "json_object"})
+        );
+        let format = ResponseFormat::JsonSchema {
+            name: "weather".to_string(),
+            description: None,
+            schema: json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+            strict: Some(true),
+        };
+        let value = serde_json::to_value(&format).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "type": "json_schema",
+                "json_schema": {
+                    "name": "weather",
+                    "schema": {"type": "object", "properties": {"city": {"type": "string"}}},
+                    "strict": true
+                }
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<ResponseFormat>(value).unwrap(),
+            format
+        );
+    }
+
+    #[test]
+    fn should_validate_top_logprobs() {
+        let mut builder = ChatCompletionRequestBuilder::default();
+        builder
+            .model("gpt-3.5-turbo")
+            .messages(vec![ChatMessage::user("Hello!")]);
+        let request = builder.logprobs(true).top_logprobs(2).build().unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["top_logprobs"],
+            json!(2)
+        );
+        for (logprobs, top_logprobs) in [(false, 2), (true, 6)] {
+            match builder
+                .logprobs(logprobs)
+                .top_logprobs(top_logprobs)
+                .build()
+            {
+                Err(OpenAiError::InvalidRequestParameter { name, .. }) => {
+                    assert_eq!(name, "top_logprobs")
+                }
+                _ => panic!("expected invalid top_logprobs {}", top_logprobs),
+            }
+        }
+    }
+
+    #[test]
+    fn should_deserialize_choice_logprobs() {
+        let choice: ChatChoice = serde_json::from_value(json!({
+            "index": 0,
+            "message": {"role": "assistant", "content": "Hi"},
+            "finish_reason": "stop",
+            "logprobs": {
+                "content": [{
+                    "token": "Hi",
+                    "logprob": -0.31,
+                    "bytes": [72, 105],
+                    "top_logprobs": [
+                        {"token": "Hi", "logprob": -0.31, "bytes": [72, 105]},
+                        {"token": "Hello", "logprob": -1.4, "bytes": null}
+                    ]
+                }]
+            }
+        }))
+        .unwrap();
+        let content = choice.logprobs.unwrap().content;
+        assert_eq!(content[0].bytes, Some(b"Hi".to_vec()));
+        assert_eq!(content[0].top_logprobs[1].token, "Hello");
+    }
+
+    #[test]
+    fn should_serialize_seed() {
+        let request = ChatCompletionRequestBuilder::default()
+            .model("gpt-3.5-turbo")
+            .messages(vec![ChatMessage::user("Hello!")])
+            .seed(42)
+            .build()
+            .unwrap();
+        assert_eq!(serde_json::to_value(&request).unwrap()["seed"], json!(42));
+    }
+
+    #[test]
+    fn should_require_messages() {
+        assert!(ChatCompletionRequestBuilder::default()
+            .model("gpt-3.5-turbo")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn should_deserialize_message_content() {
+        let message: ChatMessage = serde_json::from_value(json!({
+            "role": "user",
+            "content": [{"type": "text", "text": "Hello"}, {"type": "text", "text": "World"}]
+        }))
+        .unwrap();
+        assert_eq!(message.role, ChatRole::User);
+        assert_eq!(message.text(), Some("Hello\nWorld".to_string()));
+
+        let message: ChatMessage =
+            serde_json::from_value(json!({"role": "assistant", "content": null})).unwrap();
+        assert_eq!(message.content, None);
+    }
+}
@@ -0,0 +1,650 @@
+use crate::types::TextResult;
+use crate::{
+    ApiResponse, Assistant, Batch, ChatCompletionRequest, ChatCompletionResponse, ChatStreamChunk,
+    ClientApi, CompletionRequest, CreateAssistantRequest, CreateBatchRequest,
+    CreateFineTuningJobRequest, CreateImageRequest, CreateMessageRequest, CreateRunRequest,
+    CreateThreadRequest, DeleteResponse, EditImageRequest, EditRequest, EmbeddingRequest,
+    EmbeddingResponse, FileList, FileObject, FineTuningEventList, FineTuningJob, FineTuningJobList,
+    ImageResult, ImageVariationRequest, ListParams, ListResponse, ModerationRequest,
+    ModerationResponse, ModifyAssistantRequest, ModifyMessageRequest, ModifyThreadRequest,
+    OpenAiError, OpenAiModel, OpenAiModelResponse, OpenAiResult, OpenAiStream, Run, SpeechRequest,
+    StreamChunk, SubmitToolOutputsRequest, Thread, ThreadMessageObject, TranscriptionRequest,
+    TranscriptionResponse, TranslationRequest, TranslationResponse, UploadFileRequest,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The state of a [CircuitBreaker].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests pass through, consecutive failures are counted.
+    Closed { failures: u32 },
+    /// Requests fail fast until the open duration has passed.
+    Open { opened_at: Instant },
+    /// A single trial request at a time passes through, consecutive
+    /// successes are counted.
+    HalfOpen { successes: u32 },
+}
+
+/// Tracks failures of Api requests and rejects requests for a while once
+/// too many consecutive requests failed.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    state: Arc<Mutex<CircuitState>>,
+    /// Set while a half open trial request is outstanding, only changed
+    /// while holding the state lock.
+    probing: Arc<AtomicBool>,
+    failure_threshold: u32,
+    success_threshold: u32,
+    open_duration: Duration,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed circuit breaker that opens after `failure_threshold`
+    /// consecutive failures, stays open for `open_duration` and closes again
+    /// after `success_threshold` successful trial requests.
+    pub fn new(failure_threshold: u32, success_threshold: u32, open_duration: Duration) -> Self {
+        CircuitBreaker {
+            state: Arc::new(Mutex::new(CircuitState::Closed { failures: 0 })),
+            probing: Arc::new(AtomicBool::new(false)),
+            failure_threshold,
+            success_threshold,
+            open_duration,
+        }
+    }
+
+    /// Returns the current state.
+    pub fn state(&self) -> CircuitState {
+        *self.lock()
+    }
+
+    /// Returns an error if the circuit is open. Moves an open circuit to
+    /// half open once the open duration has passed. While half open only one
+    /// trial request is allowed until its outcome is recorded, concurrent
+    /// requests are rejected as if the circuit was open.
+    pub fn allow_request(&self) -> OpenAiResult<()> {
+        let mut state = self.lock();
+        match *state {
+            CircuitState::Closed { .. } => return Ok(()),
+            CircuitState::Open { opened_at } if opened_at.elapsed() < self.open_duration => {
+                return Err(OpenAiError::CircuitOpen);
+            }
+            CircuitState::Open { .. } => *state = CircuitState::HalfOpen { successes: 0 },
+            CircuitState::HalfOpen { .. } if self.probing.load(Ordering::SeqCst) => {
+                return Err(OpenAiError::CircuitOpen);
+            }
+            CircuitState::HalfOpen { .. } => {}
+        }
+        self.probing.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Records a successful request.
+    pub fn record_success(&self) {
+        let mut state = self.lock();
+        self.probing.store(false, Ordering::SeqCst);
+        *state = match *state {
+            CircuitState::HalfOpen { successes } if successes + 1 < self.success_threshold => {
+                CircuitState::HalfOpen {
+                    successes: successes + 1,
+                }
+            }
+            CircuitState::Open { opened_at } => CircuitState::Open { opened_at },
+            _ => CircuitState::Closed { failures: 0 },
+        };
+    }
+
+    /// Records a failed request.
+    pub fn record_failure(&self) {
+        let mut state = self.lock();
+        self.probing.store(false, Ordering::SeqCst);
+        *state = match *state {
+            CircuitState::Closed { failures } if failures + 1 < self.failure_threshold => {
+                CircuitState::Closed {
+                    failures: failures + 1,
+                }
+            }
+            CircuitState::Open { opened_at } => CircuitState::Open { opened_at },
+            _ => CircuitState::Open {
+                opened_at: Instant::now(),
+            },
+        };
+    }
+
+    /// Runs the request if the circuit allows it and records the outcome.
+    /// Only errors indicating an unavailable Api count as failures, errors
+    /// caused by the request itself do not.
+    pub async fn call<T, F>(&self, request: F) -> OpenAiResult<T>
+    where
+        F: Future<Output = OpenAiResult<T>>,
+    {
+        self.allow_request()?;
+        let mut probe = ProbeGuard {
+            breaker: self,
+            armed: true,
+        };
+        let result = request.await;
+        probe.armed = false;
+        match &result {
+            Err(err) if is_unavailable_error(err) => self.record_failure(),
+            _ => self.record_success(),
+        }
+        result
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CircuitState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Releases the half open trial slot if a request is dropped before its
+/// outcome was recorded, so a cancelled trial does not block the circuit.
+struct ProbeGuard<'a> {
+    breaker: &'a CircuitBreaker,
+    armed: bool,
+}
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            let _state = self.breaker.lock();
+            self.breaker.probing.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+fn is_unavailable_error(err: &OpenAiError) -> bool {
+    match err {
+        OpenAiError::HttpError(_)
+        | OpenAiError::TimeoutError(_)
+        | OpenAiError::HttpStatus { .. } => true,
+        OpenAiError::ApiErrorResponse(details) => details.r#type() == Some("server_error"),
+        _ => false,
+    }
+}
+
+/// A [ClientApi] that guards all requests of the wrapped client with a
+/// [CircuitBreaker].
+pub struct CircuitBreakerClient<C: ClientApi> {
+    inner: C,
+    breaker: CircuitBreaker,
+}
+
+impl<C: ClientApi> CircuitBreakerClient<C> {
+    pub fn new(inner: C, breaker: CircuitBreaker) -> Self {
+        CircuitBreakerClient { inner, breaker }
+    }
+
+    /// Returns the circuit breaker guarding the client.
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+}
+
+#[async_trait]
+impl<C: ClientApi> ClientApi for CircuitBreakerClient<C> {
+    async fn create_completion(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<TextResult>> {
+        self.breaker
+            .call(self.inner.create_completion(request))
+            .await
+    }
+
+    async fn create_completion_stream(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<StreamChunk>>> {
+        self.breaker
+            .call(self.inner.create_completion_stream(request))
+            .await
+    }
+
+    async fn create_chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<ChatCompletionResponse>> {
+        self.breaker
+            .call(self.inner.create_chat_completion(request))
+            .await
+    }
+
+    async fn create_chat_completion_stream(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<ChatStreamChunk>>> {
+        self.breaker
+            .call(self.inner.create_chat_completion_stream(request))
+            .await
+    }
+
+    async fn create_edit(&self, request: EditRequest) -> OpenAiResult<ApiResponse<TextResult>> {
+        self.breaker.call(self.inner.create_edit(request)).await
+    }
+
+    async fn create_embedding(
+        &self,
+        request: EmbeddingRequest,
+    ) -> OpenAiResult<ApiResponse<EmbeddingResponse>> {
+        self.breaker
+            .call(self.inner.create_embedding(request))
+            .await
+    }
+
+    async fn create_moderation(
+        &self,
+        request: ModerationRequest,
+    ) -> OpenAiResult<ApiResponse<ModerationResponse>> {
+        self.breaker
+            .call(self.inner.create_moderation(request))
+            .await
+    }
+
+    async fn create_speech(&self, request: SpeechRequest) -> OpenAiResult<ApiResponse<Bytes>> {
+        self.breaker.call(self.inner.create_speech(request)).await
+    }
+
+    async fn transcribe_audio(
+        &self,
+        request: TranscriptionRequest,
+    ) -> OpenAiResult<ApiResponse<TranscriptionResponse>> {
+        self.breaker
+            .call(self.inner.transcribe_audio(request))
+            .await
+    }
+
+    async fn translate_audio(
+        &self,
+        request: TranslationRequest,
+    ) -> OpenAiResult<ApiResponse<TranslationResponse>> {
+        self.breaker.call(self.inner.translate_audio(request)).await
+    }
+
+    async fn upload_file(
+        &self,
+        request: UploadFileRequest,
+    ) -> OpenAiResult<ApiResponse<FileObject>> {
+        self.breaker.call(self.inner.upload_file(request)).await
+    }
+
+    async fn list_files(&self) -> OpenAiResult<ApiResponse<FileList>> {
+        self.breaker.call(self.inner.list_files()).await
+    }
+
+    async fn retrieve_file(&self, id: &str) -> OpenAiResult<ApiResponse<FileObject>> {
+        self.breaker.call(self.inner.retrieve_file(id)).await
+    }
+
+    async fn delete_file(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.breaker.call(self.inner.delete_file(id)).await
+    }
+
+    async fn retrieve_file_content(&self, id: &str) -> OpenAiResult<ApiResponse<Bytes>> {
+        self.breaker
+            .call(self.inner.retrieve_file_content(id))
+            .await
+    }
+
+    async fn create_fine_tuning_job(
+        &self,
+        request: CreateFineTuningJobRequest,
+    ) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.breaker
+            .call(self.inner.create_fine_tuning_job(request))
+            .await
+    }
+
+    async fn list_fine_tuning_jobs(&self) -> OpenAiResult<ApiResponse<FineTuningJobList>> {
+        self.breaker.call(self.inner.list_fine_tuning_jobs()).await
+    }
+
+    async fn retrieve_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.breaker
+            .call(self.inner.retrieve_fine_tuning_job(id))
+            .await
+    }
+
+    async fn cancel_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.breaker
+            .call(self.inner.cancel_fine_tuning_job(id))
+            .await
+    }
+
+    async fn list_fine_tuning_events(
+        &self,
+        id: &str,
+        after: Option<&str>,
+    ) -> OpenAiResult<ApiResponse<FineTuningEventList>> {
+        self.breaker
+            .call(self.inner.list_fine_tuning_events(id, after))
+            .await
+    }
+
+    async fn create_assistant(
+        &self,
+        request: CreateAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.breaker
+            .call(self.inner.create_assistant(request))
+            .await
+    }
+
+    async fn retrieve_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.breaker.call(self.inner.retrieve_assistant(id)).await
+    }
+
+    async fn modify_assistant(
+        &self,
+        id: &str,
+        request: ModifyAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.breaker
+            .call(self.inner.modify_assistant(id, request))
+            .await
+    }
+
+    async fn delete_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.breaker.call(self.inner.delete_assistant(id)).await
+    }
+
+    async fn list_assistants(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Assistant>>> {
+        self.breaker.call(self.inner.list_assistants(params)).await
+    }
+
+    async fn create_thread(
+        &self,
+        request: CreateThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>> {
+        self.breaker.call(self.inner.create_thread(request)).await
+    }
+
+    async fn retrieve_thread(&self, id: &str) -> OpenAiResult<ApiResponse<Thread>> {
+        self.breaker.call(self.inner.retrieve_thread(id)).await
+    }
+
+    async fn modify_thread(
+        &self,
+        id: &str,
+        request: ModifyThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>> {
+        self.breaker
+            .call(self.inner.modify_thread(id, request))
+            .await
+    }
+
+    async fn delete_thread(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.breaker.call(self.inner.delete_thread(id)).await
+    }
+
+    async fn create_message(
+        &self,
+        thread_id: &str,
+        request: CreateMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.breaker
+            .call(self.inner.create_message(thread_id, request))
+            .await
+    }
+
+    async fn list_messages(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<ThreadMessageObject>>> {
+        self.breaker
+            .call(self.inner.list_messages(thread_id, params))
+            .await
+    }
+
+    async fn retrieve_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.breaker
+            .call(self.inner.retrieve_message(thread_id, message_id))
+            .await
+    }
+
+    async fn modify_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+        request: ModifyMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.breaker
+            .call(self.inner.modify_message(thread_id, message_id, request))
+            .await
+    }
+
+    async fn delete_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.breaker
+            .call(self.inner.delete_message(thread_id, message_id))
+            .await
+    }
+
+    async fn create_run(
+        &self,
+        thread_id: &str,
+        request: CreateRunRequest,
+    ) -> OpenAiResult<ApiResponse<Run>> {
+        self.breaker
+            .call(self.inner.create_run(thread_id, request))
+            .await
+    }
+
+    async fn retrieve_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>> {
+        self.breaker
+            .call(self.inner.retrieve_run(thread_id, run_id))
+            .await
+    }
+
+    async fn list_runs(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Run>>> {
+        self.breaker
+            .call(self.inner.list_runs(thread_id, params))
+            .await
+    }
+
+    async fn cancel_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>> {
+        self.breaker
+            .call(self.inner.cancel_run(thread_id, run_id))
+            .await
+    }
+
+    async fn submit_tool_outputs(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        request: SubmitToolOutputsRequest,
+    ) -> OpenAiResult<ApiResponse<Run>> {
+        self.breaker
+            .call(self.inner.submit_tool_outputs(thread_id, run_id, request))
+            .await
+    }
+
+    async fn create_batch(&self, request: CreateBatchRequest) -> OpenAiResult<ApiResponse<Batch>> {
+        self.breaker.call(self.inner.create_batch(request)).await
+    }
+
+    async fn retrieve_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>> {
+        self.breaker.call(self.inner.retrieve_batch(id)).await
+    }
+
+    async fn cancel_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>> {
+        self.breaker.call(self.inner.cancel_batch(id)).await
+    }
+
+    async fn list_batches(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Batch>>> {
+        self.breaker.call(self.inner.list_batches(params)).await
+    }
+
+    async fn get_models(&self) -> OpenAiResult<ApiResponse<OpenAiModelResponse>> {
+        self.breaker.call(self.inner.get_models()).await
+    }
+
+    async fn get_model(&self, model: &str) -> OpenAiResult<ApiResponse<OpenAiModel>> {
+        self.breaker.call(self.inner.get_model(model)).await
+    }
+
+    async fn create_image(
+        &self,
+        request: CreateImageRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        self.breaker.call(self.inner.create_image(request)).await
+    }
+
+    async fn edit_image(
+        &self,
+        request: EditImageRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        self.breaker.call(self.inner.edit_image(request)).await
+    }
+
+    async fn create_image_variation(
+        &self,
+        request: ImageVariationRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        self.breaker
+            .call(self.inner.create_image_variation(request))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod breaker {
+    use super::*;
+    use crate::client::request_client::create_test_server_config;
+    use crate::OpenAiClient;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    fn unavailable() -> OpenAiResult<()> {
+        Err(OpenAiError::HttpStatus {
+            status: 503,
+            body: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn should_open_after_failure_threshold() {
+        let breaker = CircuitBreaker::new(2, 1, Duration::from_secs(60));
+        assert!(breaker.call(async { unavailable() }).await.is_err());
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 1 });
+        assert!(breaker.call(async { unavailable() }).await.is_err());
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+        match breaker.call(async { Ok(()) }).await {
+            Err(OpenAiError::CircuitOpen) => {}
+            _ => panic!("expected circuit open error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_reset_failures_on_success() {
+        let breaker = CircuitBreaker::new(2, 1, Duration::from_secs(60));
+        assert!(breaker.call(async { unavailable() }).await.is_err());
+        assert!(breaker.call(async { Ok(()) }).await.is_ok());
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 0 });
+    }
+
+    #[tokio::test]
+    async fn should_not_count_request_errors_as_failures() {
+        let breaker = CircuitBreaker::new(1, 1, Duration::from_secs(60));
+        let res: OpenAiResult<()> = breaker
+            .call(async { Err(OpenAiError::invalid_api_key()) })
+            .await;
+        assert!(res.is_err());
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 0 });
+    }
+
+    #[tokio::test]
+    async fn should_close_after_successful_half_open_requests() {
+        let breaker = CircuitBreaker::new(1, 2, Duration::ZERO);
+        assert!(breaker.call(async { unavailable() }).await.is_err());
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+        assert!(breaker.call(async { Ok(()) }).await.is_ok());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen { successes: 1 });
+        assert!(breaker.call(async { Ok(()) }).await.is_ok());
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 0 });
+    }
+
+    #[tokio::test]
+    async fn should_reopen_on_half_open_failure() {
+        let breaker = CircuitBreaker::new(1, 2, Duration::ZERO);
+        assert!(breaker.call(async { unavailable() }).await.is_err());
+        breaker.allow_request().unwrap();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen { successes: 0 });
+        breaker.record_failure();
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+        assert!(breaker.call(async { unavailable() }).await.is_err());
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+    }
+
+    #[tokio::test]
+    async fn should_allow_single_half_open_trial_request() {
+        let breaker = CircuitBreaker::new(1, 2, Duration::ZERO);
+        assert!(breaker.call(async { unavailable() }).await.is_err());
+        breaker.allow_request().unwrap();
+        match breaker.call(async { Ok(()) }).await {
+            Err(OpenAiError::CircuitOpen) => {}
+            _ => panic!("expected circuit open error"),
+        }
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen { successes: 1 });
+        assert!(breaker.call(async { Ok(()) }).await.is_ok());
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 0 });
+    }
+
+    #[tokio::test]
+    async fn should_release_trial_of_cancelled_request() {
+        let breaker = CircuitBreaker::new(1, 1, Duration::ZERO);
+        assert!(breaker.call(async { unavailable() }).await.is_err());
+        let pending = breaker.call(futures::future::pending::<OpenAiResult<()>>());
+        assert!(tokio::time::timeout(Duration::from_millis(10), pending)
+            .await
+            .is_err());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen { successes: 0 });
+        assert!(breaker.call(async { Ok(()) }).await.is_ok());
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 0 });
+    }
+
+    #[tokio::test]
+    async fn client_should_fail_fast_when_open() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(ResponseTemplate::new(503).set_body_raw("unavailable", "text/plain"))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = CircuitBreakerClient::new(
+            OpenAiClient::new(config),
+            CircuitBreaker::new(2, 1, Duration::from_secs(60)),
+        );
+        assert!(client.get_models().await.is_err());
+        assert!(client.get_models().await.is_err());
+        match client.get_models().await {
+            Err(OpenAiError::CircuitOpen) => {}
+            _ => panic!("expected circuit open error"),
+        }
+    }
+}
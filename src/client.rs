@@ -1,209 +1,3607 @@
+use crate::context::{check_context_window, map_context_window_error};
+use crate::rate_limit::RateLimitInfo;
+use crate::retry::{is_retryable, retry_after};
+use crate::streaming::parse_sse_stream;
+use crate::trace;
 use crate::types::TextResult;
-use crate::OpenAiError::{ApiErrorResponse, UnexpectedJsonResponse};
 use crate::{
-    ClientApi, CompletionRequest, CreateImageRequest, EditRequest, ImageResult, OpenAiConfig,
-    OpenAiModel, OpenAiModelResponse, OpenAiResponse, OpenAiResult,
+    ApiCompatibilityReport, ApiResponse, Assistant, AuthScheme, Batch, ChatCompletionRequest,
+    ChatCompletionResponse, ChatStreamChunk, ClientApi, CompletionRequest, ConfigDiagnostics,
+    CreateAssistantRequest, CreateBatchRequest, CreateFineTuningJobRequest, CreateImageRequest,
+    CreateMessageRequest, CreateRunRequest, CreateThreadRequest, DeleteResponse, EditImageRequest,
+    EditRequest, EmbeddingRequest, EmbeddingResponse, EndpointStatus, FileList, FileObject,
+    FineTuningEventList, FineTuningJob, FineTuningJobList, ImageResult, ImageVariationRequest,
+    ListParams, ListResponse, ModerationRequest, ModerationResponse, ModifyAssistantRequest,
+    ModifyMessageRequest, ModifyThreadRequest, OpenAiConfig, OpenAiError, OpenAiErrorResponse,
+    OpenAiModel, OpenAiModelResponse, OpenAiResponse, OpenAiResult, OpenAiStream,
+    RotatingTokenProvider, Run, SpeechRequest, StreamChunk, SubmitToolOutputsRequest, Thread,
+    ThreadMessageObject, TranscriptionFormat, TranscriptionRequest, TranscriptionResponse,
+    TranslationRequest, TranslationResponse, UploadFileRequest,
 };
 use async_trait::async_trait;
-use reqwest::Client;
+use bytes::Bytes;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::multipart::Form;
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-#[derive(Default)]
+const BETA_HEADER: &str = "OpenAI-Beta";
+const ORGANIZATION_HEADER: &str = "OpenAI-Organization";
+const PROJECT_HEADER: &str = "OpenAI-Project";
+const API_KEY_HEADER: &str = "api-key";
+const ASSISTANTS_BETA: &str = "assistants=v2";
+const RATE_LIMIT_EXCEEDED: &str = "rate_limit_exceeded";
+
+/// Http client for the OpenAi Api. Cloning is cheap as the underlying
+/// connection pool is shared between clones.
+#[derive(Clone)]
 pub struct OpenAiClient {
     config: OpenAiConfig,
     client: Client,
+    timeout: Option<Duration>,
+    rate_limit: Arc<Mutex<Option<RateLimitInfo>>>,
+}
+
+impl OpenAiClient {
+    /// Creates a client for the given config, either an [OpenAiConfig] or
+    /// an [crate::AzureOpenAiConfig].
+    pub fn new(config: impl Into<OpenAiConfig>) -> Self {
+        let config = config.into();
+        let mut builder = Client::builder().user_agent(config.get_user_agent());
+        if let Some(timeout) = config.get_timeout() {
+            builder = builder.timeout(timeout);
+        }
+        let client = builder
+            .build()
+            .expect("http client could not be initialized");
+        OpenAiClient {
+            config,
+            client,
+            timeout: None,
+            rate_limit: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns the rate limit state of the latest response received by this
+    /// client or its clones.
+    pub fn last_rate_limit(&self) -> Option<RateLimitInfo> {
+        self.rate_limit
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Returns a client sharing the connection pool of this client that
+    /// applies the given timeout to all its requests, overriding the
+    /// timeout of the config.
+    ///
+    /// ```rust,no_run
+    /// # use openai_client::*;
+    /// # use std::time::Duration;
+    /// # async fn run(request: CompletionRequest) -> OpenAiResult<()> {
+    /// let client = OpenAiClient::new(OpenAiConfig::default().timeout(Duration::from_secs(30)));
+    /// let result = client
+    ///     .with_timeout(Duration::from_secs(120))
+    ///     .create_completion(request)
+    ///     .await?;
+    /// # Ok(())}
+    /// ```
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        OpenAiClient {
+            timeout: Some(timeout),
+            ..self.clone()
+        }
+    }
+
+    /// Creates a client that re-reads the access token from env
+    /// OPENAI_API_KEY on every request.
+    pub fn from_env_rotating() -> Self {
+        OpenAiClient::new(OpenAiConfig::default().token_provider(RotatingTokenProvider::new()))
+    }
+
+    /// Fetches the list of all models and the details of a specific model
+    /// concurrently.
+    pub async fn get_models_with_details(
+        &self,
+        model_id: &str,
+    ) -> OpenAiResult<(OpenAiModelResponse, OpenAiModel)> {
+        let (models, model) = tokio::join!(self.get_models(), self.get_model(model_id));
+        Ok((models?.into_data(), model?.into_data()))
+    }
+
+    /// Creates a completion and retries once with the configured fallback
+    /// model if the requested model is not found.
+    pub async fn create_completion_with_fallback(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<TextResult>> {
+        match self.create_completion(request.clone()).await {
+            Err(err) if err.is_model_not_found() => {
+                let fallback = self.config.get_model_fallback(&request.model).ok_or(
+                    OpenAiError::NoFallbackAvailable {
+                        model: request.model.clone(),
+                    },
+                )?;
+                let request = CompletionRequest {
+                    model: fallback.to_string(),
+                    ..request
+                };
+                self.create_completion(request).await
+            }
+            result => result,
+        }
+    }
+
+    /// Creates a completion and retries up to `max_retries` times if the
+    /// response contains no text. Returns [OpenAiError::EmptyResponse] if all
+    /// attempts produced empty results.
+    pub async fn create_completion_retry_on_empty(
+        &self,
+        request: CompletionRequest,
+        max_retries: u32,
+    ) -> OpenAiResult<ApiResponse<TextResult>> {
+        for _ in 0..=max_retries {
+            let result = self.create_completion(request.clone()).await?;
+            if result.has_text() {
+                return Ok(result);
+            }
+        }
+        Err(OpenAiError::EmptyResponse {
+            attempts: max_retries + 1,
+        })
+    }
+
+    /// Creates a completion after checking the estimated prompt tokens plus
+    /// max_tokens against the context window of the model. Fails with
+    /// [OpenAiError::ContextWindowExceeded] without sending the request if
+    /// the window would be exceeded. An unset max_tokens is left to the Api
+    /// default.
+    pub async fn create_completion_with_context_check(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<TextResult>> {
+        let request = match self.config.get_completion_defaults() {
+            Some(defaults) => defaults.apply(request),
+            None => request,
+        };
+        self.create_completion(check_context_window(request)?).await
+    }
+
+    /// Checks connectivity and authentication by listing the models and
+    /// returns the round trip duration. Note that this counts against the
+    /// rate limits like any other request.
+    pub async fn ping(&self) -> OpenAiResult<Duration> {
+        let start = Instant::now();
+        self.get_models().await?;
+        Ok(start.elapsed())
+    }
+
+    /// Returns the config diagnostics including the result of a ping.
+    pub async fn self_test(&self) -> OpenAiResult<ConfigDiagnostics> {
+        let ping = self.ping().await?;
+        Ok(ConfigDiagnostics {
+            ping_ms: Some(ping.as_millis() as u64),
+            ..self.config.diagnostics()
+        })
+    }
+
+    /// Probes the Api for optional endpoints. This is useful for OpenAi
+    /// compatible Apis that only implement a subset of the endpoints. An
+    /// endpoint is considered unsupported if it responds with 404 or 405.
+    /// Authentication failures and server errors are returned as error, they
+    /// tell nothing about the supported endpoints.
+    pub async fn check_api_compatibility(&self) -> OpenAiResult<ApiCompatibilityReport> {
+        let (chat, embeddings, fine_tuning, assistants) = tokio::join!(
+            self.probe_endpoint(Method::POST, "chat/completions"),
+            self.probe_endpoint(Method::POST, "embeddings"),
+            self.probe_endpoint(Method::GET, "fine_tuning/jobs"),
+            self.probe_endpoint(Method::GET, "assistants"),
+        );
+        Ok(ApiCompatibilityReport {
+            version: self.config.get_version().to_string(),
+            supports_chat: chat?,
+            supports_embeddings: embeddings?,
+            supports_fine_tuning: fine_tuning?,
+            supports_assistants: assistants?,
+        })
+    }
+
+    /// Sends a request to the endpoint and reports it reachable if the Api
+    /// answered with neither a server error, an authentication error nor
+    /// an unknown endpoint.
+    pub(crate) async fn probe_connectivity(
+        &self,
+        method: Method,
+        endpoint: &str,
+    ) -> EndpointStatus {
+        let start = Instant::now();
+        let request = match self.request(method.clone(), endpoint) {
+            Ok(request) if method == Method::POST => request.json(&serde_json::json!({})),
+            Ok(request) => request,
+            Err(err) => {
+                return EndpointStatus::Unreachable {
+                    reason: err.to_string(),
+                }
+            }
+        };
+        match request.send().await {
+            Ok(response) => {
+                let status = response.status();
+                if status.is_server_error()
+                    || status == StatusCode::UNAUTHORIZED
+                    || status == StatusCode::FORBIDDEN
+                    || status == StatusCode::NOT_FOUND
+                    || status == StatusCode::METHOD_NOT_ALLOWED
+                {
+                    EndpointStatus::Unreachable {
+                        reason: format!("status {}", status),
+                    }
+                } else {
+                    EndpointStatus::Reachable {
+                        latency_ms: start.elapsed().as_millis() as u64,
+                    }
+                }
+            }
+            Err(err) => EndpointStatus::Unreachable {
+                reason: err.to_string(),
+            },
+        }
+    }
+
+    async fn probe_endpoint(&self, method: Method, path: &str) -> OpenAiResult<bool> {
+        let mut request = self.request(method.clone(), &self.config.get_version_path(path))?;
+        if method == Method::POST {
+            request = request.json(&serde_json::json!({}));
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_server_error()
+            || status == StatusCode::UNAUTHORIZED
+            || status == StatusCode::FORBIDDEN
+        {
+            return Err(self.parse_error(response).await);
+        }
+        Ok(status != StatusCode::NOT_FOUND && status != StatusCode::METHOD_NOT_ALLOWED)
+    }
+
+    fn request(&self, method: Method, endpoint: &str) -> OpenAiResult<RequestBuilder> {
+        self.request_url(method, self.config.api_url(endpoint))
+    }
+
+    fn request_url(&self, method: Method, url: String) -> OpenAiResult<RequestBuilder> {
+        let token = self.config.get_access_token()?;
+        let mut builder = match self.config.get_auth_scheme() {
+            AuthScheme::Bearer => self
+                .client
+                .request(method, url)
+                .header("Authorization", format!("Bearer {}", token)),
+            AuthScheme::ApiKey => self
+                .client
+                .request(method, url)
+                .header(API_KEY_HEADER, token),
+        };
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if !self.config.get_query_params().is_empty() {
+            builder = builder.query(self.config.get_query_params());
+        }
+        if let Some(organization) = self.config.get_organization() {
+            builder = builder.header(ORGANIZATION_HEADER, organization);
+        }
+        if let Some(project) = self.config.get_project() {
+            builder = builder.header(PROJECT_HEADER, project);
+        }
+        Ok(builder)
+    }
+
+    /// Creates a request for the Assistants Api, which requires a beta header.
+    fn beta_request(&self, method: Method, endpoint: &str) -> OpenAiResult<RequestBuilder> {
+        Ok(self
+            .request(method, endpoint)?
+            .header(BETA_HEADER, ASSISTANTS_BETA))
+    }
+
+    /// Sends the request, retrying rate limited and failed requests if
+    /// configured. Rate limited responses are returned as
+    /// [OpenAiError::RateLimitError] once no retries are left, other 429
+    /// errors like an exceeded quota are returned right away.
+    async fn execute(&self, builder: RequestBuilder) -> OpenAiResult<Response> {
+        self.execute_with(builder, || None).await
+    }
+
+    /// Like [OpenAiClient::execute], requests with a body that can't be
+    /// cloned like multipart forms are recreated by `rebuild` for retries.
+    async fn execute_with(
+        &self,
+        builder: RequestBuilder,
+        rebuild: impl Fn() -> Option<RequestBuilder>,
+    ) -> OpenAiResult<Response> {
+        let retry = self.config.get_retry();
+        let mut attempt = 0;
+        loop {
+            let retry = retry.filter(|r| attempt < r.max_retries);
+            let request = retry.and_then(|_| builder.try_clone().or_else(&rebuild));
+            let response = match request {
+                Some(request) => self.record_rate_limit(request.send().await?),
+                None => {
+                    let response = self.record_rate_limit(builder.send().await?);
+                    trace::response_status(response.status());
+                    return self.check_rate_limit(response).await;
+                }
+            };
+            trace::response_status(response.status());
+            let Some(retry) = retry.filter(|_| is_retryable(response.status())) else {
+                return Ok(response);
+            };
+            let delay = retry.delay(&response, attempt);
+            if let Err(err) = self.check_rate_limit(response).await {
+                if !matches!(err, OpenAiError::RateLimitError { .. }) {
+                    return Err(err);
+                }
+            }
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Maps rate limited responses to [OpenAiError::RateLimitError]. Other
+    /// errors sent with a 429, e.g. `insufficient_quota`, are returned as
+    /// [OpenAiError::ApiErrorResponse].
+    async fn check_rate_limit(&self, response: Response) -> OpenAiResult<Response> {
+        if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response);
+        }
+        let retry_after = retry_after(&response);
+        let rate_limit = RateLimitInfo::from_headers(response.headers()).map(Box::new);
+        let details = match self.parse_error(response).await {
+            OpenAiError::ApiErrorResponse(details)
+                if details.code.as_deref() != Some(RATE_LIMIT_EXCEEDED) =>
+            {
+                return Err(OpenAiError::ApiErrorResponse(details));
+            }
+            OpenAiError::ApiErrorResponse(details) => Some(details),
+            _ => None,
+        };
+        Err(OpenAiError::RateLimitError {
+            retry_after,
+            details,
+            rate_limit,
+        })
+    }
+
+    fn record_rate_limit(&self, response: Response) -> Response {
+        if let Some(info) = RateLimitInfo::from_headers(response.headers()) {
+            *self.rate_limit.lock().unwrap_or_else(|e| e.into_inner()) = Some(info);
+        }
+        response
+    }
+
+    async fn send<T>(&self, builder: RequestBuilder) -> OpenAiResult<ApiResponse<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let endpoint = trace::endpoint(&builder);
+        trace::traced(&endpoint, async {
+            let response = self.execute(builder).await?;
+            self.parse_api_response(response).await
+        })
+        .await
+    }
+
+    async fn get_request<T>(&self, endpoint: &str) -> OpenAiResult<ApiResponse<T>>
+    where
+        T: DeserializeOwned,
+    {
+        trace::traced(endpoint, async {
+            let response = self.execute(self.request(Method::GET, endpoint)?).await?;
+            self.parse_api_response(response).await
+        })
+        .await
+    }
+
+    async fn get_request_with_params<T>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> OpenAiResult<ApiResponse<T>>
+    where
+        T: DeserializeOwned,
+    {
+        trace::traced(endpoint, async {
+            let url = self.config.api_url_with_params(endpoint, params);
+            let response = self.execute(self.request_url(Method::GET, url)?).await?;
+            self.parse_api_response(response).await
+        })
+        .await
+    }
+
+    async fn post_empty_request<T>(&self, endpoint: &str) -> OpenAiResult<ApiResponse<T>>
+    where
+        T: DeserializeOwned,
+    {
+        trace::traced(endpoint, async {
+            let response = self.execute(self.request(Method::POST, endpoint)?).await?;
+            self.parse_api_response(response).await
+        })
+        .await
+    }
+
+    async fn post_request<R, T>(&self, endpoint: &str, body: R) -> OpenAiResult<ApiResponse<T>>
+    where
+        T: DeserializeOwned,
+        R: Serialize,
+    {
+        trace::traced(endpoint, async {
+            trace::request_body(&body);
+            let response = self
+                .execute(self.request(Method::POST, endpoint)?.json(&body))
+                .await?;
+            self.parse_api_response(response).await
+        })
+        .await
+    }
+
+    /// Sends the request and returns the server sent events of the response
+    /// as stream. Json responses, e.g. errors, are returned as error.
+    async fn post_stream_request<R, T>(
+        &self,
+        endpoint: &str,
+        body: R,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<T>>>
+    where
+        T: DeserializeOwned + Send + 'static,
+        R: Serialize,
+    {
+        let response = trace::traced(endpoint, async {
+            trace::request_body(&body);
+            self.execute(self.request(Method::POST, endpoint)?.json(&body))
+                .await
+        })
+        .await?;
+        let is_event_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("event-stream"));
+        if is_event_stream {
+            let rate_limit = RateLimitInfo::from_headers(response.headers());
+            let stream = parse_sse_stream(response, self.config.get_max_response_body_bytes());
+            return Ok(ApiResponse::new(stream, rate_limit));
+        }
+        Err(self.parse_error(response).await)
+    }
+
+    /// Sends the request and returns the raw body of a successful response.
+    async fn post_bytes_request<R>(
+        &self,
+        endpoint: &str,
+        body: R,
+    ) -> OpenAiResult<ApiResponse<Bytes>>
+    where
+        R: Serialize,
+    {
+        trace::traced(endpoint, async {
+            trace::request_body(&body);
+            let response = self
+                .execute(self.request(Method::POST, endpoint)?.json(&body))
+                .await?;
+            self.read_bytes(response, self.config.get_max_response_body_bytes())
+                .await
+        })
+        .await
+    }
+
+    async fn delete_request<T>(&self, endpoint: &str) -> OpenAiResult<ApiResponse<T>>
+    where
+        T: DeserializeOwned,
+    {
+        trace::traced(endpoint, async {
+            let response = self
+                .execute(self.request(Method::DELETE, endpoint)?)
+                .await?;
+            self.parse_api_response(response).await
+        })
+        .await
+    }
+
+    /// Reads the raw body of a successful response of at most `limit` bytes.
+    async fn read_bytes(
+        &self,
+        response: Response,
+        limit: Option<usize>,
+    ) -> OpenAiResult<ApiResponse<Bytes>> {
+        if !response.status().is_success() {
+            return Err(self.parse_error(response).await);
+        }
+        let rate_limit = RateLimitInfo::from_headers(response.headers());
+        let bytes = match limit {
+            Some(limit) => Bytes::from(read_body(response, limit).await?),
+            None => response.bytes().await?,
+        };
+        Ok(ApiResponse::new(bytes, rate_limit))
+    }
+
+    /// Sends the form created by `form`, which is called again for every
+    /// retry as multipart bodies can't be cloned.
+    async fn post_multipart(
+        &self,
+        endpoint: &str,
+        form: impl Fn() -> Form,
+    ) -> OpenAiResult<Response> {
+        let builder = self.request(Method::POST, endpoint)?.multipart(form());
+        let rebuild = || {
+            self.request(Method::POST, endpoint)
+                .ok()
+                .map(|b| b.multipart(form()))
+        };
+        trace::traced(endpoint, self.execute_with(builder, rebuild)).await
+    }
+
+    /// Sends an audio form and parses the response according to the requested
+    /// format.
+    async fn post_audio_request(
+        &self,
+        endpoint: &str,
+        form: impl Fn() -> Form,
+        format: Option<TranscriptionFormat>,
+    ) -> OpenAiResult<ApiResponse<TranscriptionResponse>> {
+        let response = self.post_multipart(endpoint, form).await?;
+        if format.is_some_and(|f| f.is_plain_text()) {
+            let rate_limit = RateLimitInfo::from_headers(response.headers());
+            let text = self.read_text(response).await?;
+            return Ok(ApiResponse::new(
+                TranscriptionResponse::from_text(text),
+                rate_limit,
+            ));
+        }
+        let response: ApiResponse<OpenAiResponse<_>> = self.parse_api_response(response).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    /// Reads the body of a successful plain text response.
+    async fn read_text(&self, response: Response) -> OpenAiResult<String> {
+        if !response.status().is_success() {
+            return Err(self.parse_error(response).await);
+        }
+        match self.config.get_max_response_body_bytes() {
+            Some(limit) => {
+                Ok(String::from_utf8_lossy(&read_body(response, limit).await?).into_owned())
+            }
+            None => Ok(response.text().await?),
+        }
+    }
+
+    /// Parses the body of a response that is not the expected payload.
+    async fn parse_error(&self, response: Response) -> OpenAiError {
+        let value: Value = match self.parse_response(response).await {
+            Ok(value) => value,
+            Err(err) => return err,
+        };
+        match serde_json::from_value::<OpenAiErrorResponse>(value.clone()) {
+            Ok(err) => OpenAiError::ApiErrorResponse(err.error),
+            Err(_) => OpenAiError::UnexpectedJsonResponse(value),
+        }
+    }
+
+    /// Parses the json response body together with the rate limit headers.
+    async fn parse_api_response<T>(&self, response: Response) -> OpenAiResult<ApiResponse<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let rate_limit = RateLimitInfo::from_headers(response.headers());
+        let data = self.parse_response(response).await?;
+        Ok(ApiResponse::new(data, rate_limit))
+    }
+
+    /// Parses the json response body. Responses declaring a non json content
+    /// type, e.g. plain text errors from proxies, are returned as
+    /// [OpenAiError::HttpStatus] with the body text.
+    async fn parse_response<T>(&self, response: Response) -> OpenAiResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        let is_json = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.contains("json"));
+        let limit = self.config.get_max_response_body_bytes();
+        if let Some(false) = is_json {
+            let status = response.status().as_u16();
+            let body = match limit {
+                Some(limit) => read_body(response, limit)
+                    .await
+                    .ok()
+                    .map(|b| String::from_utf8_lossy(&b).into_owned()),
+                None => response.text().await.ok(),
+            };
+            let body = body.filter(|b| !b.is_empty());
+            return Err(OpenAiError::HttpStatus { status, body });
+        }
+        if cfg!(feature = "tracing") {
+            let value: Value = read_json(response, limit).await?;
+            trace::response_body(&value);
+            Ok(serde_json::from_value(value)?)
+        } else {
+            read_json(response, limit).await
+        }
+    }
+}
+
+/// Reads the json response body, enforcing the optional body size limit.
+async fn read_json<T>(response: Response, limit: Option<usize>) -> OpenAiResult<T>
+where
+    T: DeserializeOwned,
+{
+    // A declared content length is enforced by the transport, so only
+    // bodies without one or exceeding the limit need to be read in chunks.
+    match limit {
+        Some(limit) if response.content_length().is_none_or(|l| l as usize > limit) => {
+            Ok(serde_json::from_slice(&read_body(response, limit).await?)?)
+        }
+        _ => Ok(response.json().await?),
+    }
+}
+
+/// Reads the response body, failing with [OpenAiError::ResponseTooLarge] as
+/// soon as the limit is exceeded.
+async fn read_body(mut response: Response, limit: usize) -> OpenAiResult<Vec<u8>> {
+    if let Some(length) = response.content_length() {
+        if length as usize > limit {
+            return Err(OpenAiError::ResponseTooLarge {
+                received: length as usize,
+                limit,
+            });
+        }
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if body.len() > limit {
+            return Err(OpenAiError::ResponseTooLarge {
+                received: body.len(),
+                limit,
+            });
+        }
+    }
+    Ok(body)
 }
 
-impl OpenAiClient {
-    pub fn new(config: OpenAiConfig) -> Self {
-        OpenAiClient {
-            config,
-            client: Client::new(),
-        }
+impl Default for OpenAiClient {
+    fn default() -> Self {
+        OpenAiClient::new(OpenAiConfig::default())
+    }
+}
+
+#[async_trait]
+impl ClientApi for OpenAiClient {
+    async fn create_completion(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<TextResult>> {
+        let request = match self.config.get_completion_defaults() {
+            Some(defaults) => defaults.apply(request),
+            None => request,
+        };
+        request.validate()?;
+        if request.stream == Some(true) {
+            return Err(OpenAiError::InvalidRequestParameter {
+                name: "stream".to_string(),
+                reason: "use create_completion_stream for streamed completions".to_string(),
+            });
+        }
+        #[cfg(feature = "tracing")]
+        if let Some(threshold) = self.config.get_cost_warning_threshold() {
+            let prompt_tokens = crate::context::estimate_prompt_tokens(&request);
+            if let Some(warning) = crate::warn_if_expensive(&request, prompt_tokens, threshold) {
+                tracing::warn!("{}", warning);
+            }
+        }
+        let model = request.model.clone();
+        let response: ApiResponse<OpenAiResponse<TextResult>> = self
+            .post_request(&self.config.get_completion_path(), request)
+            .await?;
+        response
+            .try_map(OpenAiResponse::into_result)
+            .map_err(|err| map_context_window_error(err, &model))
+    }
+
+    async fn create_completion_stream(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<StreamChunk>>> {
+        let request = match self.config.get_completion_defaults() {
+            Some(defaults) => defaults.apply(request),
+            None => request,
+        };
+        request.validate()?;
+        let request = CompletionRequest {
+            stream: Some(true),
+            ..request
+        };
+        self.post_stream_request(&self.config.get_completion_path(), request)
+            .await
+    }
+
+    async fn create_chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<ChatCompletionResponse>> {
+        request.validate()?;
+        if request.stream == Some(true) {
+            return Err(OpenAiError::InvalidRequestParameter {
+                name: "stream".to_string(),
+                reason: "use create_chat_completion_stream for streamed chat completions"
+                    .to_string(),
+            });
+        }
+        let model = request.model.clone();
+        let response: ApiResponse<OpenAiResponse<ChatCompletionResponse>> = self
+            .post_request(&self.config.get_chat_completion_path(), request)
+            .await?;
+        response
+            .try_map(OpenAiResponse::into_result)
+            .map_err(|err| map_context_window_error(err, &model))
+    }
+
+    async fn create_chat_completion_stream(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<ChatStreamChunk>>> {
+        request.validate()?;
+        let request = ChatCompletionRequest {
+            stream: Some(true),
+            ..request
+        };
+        self.post_stream_request(&self.config.get_chat_completion_path(), request)
+            .await
+    }
+
+    async fn create_edit(&self, request: EditRequest) -> OpenAiResult<ApiResponse<TextResult>> {
+        request.validate()?;
+        let response: ApiResponse<OpenAiResponse<TextResult>> = self
+            .post_request(&self.config.get_edit_path(), request)
+            .await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn create_embedding(
+        &self,
+        request: EmbeddingRequest,
+    ) -> OpenAiResult<ApiResponse<EmbeddingResponse>> {
+        let response: ApiResponse<OpenAiResponse<_>> = self
+            .post_request(&self.config.get_embedding_path(), request)
+            .await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn create_moderation(
+        &self,
+        request: ModerationRequest,
+    ) -> OpenAiResult<ApiResponse<ModerationResponse>> {
+        let response: ApiResponse<OpenAiResponse<_>> = self
+            .post_request(&self.config.get_moderation_path(), request)
+            .await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn create_speech(&self, request: SpeechRequest) -> OpenAiResult<ApiResponse<Bytes>> {
+        request.validate()?;
+        self.post_bytes_request(&self.config.get_speech_path(), request)
+            .await
+    }
+
+    async fn transcribe_audio(
+        &self,
+        request: TranscriptionRequest,
+    ) -> OpenAiResult<ApiResponse<TranscriptionResponse>> {
+        request.validate()?;
+        self.post_audio_request(
+            &self.config.get_transcription_path(),
+            || request.form(),
+            request.response_format,
+        )
+        .await
+    }
+
+    async fn translate_audio(
+        &self,
+        request: TranslationRequest,
+    ) -> OpenAiResult<ApiResponse<TranslationResponse>> {
+        request.validate()?;
+        self.post_audio_request(
+            &self.config.get_translation_path(),
+            || request.form(),
+            request.response_format,
+        )
+        .await
+    }
+
+    async fn upload_file(
+        &self,
+        request: UploadFileRequest,
+    ) -> OpenAiResult<ApiResponse<FileObject>> {
+        let response = self
+            .post_multipart(&self.config.get_files_path(), || request.form())
+            .await?;
+        let response: ApiResponse<OpenAiResponse<_>> = self.parse_api_response(response).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn list_files(&self) -> OpenAiResult<ApiResponse<FileList>> {
+        let response: ApiResponse<OpenAiResponse<_>> =
+            self.get_request(&self.config.get_files_path()).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn retrieve_file(&self, id: &str) -> OpenAiResult<ApiResponse<FileObject>> {
+        let response: ApiResponse<OpenAiResponse<_>> =
+            self.get_request(&self.config.get_file_path(id)).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn delete_file(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        let response: ApiResponse<OpenAiResponse<_>> =
+            self.delete_request(&self.config.get_file_path(id)).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn retrieve_file_content(&self, id: &str) -> OpenAiResult<ApiResponse<Bytes>> {
+        let endpoint = self.config.get_file_content_path(id);
+        trace::traced(&endpoint, async {
+            let response = self.execute(self.request(Method::GET, &endpoint)?).await?;
+            // file contents like fine tuning results legitimately exceed the
+            // response body limit meant for json responses
+            self.read_bytes(response, None).await
+        })
+        .await
+    }
+
+    async fn create_fine_tuning_job(
+        &self,
+        request: CreateFineTuningJobRequest,
+    ) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        let response: ApiResponse<OpenAiResponse<_>> = self
+            .post_request(&self.config.get_fine_tuning_jobs_path(), request)
+            .await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn list_fine_tuning_jobs(&self) -> OpenAiResult<ApiResponse<FineTuningJobList>> {
+        let response: ApiResponse<OpenAiResponse<_>> = self
+            .get_request(&self.config.get_fine_tuning_jobs_path())
+            .await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn retrieve_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        let response: ApiResponse<OpenAiResponse<_>> = self
+            .get_request(&self.config.get_fine_tuning_job_path(id))
+            .await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn cancel_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        let response: ApiResponse<OpenAiResponse<_>> = self
+            .post_empty_request(&self.config.get_fine_tuning_job_cancel_path(id))
+            .await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn list_fine_tuning_events(
+        &self,
+        id: &str,
+        after: Option<&str>,
+    ) -> OpenAiResult<ApiResponse<FineTuningEventList>> {
+        let params: Vec<(&str, &str)> = after.map(|after| ("after", after)).into_iter().collect();
+        let response: ApiResponse<OpenAiResponse<_>> = self
+            .get_request_with_params(&self.config.get_fine_tuning_job_events_path(id), &params)
+            .await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn create_assistant(
+        &self,
+        request: CreateAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>> {
+        let builder = self
+            .beta_request(Method::POST, &self.config.get_assistants_path())?
+            .json(&request);
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn retrieve_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<Assistant>> {
+        let builder = self.beta_request(Method::GET, &self.config.get_assistant_path(id))?;
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn modify_assistant(
+        &self,
+        id: &str,
+        request: ModifyAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>> {
+        let builder = self
+            .beta_request(Method::POST, &self.config.get_assistant_path(id))?
+            .json(&request);
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn delete_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        let builder = self.beta_request(Method::DELETE, &self.config.get_assistant_path(id))?;
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn list_assistants(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Assistant>>> {
+        let builder = self
+            .beta_request(Method::GET, &self.config.get_assistants_path())?
+            .query(&params.to_query());
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn create_thread(
+        &self,
+        request: CreateThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>> {
+        let builder = self
+            .beta_request(Method::POST, &self.config.get_threads_path())?
+            .json(&request);
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn retrieve_thread(&self, id: &str) -> OpenAiResult<ApiResponse<Thread>> {
+        let builder = self.beta_request(Method::GET, &self.config.get_thread_path(id))?;
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn modify_thread(
+        &self,
+        id: &str,
+        request: ModifyThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>> {
+        let builder = self
+            .beta_request(Method::POST, &self.config.get_thread_path(id))?
+            .json(&request);
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn delete_thread(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        let builder = self.beta_request(Method::DELETE, &self.config.get_thread_path(id))?;
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn create_message(
+        &self,
+        thread_id: &str,
+        request: CreateMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        let builder = self
+            .beta_request(
+                Method::POST,
+                &self.config.get_thread_messages_path(thread_id),
+            )?
+            .json(&request);
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn list_messages(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<ThreadMessageObject>>> {
+        let builder = self
+            .beta_request(
+                Method::GET,
+                &self.config.get_thread_messages_path(thread_id),
+            )?
+            .query(&params.to_query());
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn retrieve_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        let path = self.config.get_thread_message_path(thread_id, message_id);
+        let builder = self.beta_request(Method::GET, &path)?;
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn modify_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+        request: ModifyMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        let path = self.config.get_thread_message_path(thread_id, message_id);
+        let builder = self.beta_request(Method::POST, &path)?.json(&request);
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn delete_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        let path = self.config.get_thread_message_path(thread_id, message_id);
+        let builder = self.beta_request(Method::DELETE, &path)?;
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn create_run(
+        &self,
+        thread_id: &str,
+        request: CreateRunRequest,
+    ) -> OpenAiResult<ApiResponse<Run>> {
+        if request.stream == Some(true) {
+            return Err(OpenAiError::InvalidRequestParameter {
+                name: "stream".to_string(),
+                reason: "streamed runs are not supported".to_string(),
+            });
+        }
+        let builder = self
+            .beta_request(Method::POST, &self.config.get_thread_runs_path(thread_id))?
+            .json(&request);
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn retrieve_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>> {
+        let path = self.config.get_thread_run_path(thread_id, run_id);
+        let builder = self.beta_request(Method::GET, &path)?;
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn list_runs(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Run>>> {
+        let builder = self
+            .beta_request(Method::GET, &self.config.get_thread_runs_path(thread_id))?
+            .query(&params.to_query());
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn cancel_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>> {
+        let path = self.config.get_thread_run_cancel_path(thread_id, run_id);
+        let builder = self.beta_request(Method::POST, &path)?;
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn submit_tool_outputs(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        request: SubmitToolOutputsRequest,
+    ) -> OpenAiResult<ApiResponse<Run>> {
+        let path = self
+            .config
+            .get_thread_run_tool_outputs_path(thread_id, run_id);
+        let builder = self.beta_request(Method::POST, &path)?.json(&request);
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn create_batch(&self, request: CreateBatchRequest) -> OpenAiResult<ApiResponse<Batch>> {
+        let response: ApiResponse<OpenAiResponse<_>> = self
+            .post_request(&self.config.get_batches_path(), request)
+            .await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn retrieve_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>> {
+        let response: ApiResponse<OpenAiResponse<_>> =
+            self.get_request(&self.config.get_batch_path(id)).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn cancel_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>> {
+        let response: ApiResponse<OpenAiResponse<_>> = self
+            .post_empty_request(&self.config.get_batch_cancel_path(id))
+            .await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn list_batches(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Batch>>> {
+        let builder = self
+            .request(Method::GET, &self.config.get_batches_path())?
+            .query(&params.to_query());
+        let response: ApiResponse<OpenAiResponse<_>> = self.send(builder).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn get_models(&self) -> OpenAiResult<ApiResponse<OpenAiModelResponse>> {
+        let response: ApiResponse<OpenAiResponse<_>> =
+            self.get_request(&self.config.get_models_path()).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn get_model(&self, model: &str) -> OpenAiResult<ApiResponse<OpenAiModel>> {
+        let response: ApiResponse<OpenAiResponse<_>> =
+            self.get_request(&self.config.get_model_path(model)).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn create_image(
+        &self,
+        request: CreateImageRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        let response: ApiResponse<OpenAiResponse<_>> = self
+            .post_request(&self.config.get_create_image_path(), request)
+            .await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn edit_image(
+        &self,
+        request: EditImageRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        let response = self
+            .post_multipart(&self.config.get_edit_image_path(), || request.form())
+            .await?;
+        let response: ApiResponse<OpenAiResponse<_>> = self.parse_api_response(response).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+
+    async fn create_image_variation(
+        &self,
+        request: ImageVariationRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        let response = self
+            .post_multipart(&self.config.get_image_variations_path(), || request.form())
+            .await?;
+        let response: ApiResponse<OpenAiResponse<_>> = self.parse_api_response(response).await?;
+        response.try_map(OpenAiResponse::into_result)
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::assertions_on_constants,
+    clippy::expect_fun_call,
+    clippy::needless_borrows_for_generic_args
+)]
+pub(crate) mod request_client {
+    use crate::*;
+    use futures::StreamExt;
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use std::env;
+    use std::fs::File;
+    use std::io::Read;
+    use std::time::{Duration, Instant};
+    use wiremock::matchers::{
+        body_json, body_partial_json, body_string_contains, header, header_regex, method, path,
+        query_param,
+    };
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn should_give_http_error_for_invalid_response() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.get_models().await {
+            Err(OpenAiError::HttpError(_)) => assert!(true),
+            _ => assert!(false, "expected response parsing error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_give_http_status_for_plain_text_responses() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(ResponseTemplate::new(502).set_body_raw("Bad Gateway", "text/plain"))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .respond_with(
+                ResponseTemplate::new(503)
+                    .set_body_raw("Service Unavailable", "text/plain; charset=utf-8"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.get_models().await {
+            Err(OpenAiError::HttpStatus { status, body }) => {
+                assert_eq!(status, 502);
+                assert_eq!(body, Some("Bad Gateway".to_string()));
+            }
+            _ => panic!("expected http status error"),
+        }
+
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .build()
+            .unwrap();
+        match client.create_completion(request).await {
+            Err(OpenAiError::HttpStatus { status, body }) => {
+                assert_eq!(status, 503);
+                assert_eq!(body, Some("Service Unavailable".to_string()));
+            }
+            _ => panic!("expected http status error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_return_model_success() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_model_path("text-davinci-003")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("model_response")))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.get_model("text-davinci-003").await {
+            Ok(_) => assert!(true),
+            Err(_) => assert!(false, "expected success response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_return_models_success() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.get_models().await {
+            Ok(_) => assert!(true),
+            Err(_) => assert!(false, "expected success response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_fetch_models_and_model_concurrently() {
+        let (config, server) = create_test_server_config().await;
+        let delay = Duration::from_millis(300);
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json_response("models_response"))
+                    .set_delay(delay),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(config.get_model_path("text-davinci-003")))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json_response("model_response"))
+                    .set_delay(delay),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let start = Instant::now();
+        let (models, model) = client
+            .get_models_with_details("text-davinci-003")
+            .await
+            .expect("expected success response");
+        assert!(start.elapsed() < delay * 2);
+        assert!(!models.data.is_empty());
+        assert_eq!(model.id, "text-davinci-003");
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_fail_fetching_models_with_details_if_one_fails() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        if client.get_models_with_details("unknown").await.is_ok() {
+            panic!("expected error response")
+        }
+    }
+
+    #[tokio::test]
+    async fn should_use_rotated_token_for_next_request() {
+        let var = "OPENAI_TEST_CLIENT_ROTATING_TOKEN";
+        let server = MockServer::start().await;
+        let config = OpenAiConfig::default()
+            .base_url(&server.uri())
+            .token_provider(RotatingTokenProvider::from_env_var(var));
+        for token in ["first", "second"] {
+            Mock::given(method("GET"))
+                .and(path(config.get_models_path()))
+                .and(header(
+                    "Authorization",
+                    format!("Bearer {}", token).as_str(),
+                ))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = OpenAiClient::new(config);
+        env::set_var(var, "first");
+        let first = client.get_models().await;
+        env::set_var(var, "second");
+        let second = client.get_models().await;
+        env::remove_var(var);
+        assert!(first.is_ok());
+        assert!(second.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_share_cloned_client_between_tasks() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_model_path("text-davinci-003")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("model_response")))
+            .expect(10)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let handles: Vec<_> = (0..10)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.get_model("text-davinci-003").await })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.await.unwrap().is_ok());
+        }
+    }
+
+    async fn mount_model_not_found(server: &MockServer, config: &OpenAiConfig, model: &str) {
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .and(body_partial_json(serde_json::json!({ "model": model })))
+            .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+                "error": {
+                    "code": "model_not_found",
+                    "message": format!("The model `{}` does not exist", model),
+                    "param": null,
+                    "type": "invalid_request_error"
+                }
+            })))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn should_retry_completion_with_fallback_model() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.model_fallback("text-davinci-004", "text-davinci-003");
+        mount_model_not_found(&server, &config, "text-davinci-004").await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .and(body_partial_json(
+                serde_json::json!({ "model": "text-davinci-003" }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-004")
+            .prompt("I am so tired I could")
+            .build()
+            .unwrap();
+        let result = client
+            .create_completion_with_fallback(request)
+            .await
+            .expect("expected fallback success response");
+        assert_eq!(result.model, Some("text-davinci-003".to_string()));
+    }
+
+    #[tokio::test]
+    async fn should_fail_without_configured_fallback_model() {
+        let (config, server) = create_test_server_config().await;
+        mount_model_not_found(&server, &config, "text-davinci-004").await;
+
+        let client = OpenAiClient::new(config);
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-004")
+            .build()
+            .unwrap();
+        match client.create_completion_with_fallback(request).await {
+            Err(OpenAiError::NoFallbackAvailable { model }) => {
+                assert_eq!(model, "text-davinci-004")
+            }
+            _ => panic!("expected no fallback available error"),
+        }
+    }
+
+    fn empty_completion_response() -> ResponseTemplate {
+        let mut json = json_response("completion_response");
+        json["choices"][0]["text"] = Value::from("\n\n");
+        ResponseTemplate::new(200).set_body_json(json)
+    }
+
+    #[tokio::test]
+    async fn should_retry_completion_on_empty_text() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .respond_with(empty_completion_response())
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .build()
+            .unwrap();
+        let result = client
+            .create_completion_retry_on_empty(request, 2)
+            .await
+            .expect("expected non empty response");
+        assert!(result.has_text());
+    }
+
+    #[tokio::test]
+    async fn should_reject_completion_exceeding_context_window() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("a".repeat(16_000).as_str())
+            .max_tokens(200)
+            .build()
+            .unwrap();
+        match client.create_completion_with_context_check(request).await {
+            Err(OpenAiError::ContextWindowExceeded {
+                max_tokens,
+                prompt_tokens,
+                ..
+            }) => {
+                assert_eq!(max_tokens, 4_097);
+                assert_eq!(prompt_tokens, 4_000);
+            }
+            other => panic!("expected context window error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_keep_unset_max_tokens_after_context_check() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .and(|request: &wiremock::Request| {
+                let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+                body.get("max_tokens").is_none()
+            })
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("a".repeat(200).as_str())
+            .build()
+            .unwrap();
+        assert!(client
+            .create_completion_with_context_check(request)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_fail_when_all_completions_are_empty() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .respond_with(empty_completion_response())
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .build()
+            .unwrap();
+        match client.create_completion_retry_on_empty(request, 2).await {
+            Err(OpenAiError::EmptyResponse { attempts }) => assert_eq!(attempts, 3),
+            _ => panic!("expected empty response error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_send_configured_user_agent() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.with_user_agent("my-app/1.0");
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .and(header("User-Agent", "my-app/1.0"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        assert!(client.get_models().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_ignore_invalid_user_agent() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.with_user_agent("my-app\r\n1.0");
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        assert!(client.get_models().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_send_organization_and_project_headers() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.organization("org-abc123").project("proj_abc123");
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .and(header("OpenAI-Organization", "org-abc123"))
+            .and(header("OpenAI-Project", "proj_abc123"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .and(header("OpenAI-Organization", "org-abc123"))
+            .and(header("OpenAI-Project", "proj_abc123"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        assert!(client.get_models().await.is_ok());
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("I am so tired I could")
+            .build()
+            .unwrap();
+        assert!(client.create_completion(request).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_send_azure_requests_to_deployment() {
+        let server = MockServer::start().await;
+        let azure =
+            AzureOpenAiConfig::new("my-resource", "gpt-35-turbo", "2024-02-01", "azure-key")
+                .endpoint(&server.uri());
+        Mock::given(method("POST"))
+            .and(path("/openai/deployments/gpt-35-turbo/chat/completions"))
+            .and(query_param("api-version", "2024-02-01"))
+            .and(header("api-key", "azure-key"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("chat_completion_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/openai/files"))
+            .and(query_param("api-version", "2024-02-01"))
+            .and(header("api-key", "azure-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("files_response")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(azure);
+        let request = ChatCompletionRequestBuilder::default()
+            .model("gpt-35-turbo")
+            .messages(vec![ChatMessage::user("Hello!")])
+            .build()
+            .unwrap();
+        client
+            .create_chat_completion(request)
+            .await
+            .expect("expected chat completion");
+        client.list_files().await.expect("expected files");
+        for request in server.received_requests().await.unwrap() {
+            assert!(request
+                .headers
+                .iter()
+                .all(|(name, _)| name.as_str() != "authorization"));
+        }
+    }
+
+    #[tokio::test]
+    async fn should_fail_with_timeout_error() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.timeout(Duration::from_millis(50));
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json_response("models_response"))
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.get_models().await {
+            Err(OpenAiError::TimeoutError(_)) => {}
+            other => panic!("expected timeout error, got {:?}", other),
+        }
+        assert!(client
+            .with_timeout(Duration::from_secs(5))
+            .get_models()
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_apply_per_call_timeout() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json_response("models_response"))
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client
+            .with_timeout(Duration::from_millis(50))
+            .get_models()
+            .await
+        {
+            Err(OpenAiError::TimeoutError(_)) => {}
+            other => panic!("expected timeout error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_parse_rate_limit_headers() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json_response("models_response"))
+                    .insert_header("x-ratelimit-limit-requests", "60")
+                    .insert_header("x-ratelimit-remaining-requests", "59")
+                    .insert_header("x-ratelimit-reset-requests", "1s")
+                    .insert_header("x-ratelimit-limit-tokens", "150000")
+                    .insert_header("x-ratelimit-remaining-tokens", "149984")
+                    .insert_header("x-ratelimit-reset-tokens", "6m0s"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        assert_eq!(client.last_rate_limit(), None);
+        let response = client.get_models().await.expect("expected models");
+        assert!(!response.data.data.is_empty());
+        assert_eq!(
+            response.rate_limit,
+            Some(RateLimitInfo {
+                limit_requests: Some(60),
+                remaining_requests: Some(59),
+                reset_requests: Some(Duration::from_secs(1)),
+                limit_tokens: Some(150000),
+                remaining_tokens: Some(149984),
+                reset_tokens: Some(Duration::from_secs(360)),
+            })
+        );
+        assert_eq!(client.last_rate_limit(), response.rate_limit);
+    }
+
+    #[tokio::test]
+    async fn should_return_rate_limit_with_rate_limit_error() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(429)
+                    .insert_header("x-ratelimit-remaining-requests", "0")
+                    .insert_header("x-ratelimit-reset-requests", "20ms"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.get_models().await {
+            Err(OpenAiError::RateLimitError {
+                rate_limit: Some(rate_limit),
+                ..
+            }) => {
+                assert_eq!(rate_limit.remaining_requests, Some(0));
+                assert_eq!(rate_limit.reset_requests, Some(Duration::from_millis(20)));
+            }
+            other => panic!("expected rate limit error, got {:?}", other),
+        }
+    }
+
+    fn fast_retry(max_retries: u32) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+            backoff_multiplier: 2.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_retry_rate_limited_request() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.retry(fast_retry(3));
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        assert!(client.get_models().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_retry_server_errors_with_body() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.retry(fast_retry(1));
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .and(body_partial_json(
+                serde_json::json!({"model": "text-davinci-003"}),
+            ))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .and(body_partial_json(
+                serde_json::json!({"model": "text-davinci-003"}),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("I am so tired I could")
+            .build()
+            .unwrap();
+        assert!(client.create_completion(request).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_return_rate_limit_error_when_retries_are_exhausted() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "2"))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config.clone());
+        match client.get_models().await {
+            Err(OpenAiError::RateLimitError { retry_after, .. }) => {
+                assert_eq!(retry_after, Some(Duration::from_secs(2)))
+            }
+            other => panic!("expected rate limit error, got {:?}", other),
+        }
+
+        let client = OpenAiClient::new(config.retry(fast_retry(1)));
+        assert!(matches!(
+            client.get_models().await,
+            Err(OpenAiError::RateLimitError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_keep_rate_limit_error_details() {
+        let (config, server) = create_test_server_config().await;
+        let body = serde_json::json!({"error": {
+            "message": "Rate limit reached for requests",
+            "type": "requests",
+            "param": null,
+            "code": "rate_limit_exceeded"
+        }});
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(ResponseTemplate::new(429).set_body_json(body))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.get_models().await {
+            Err(OpenAiError::RateLimitError {
+                details: Some(details),
+                ..
+            }) => assert_eq!(details.message, "Rate limit reached for requests"),
+            other => panic!("expected rate limit error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_insufficient_quota() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.retry(fast_retry(3));
+        let body = serde_json::json!({"error": {
+            "message": "You exceeded your current quota",
+            "type": "insufficient_quota",
+            "param": null,
+            "code": "insufficient_quota"
+        }});
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(ResponseTemplate::new(429).set_body_json(body))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.get_models().await {
+            Err(OpenAiError::ApiErrorResponse(details)) => {
+                assert_eq!(details.code.as_deref(), Some("insufficient_quota"))
+            }
+            other => panic!("expected quota error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_send_default_user_agent() {
+        let (config, server) = create_test_server_config().await;
+        let user_agent = std::env::var("OPENAI_USER_AGENT")
+            .unwrap_or_else(|_| format!("openai-client-rust/{}", env!("CARGO_PKG_VERSION")));
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .and(header("User-Agent", user_agent.as_str()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        assert!(client.get_models().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_fail_on_response_exceeding_body_limit() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.with_max_response_body_bytes(Some(64));
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.get_models().await {
+            Err(OpenAiError::ResponseTooLarge { received, limit }) => {
+                assert_eq!(limit, 64);
+                assert!(received > limit);
+            }
+            other => panic!("expected response too large error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_read_response_without_body_limit() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.with_max_response_body_bytes(None);
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        assert!(client.get_models().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_add_ping_to_self_test() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config.clone());
+        let diagnostics = client.self_test().await.unwrap();
+        assert!(diagnostics.ping_ms.is_some());
+        assert_eq!(diagnostics.base_url, config.diagnostics().base_url);
+        assert!(diagnostics.has_access_token);
+    }
+
+    #[tokio::test]
+    async fn should_return_ping_latency() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json_response("models_response"))
+                    .set_delay(Duration::from_millis(10)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let latency = client.ping().await.expect("expected ping success");
+        assert!(latency > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn should_return_api_error_on_failed_ping() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "error": {
+                    "code": "invalid_api_key",
+                    "message": "Incorrect API key provided",
+                    "param": null,
+                    "type": "invalid_request_error"
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.ping().await {
+            Err(OpenAiError::ApiErrorResponse(details)) => {
+                assert_eq!(details.code, Some("invalid_api_key".to_string()))
+            }
+            _ => panic!("expected api error response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_report_api_compatibility() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/fine_tuning/jobs"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let report = client.check_api_compatibility().await.unwrap();
+        assert_eq!(
+            report,
+            ApiCompatibilityReport {
+                version: "v1".to_string(),
+                supports_chat: true,
+                supports_embeddings: false,
+                supports_fine_tuning: true,
+                supports_assistants: false,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn should_fail_api_compatibility_check_on_auth_error() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(wiremock::matchers::any())
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "error": {
+                    "code": "invalid_api_key",
+                    "message": "Incorrect API key provided",
+                    "param": null,
+                    "type": "invalid_request_error"
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.check_api_compatibility().await {
+            Err(OpenAiError::ApiErrorResponse(details)) => {
+                assert_eq!(details.code, Some("invalid_api_key".to_string()))
+            }
+            other => panic!("expected api error response, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_fail_api_compatibility_check_on_server_error() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(wiremock::matchers::any())
+            .respond_with(ResponseTemplate::new(503).set_body_raw("unavailable", "text/plain"))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        assert!(matches!(
+            client.check_api_compatibility().await,
+            Err(OpenAiError::HttpStatus { status: 503, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_report_connectivity_of_all_endpoints() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_create_image_path()))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config.clone());
+        let report = config.validate_connectivity(&client).await;
+        assert!(report.models_endpoint.is_reachable());
+        assert!(report.completion_endpoint.is_reachable());
+        assert_eq!(
+            report.image_endpoint,
+            EndpointStatus::Unreachable {
+                reason: "status 503 Service Unavailable".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_edit_response() {
+        let (config, server) = create_test_server_config().await;
+
+        let request = EditRequestBuilder::default()
+            .model("text-davinci-edit-001")
+            .input("What day of the wek is it?")
+            .instruction("Fix the spelling mistakes")
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&request).expect("request serialized");
+
+        Mock::given(method("POST"))
+            .and(path(config.get_edit_path()))
+            .and(body_json(json))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("edit_response")))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.create_edit(request).await {
+            Ok(_) => assert!(true),
+            Err(e) => {
+                println!("ERR: {:?}", e);
+                assert!(false, "expected success response")
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_return_completion_response() {
+        let (config, server) = create_test_server_config().await;
+
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("I am so tired I could")
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&request).expect("request serialized");
+
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .and(body_json(json))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        match client.create_completion(request).await {
+            Ok(result) => assert_eq!(result.system_fingerprint.as_deref(), Some("fp_44709d6fcb")),
+            Err(e) => {
+                println!("ERR: {:?}", e);
+                assert!(false, "expected success response")
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_return_chat_completion_response() {
+        let (config, server) = create_test_server_config().await;
+
+        let request = ChatCompletionRequestBuilder::default()
+            .model("gpt-3.5-turbo")
+            .messages(vec![
+                ChatMessage::system("You are a helpful assistant."),
+                ChatMessage::user("Hello!"),
+            ])
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&request).expect("request serialized");
+
+        Mock::given(method("POST"))
+            .and(path(config.get_chat_completion_path()))
+            .and(body_json(json))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("chat_completion_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let response = client
+            .create_chat_completion(request)
+            .await
+            .expect("expected success response");
+        assert_eq!(response.object, ObjectType::ChatCompletion);
+        assert_eq!(response.choices[0].message.role, ChatRole::Assistant);
+        assert_eq!(
+            response.best_text(),
+            Some("Hello! How can I assist you today?".to_string())
+        );
+        assert_eq!(response.usage.total_tokens, 28);
+        assert_eq!(
+            response.system_fingerprint.as_deref(),
+            Some("fp_44709d6fcb")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_stream_completion_chunks() {
+        let (config, server) = create_test_server_config().await;
+        let chunk = |text: &str| {
+            format!(
+                r#"data: {{"id":"cmpl-1","object":"text_completion","created":1669646641,"model":"text-davinci-003","choices":[{{"text":"{}","index":0,"logprobs":null,"finish_reason":null}}]}}"#,
+                text
+            )
+        };
+        let body = format!(
+            "{}\n\n: keep-alive\n\n{}\r\n\r\ndata: [DONE]\n\n",
+            chunk("Hello"),
+            chunk(" world")
+        );
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .and(body_partial_json(serde_json::json!({"stream": true})))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("Say hello")
+            .build()
+            .unwrap();
+        let chunks: Vec<_> = client
+            .create_completion_stream(request)
+            .await
+            .expect("expected stream")
+            .into_data()
+            .collect()
+            .await;
+        let text: String = chunks
+            .into_iter()
+            .map(|chunk| chunk.unwrap().choices[0].text.clone())
+            .collect();
+        assert_eq!(text, "Hello world");
+    }
+
+    #[tokio::test]
+    async fn should_stream_chat_completion_chunks() {
+        let (config, server) = create_test_server_config().await;
+        let chunk = |delta: &str| {
+            format!(
+                r#"data: {{"id":"chatcmpl-1","object":"chat.completion.chunk","created":1699180000,"model":"gpt-3.5-turbo","choices":[{{"index":0,"delta":{},"finish_reason":null}}]}}"#,
+                delta
+            )
+        };
+        let body = format!(
+            "{}\n\n{}\n\n{}\n\ndata: [DONE]\n\n",
+            chunk(r#"{"role":"assistant","content":""}"#),
+            chunk(r#"{"content":"Hello"}"#),
+            chunk(r#"{"content":"!"}"#)
+        );
+        Mock::given(method("POST"))
+            .and(path(config.get_chat_completion_path()))
+            .and(body_partial_json(serde_json::json!({"stream": true})))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = ChatCompletionRequestBuilder::default()
+            .model("gpt-3.5-turbo")
+            .messages(vec![ChatMessage::user("Hello!")])
+            .build()
+            .unwrap();
+        let stream = client
+            .create_chat_completion_stream(request)
+            .await
+            .expect("expected stream")
+            .into_data();
+        let response = collect_chat_stream(stream)
+            .await
+            .expect("expected collected response");
+        assert_eq!(response.choices[0].message.role, ChatRole::Assistant);
+        assert_eq!(response.best_text(), Some("Hello!".to_string()));
+    }
+
+    #[tokio::test]
+    async fn should_collect_streamed_tool_calls() {
+        let (config, server) = create_test_server_config().await;
+        let body = std::fs::read_to_string("test_data/chat_tool_call_stream.txt")
+            .expect("sse test data exists");
+        Mock::given(method("POST"))
+            .and(path(config.get_chat_completion_path()))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = ChatCompletionRequestBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![ChatMessage::user("Weather and time in Paris?")])
+            .build()
+            .unwrap();
+        let stream = client
+            .create_chat_completion_stream(request)
+            .await
+            .expect("expected stream")
+            .into_data();
+        let response = collect_chat_stream(stream)
+            .await
+            .expect("expected collected response");
+        let choice = &response.choices[0];
+        assert_eq!(choice.finish_reason, Some(FinishReason::ToolCalls));
+        let calls = choice
+            .message
+            .tool_calls
+            .as_ref()
+            .expect("expected tool calls");
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].id, "call_weather");
+        assert_eq!(calls[0].function.name, "get_weather");
+        assert_eq!(calls[0].function.arguments, r#"{"location": "Paris"}"#);
+        assert_eq!(calls[1].id, "call_time");
+        assert_eq!(calls[1].function.arguments, "{}");
+    }
+
+    #[tokio::test]
+    async fn should_return_api_error_for_failed_stream() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "error": {"message": "invalid key", "type": "invalid_request_error", "param": null, "code": null}
+            })))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("Say hello")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.create_completion_stream(request).await,
+            Err(OpenAiError::ApiErrorResponse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_reject_stream_flag_for_completion() {
+        let client = OpenAiClient::default();
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .stream(true)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.create_completion(request).await,
+            Err(OpenAiError::InvalidRequestParameter { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_return_embedding_response() {
+        let (config, server) = create_test_server_config().await;
+
+        let request = EmbeddingRequestBuilder::default()
+            .model("text-embedding-3-small")
+            .input(StringOrListParam::ListParam(vec![
+                "The food was delicious".to_string(),
+                "The waiter was friendly".to_string(),
+            ]))
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&request).expect("request serialized");
+
+        Mock::given(method("POST"))
+            .and(path(config.get_embedding_path()))
+            .and(body_json(json))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("embedding_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let response = client
+            .create_embedding(request)
+            .await
+            .expect("expected success response")
+            .into_data();
+        assert_eq!(response.object, ObjectType::List);
+        assert_eq!(response.data.len(), 2);
+        assert_eq!(response.data[1].index, 1);
+        assert_eq!(response.data[0].embedding.len(), 3);
+        assert_eq!(response.usage.total_tokens, 8);
+    }
+
+    #[tokio::test]
+    async fn should_return_moderation_response() {
+        let (config, server) = create_test_server_config().await;
+
+        let request = ModerationRequestBuilder::default()
+            .input(StringOrListParam::StringParam(
+                "I will hurt you".to_string(),
+            ))
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&request).expect("request serialized");
+
+        Mock::given(method("POST"))
+            .and(path(config.get_moderation_path()))
+            .and(body_json(json))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("moderation_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let response = client
+            .create_moderation(request)
+            .await
+            .expect("expected success response");
+        assert!(response.is_flagged());
+        let result = &response.results[0];
+        assert!(result.categories.violence);
+        assert!(result.categories.harassment_threatening);
+        assert!(!result.categories.self_harm);
+        assert!(result.category_scores.violence > 0.9);
+    }
+
+    #[tokio::test]
+    async fn should_return_speech_audio() {
+        let (config, server) = create_test_server_config().await;
+
+        let request = SpeechRequestBuilder::default()
+            .model("tts-1")
+            .input("Hello world")
+            .voice(Voice::Alloy)
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&request).expect("request serialized");
+        let audio = vec![0x49, 0x44, 0x33, 0x04];
+
+        Mock::given(method("POST"))
+            .and(path(config.get_speech_path()))
+            .and(header("content-type", "application/json"))
+            .and(body_json(json))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(audio.clone(), "audio/mpeg"))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let response = client
+            .create_speech(request)
+            .await
+            .expect("expected success response");
+        assert_eq!(response.as_ref(), audio.as_slice());
+    }
+
+    #[tokio::test]
+    async fn should_return_api_error_for_failed_speech() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_speech_path()))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": {"message": "invalid voice", "type": "invalid_request_error", "param": "voice", "code": null}
+            })))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = SpeechRequestBuilder::default()
+            .model("tts-1")
+            .input("Hello world")
+            .voice(Voice::Alloy)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.create_speech(request).await,
+            Err(OpenAiError::ApiErrorResponse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_return_verbose_transcription() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_transcription_path()))
+            .and(header_regex(
+                "content-type",
+                "^multipart/form-data; boundary=.+",
+            ))
+            .and(body_string_contains("filename=\"hello.mp3\""))
+            .and(body_string_contains("verbose_json"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("transcription_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = TranscriptionRequestBuilder::default()
+            .file_bytes(vec![0x49, 0x44, 0x33])
+            .file_name("hello.mp3")
+            .model("whisper-1")
+            .response_format(TranscriptionFormat::VerboseJson)
+            .build()
+            .unwrap();
+        let response = client
+            .transcribe_audio(request)
+            .await
+            .expect("expected success response")
+            .into_data();
+        assert_eq!(response.text, "Hello world.");
+        assert_eq!(response.language, Some("english".to_string()));
+        assert_eq!(response.words.unwrap().len(), 2);
+        assert_eq!(response.segments.unwrap()[0].tokens.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn should_return_plain_text_transcription() {
+        let (config, server) = create_test_server_config().await;
+        let srt = "1\n00:00:00,000 --> 00:00:02,950\nHello world.\n";
+        Mock::given(method("POST"))
+            .and(path(config.get_transcription_path()))
+            .and(body_string_contains("srt"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(srt, "text/plain"))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = TranscriptionRequestBuilder::default()
+            .file_bytes(vec![0x49, 0x44, 0x33])
+            .file_name("hello.mp3")
+            .model("whisper-1")
+            .response_format(TranscriptionFormat::Srt)
+            .build()
+            .unwrap();
+        let response = client
+            .transcribe_audio(request)
+            .await
+            .expect("expected success response");
+        assert_eq!(response.text, srt);
+        assert_eq!(response.segments, None);
+    }
+
+    #[tokio::test]
+    async fn should_return_translation_response() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_translation_path()))
+            .and(header_regex(
+                "content-type",
+                "^multipart/form-data; boundary=.+",
+            ))
+            .and(body_string_contains("name=\"model\"\r\n\r\nwhisper-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("translation_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = TranslationRequestBuilder::default()
+            .file_bytes(vec![0x49, 0x44, 0x33])
+            .file_name("german.m4a")
+            .model("whisper-1")
+            .build()
+            .unwrap();
+        let response = client
+            .translate_audio(request)
+            .await
+            .expect("expected success response");
+        assert!(response.text.starts_with("Hello, my name is Wolfgang"));
+    }
+
+    #[tokio::test]
+    async fn should_upload_file() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_files_path()))
+            .and(header_regex(
+                "content-type",
+                "^multipart/form-data; boundary=.+",
+            ))
+            .and(body_string_contains("name=\"purpose\"\r\n\r\nfine-tune"))
+            .and(body_string_contains("filename=\"mydata.jsonl\""))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("file_response")))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = UploadFileRequestBuilder::default()
+            .file_bytes(b"{\"prompt\": \"a\", \"completion\": \"b\"}\n".to_vec())
+            .file_name("mydata.jsonl")
+            .purpose(FilePurpose::FineTune)
+            .build()
+            .unwrap();
+        let file = client
+            .upload_file(request)
+            .await
+            .expect("expected success response");
+        assert_eq!(file.id, "file-abc123");
+        assert_eq!(file.object, ObjectType::File);
+        assert_eq!(file.purpose, FilePurpose::FineTune.as_str());
+    }
+
+    #[tokio::test]
+    async fn should_retry_file_upload() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.retry(fast_retry(1));
+        Mock::given(method("POST"))
+            .and(path(config.get_files_path()))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_files_path()))
+            .and(body_string_contains("filename=\"mydata.jsonl\""))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("file_response")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = UploadFileRequestBuilder::default()
+            .file_bytes(b"{\"prompt\": \"a\", \"completion\": \"b\"}\n".to_vec())
+            .file_name("mydata.jsonl")
+            .purpose(FilePurpose::FineTune)
+            .build()
+            .unwrap();
+        assert!(client.upload_file(request).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_edit_image_with_mask() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_edit_image_path()))
+            .and(header_regex(
+                "content-type",
+                "^multipart/form-data; boundary=.+",
+            ))
+            .and(body_string_contains(
+                "name=\"image\"; filename=\"otter.png\"",
+            ))
+            .and(body_string_contains("name=\"mask\"; filename=\"mask.png\""))
+            .and(body_string_contains(
+                "name=\"prompt\"\r\n\r\nA cute baby sea otter wearing a beret",
+            ))
+            .and(body_string_contains("name=\"n\"\r\n\r\n2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("create_image_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = EditImageRequestBuilder::default()
+            .image_bytes(b"image".to_vec())
+            .image_name("otter.png")
+            .mask_bytes(b"mask".to_vec())
+            .prompt("A cute baby sea otter wearing a beret")
+            .n(2)
+            .build()
+            .unwrap();
+        let result = client
+            .edit_image(request)
+            .await
+            .expect("expected success response")
+            .into_data();
+        assert!(!result.data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_create_image_variation() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_image_variations_path()))
+            .and(header_regex(
+                "content-type",
+                "^multipart/form-data; boundary=.+",
+            ))
+            .and(body_string_contains(
+                "name=\"image\"; filename=\"otter.png\"",
+            ))
+            .and(body_string_contains("name=\"size\"\r\n\r\n512x512"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("create_image_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = ImageVariationRequestBuilder::default()
+            .image_bytes(b"image".to_vec())
+            .image_name("otter.png")
+            .size("512x512")
+            .build()
+            .unwrap();
+        let result = client
+            .create_image_variation(request)
+            .await
+            .expect("expected success response")
+            .into_data();
+        assert!(!result.data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_list_retrieve_and_delete_files() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_files_path()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("files_response")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(config.get_file_path("file-abc123")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("file_response")))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(config.get_file_path("file-abc123")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "file-abc123",
+                "object": "file",
+                "deleted": true
+            })))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let files = client
+            .list_files()
+            .await
+            .expect("expected file list")
+            .into_data();
+        assert_eq!(files.data.len(), 2);
+        let file = client
+            .retrieve_file("file-abc123")
+            .await
+            .expect("expected file");
+        assert_eq!(file.filename, "mydata.jsonl");
+        let deleted = client
+            .delete_file("file-abc123")
+            .await
+            .expect("expected delete response");
+        assert!(deleted.deleted);
+    }
+
+    #[tokio::test]
+    async fn should_retrieve_file_content() {
+        let (config, server) = create_test_server_config().await;
+        let content = "{\"prompt\": \"a\", \"completion\": \"b\"}\n";
+        Mock::given(method("GET"))
+            .and(path(config.get_file_content_path("file-abc123")))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(content, "application/octet-stream"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(config.get_file_content_path("file-missing")))
+            .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+                "error": {"message": "No such File object", "type": "invalid_request_error", "param": "id", "code": null}
+            })))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let bytes = client
+            .retrieve_file_content("file-abc123")
+            .await
+            .expect("expected file content");
+        assert_eq!(bytes.as_ref(), content.as_bytes());
+        assert!(matches!(
+            client.retrieve_file_content("file-missing").await,
+            Err(OpenAiError::ApiErrorResponse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_not_limit_file_content() {
+        let (config, server) = create_test_server_config().await;
+        let config = config.with_max_response_body_bytes(Some(16));
+        let content = "x".repeat(64);
+        Mock::given(method("GET"))
+            .and(path(config.get_file_content_path("file-abc123")))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(content.clone(), "application/octet-stream"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let bytes = client
+            .retrieve_file_content("file-abc123")
+            .await
+            .expect("expected file content");
+        assert_eq!(bytes.as_ref(), content.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn should_create_and_cancel_fine_tuning_job() {
+        let (config, server) = create_test_server_config().await;
+
+        let request = CreateFineTuningJobRequestBuilder::default()
+            .training_file("file-abc123")
+            .model("gpt-3.5-turbo-0125")
+            .hyperparameters(Hyperparameters {
+                n_epochs: Some(HyperparameterValue::Value(3)),
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&request).expect("request serialized");
+        let mut cancelled = json_response("fine_tuning_job_response");
+        cancelled["status"] = Value::from("cancelled");
+
+        Mock::given(method("POST"))
+            .and(path(config.get_fine_tuning_jobs_path()))
+            .and(body_json(json))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("fine_tuning_job_response")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_fine_tuning_job_cancel_path("ftjob-abc123")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(cancelled))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let job = client
+            .create_fine_tuning_job(request)
+            .await
+            .expect("expected created job");
+        assert_eq!(job.object, ObjectType::FineTuningJob);
+        assert_eq!(job.status, FineTuningJobStatus::Queued);
+        assert_eq!(
+            job.hyperparameters.n_epochs,
+            Some(HyperparameterValue::Auto)
+        );
+        let job = client
+            .cancel_fine_tuning_job(&job.id)
+            .await
+            .expect("expected cancelled job");
+        assert!(job.status.is_finished());
     }
 
-    async fn get_request<T>(&self, endpoint: &str) -> OpenAiResult<T>
-    where
-        T: DeserializeOwned,
-    {
-        let res = self
-            .client
-            .get(self.config.api_url(endpoint))
-            .header(
-                "Authorization",
-                format!("Bearer {}", self.config.get_access_token()?),
-            )
-            .send()
-            .await?
-            .json()
-            .await?;
-        Ok(res)
+    #[tokio::test]
+    async fn should_list_and_retrieve_fine_tuning_jobs() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_fine_tuning_jobs_path()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [json_response("fine_tuning_job_response")],
+                "has_more": false
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(config.get_fine_tuning_job_path("ftjob-abc123")))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("fine_tuning_job_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let jobs = client
+            .list_fine_tuning_jobs()
+            .await
+            .expect("expected job list")
+            .into_data();
+        assert_eq!(jobs.data.len(), 1);
+        assert!(!jobs.has_more);
+        let job = client
+            .retrieve_fine_tuning_job("ftjob-abc123")
+            .await
+            .expect("expected job");
+        assert_eq!(job.training_file, "file-abc123");
     }
 
-    async fn post_request<R, T>(&self, endpoint: &str, body: R) -> OpenAiResult<T>
-    where
-        T: DeserializeOwned,
-        R: Serialize,
-    {
-        let res = self
-            .client
-            .post(self.config.api_url(endpoint))
-            .header(
-                "Authorization",
-                format!("Bearer {}", self.config.get_access_token()?),
-            )
-            .json(&body)
-            .send()
-            .await?
-            .json()
-            .await?;
-        Ok(res)
+    #[tokio::test]
+    async fn should_page_through_fine_tuning_events() {
+        let (config, server) = create_test_server_config().await;
+        let events_path = config.get_fine_tuning_job_events_path("ftjob-abc123");
+        Mock::given(method("GET"))
+            .and(path(events_path.as_str()))
+            .and(query_param("after", "ft-event-tyiGuB72evQncpH87xe505Sv"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [],
+                "has_more": false
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(events_path.as_str()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json_response("fine_tuning_events_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let events = client
+            .list_fine_tuning_events("ftjob-abc123", None)
+            .await
+            .expect("expected events")
+            .into_data();
+        assert_eq!(events.data.len(), 2);
+        assert_eq!(events.data[0].object, ObjectType::FineTuningJobEvent);
+        let after = events.after().expect("expected cursor");
+        let next = client
+            .list_fine_tuning_events("ftjob-abc123", Some(after))
+            .await
+            .expect("expected events")
+            .into_data();
+        assert!(next.data.is_empty());
+        assert_eq!(next.after(), None);
     }
 
-    fn unwrap_response<T>(&self, response: OpenAiResponse<T>) -> OpenAiResult<T> {
-        match response {
-            OpenAiResponse::Success(res) => Ok(res),
-            OpenAiResponse::Error(err) => Err(ApiErrorResponse(err.error)),
-            OpenAiResponse::Other(f) => Err(UnexpectedJsonResponse(f)),
-        }
+    #[tokio::test]
+    async fn should_create_and_modify_assistant() {
+        let (config, server) = create_test_server_config().await;
+
+        let request = CreateAssistantRequestBuilder::default()
+            .model("gpt-4o")
+            .name("Math Tutor")
+            .instructions("You are a personal math tutor.")
+            .tools(vec![AssistantTool::CodeInterpreter])
+            .tool_resources(ToolResources {
+                code_interpreter: Some(CodeInterpreterResources {
+                    file_ids: vec!["file-abc123".to_string()],
+                }),
+                file_search: None,
+            })
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&request).expect("request serialized");
+
+        Mock::given(method("POST"))
+            .and(path(config.get_assistants_path()))
+            .and(header("OpenAI-Beta", "assistants=v2"))
+            .and(body_json(json))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("assistant_response")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_assistant_path("asst_abc123")))
+            .and(header("OpenAI-Beta", "assistants=v2"))
+            .and(body_json(serde_json::json!({"name": "Algebra Tutor"})))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("assistant_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let assistant = client
+            .create_assistant(request)
+            .await
+            .expect("expected assistant");
+        assert_eq!(assistant.object, ObjectType::Assistant);
+        assert_eq!(assistant.tools, vec![AssistantTool::CodeInterpreter]);
+        let request = ModifyAssistantRequestBuilder::default()
+            .name("Algebra Tutor")
+            .build()
+            .unwrap();
+        assert!(client
+            .modify_assistant(&assistant.id, request)
+            .await
+            .is_ok());
     }
-}
 
-#[async_trait]
-impl ClientApi for OpenAiClient {
-    async fn create_completion(&self, request: CompletionRequest) -> OpenAiResult<TextResult> {
-        self.unwrap_response(
-            self.post_request(&self.config.get_completion_path(), request)
-                .await?,
-        )
+    #[tokio::test]
+    async fn should_list_retrieve_and_delete_assistants() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_assistants_path()))
+            .and(header("OpenAI-Beta", "assistants=v2"))
+            .and(query_param("limit", "1"))
+            .and(query_param("order", "asc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [json_response("assistant_response")],
+                "first_id": "asst_abc123",
+                "last_id": "asst_abc123",
+                "has_more": true
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(config.get_assistant_path("asst_abc123")))
+            .and(header("OpenAI-Beta", "assistants=v2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("assistant_response")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(config.get_assistant_path("asst_abc123")))
+            .and(header("OpenAI-Beta", "assistants=v2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "asst_abc123",
+                "object": "assistant.deleted",
+                "deleted": true
+            })))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let params = ListParams {
+            limit: Some(1),
+            order: Some(ListOrder::Asc),
+            ..Default::default()
+        };
+        let page = client
+            .list_assistants(params.clone())
+            .await
+            .expect("expected assistants")
+            .into_data();
+        assert_eq!(page.data.len(), 1);
+        assert_eq!(
+            page.next_page(&params).and_then(|p| p.after),
+            Some("asst_abc123".to_string())
+        );
+        let assistant = client
+            .retrieve_assistant("asst_abc123")
+            .await
+            .expect("expected assistant");
+        assert_eq!(assistant.name, Some("Math Tutor".to_string()));
+        assert!(
+            client
+                .delete_assistant("asst_abc123")
+                .await
+                .expect("expected delete response")
+                .deleted
+        );
     }
 
-    async fn create_edit(&self, request: EditRequest) -> OpenAiResult<TextResult> {
-        self.unwrap_response(
-            self.post_request(&self.config.get_edit_path(), request)
-                .await?,
-        )
+    #[tokio::test]
+    async fn should_create_modify_and_delete_thread() {
+        let (config, server) = create_test_server_config().await;
+
+        let request = CreateThreadRequestBuilder::default()
+            .messages(vec![ThreadMessage::user("Hello")])
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&request).expect("request serialized");
+
+        Mock::given(method("POST"))
+            .and(path(config.get_threads_path()))
+            .and(header("OpenAI-Beta", "assistants=v2"))
+            .and(body_json(json))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("thread_response")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_thread_path("thread_abc123")))
+            .and(body_json(serde_json::json!({"metadata": {"user": "43"}})))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("thread_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(config.get_thread_path("thread_abc123")))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("thread_response")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(config.get_thread_path("thread_abc123")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "thread_abc123",
+                "object": "thread.deleted",
+                "deleted": true
+            })))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let thread = client
+            .create_thread(request)
+            .await
+            .expect("expected thread");
+        assert_eq!(thread.object, ObjectType::Thread);
+        assert_eq!(
+            thread.metadata.as_ref().and_then(|m| m.get("user")),
+            Some(&"42".to_string())
+        );
+        let request = ModifyThreadRequestBuilder::default()
+            .metadata(HashMap::from([("user".to_string(), "43".to_string())]))
+            .build()
+            .unwrap();
+        assert!(client.modify_thread(&thread.id, request).await.is_ok());
+        assert!(client.retrieve_thread(&thread.id).await.is_ok());
+        let deleted = client
+            .delete_thread(&thread.id)
+            .await
+            .expect("expected delete response");
+        assert!(deleted.deleted);
+        assert_eq!(
+            deleted.object,
+            ObjectType::Unknown("thread.deleted".to_string())
+        );
     }
 
-    async fn get_models(&self) -> OpenAiResult<OpenAiModelResponse> {
-        let response = self.get_request(&self.config.get_models_path()).await?;
-        self.unwrap_response(response)
+    #[tokio::test]
+    async fn should_create_and_list_thread_messages() {
+        let (config, server) = create_test_server_config().await;
+
+        let request = CreateMessageRequestBuilder::default()
+            .role(MessageRole::User)
+            .content(MessageContent::Text(
+                "How does AI work? Explain it in simple terms.".to_string(),
+            ))
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&request).expect("request serialized");
+        let messages_path = config.get_thread_messages_path("thread_abc123");
+
+        Mock::given(method("POST"))
+            .and(path(messages_path.as_str()))
+            .and(header("OpenAI-Beta", "assistants=v2"))
+            .and(body_json(json))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("message_response")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(messages_path.as_str()))
+            .and(query_param("after", "msg_abc000"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [json_response("message_response")],
+                "first_id": "msg_abc123",
+                "last_id": "msg_abc123",
+                "has_more": false
+            })))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let message = client
+            .create_message("thread_abc123", request)
+            .await
+            .expect("expected message");
+        assert_eq!(message.object, ObjectType::ThreadMessage);
+        assert_eq!(
+            message.text(),
+            "How does AI work? Explain it in simple terms."
+        );
+        assert!(matches!(
+            &message.content[1],
+            ContentBlock::ImageFile { image_file } if image_file.file_id == "file-abc123"
+        ));
+        let params = ListParams {
+            after: Some("msg_abc000".to_string()),
+            ..Default::default()
+        };
+        let page = client
+            .list_messages("thread_abc123", params.clone())
+            .await
+            .expect("expected messages")
+            .into_data();
+        assert_eq!(page.data.len(), 1);
+        assert_eq!(page.next_page(&params), None);
     }
 
-    async fn get_model(&self, model: &str) -> OpenAiResult<OpenAiModel> {
-        let resp = self.get_request(&self.config.get_model_path(model)).await?;
-        self.unwrap_response(resp)
+    #[tokio::test]
+    async fn should_retrieve_modify_and_delete_thread_message() {
+        let (config, server) = create_test_server_config().await;
+        let message_path = config.get_thread_message_path("thread_abc123", "msg_abc123");
+        Mock::given(method("GET"))
+            .and(path(message_path.as_str()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("message_response")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(message_path.as_str()))
+            .and(body_json(
+                serde_json::json!({"metadata": {"modified": "true"}}),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("message_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(message_path.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "msg_abc123",
+                "object": "thread.message.deleted",
+                "deleted": true
+            })))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let message = client
+            .retrieve_message("thread_abc123", "msg_abc123")
+            .await
+            .expect("expected message");
+        assert_eq!(message.thread_id, "thread_abc123");
+        let request = ModifyMessageRequestBuilder::default()
+            .metadata(HashMap::from([(
+                "modified".to_string(),
+                "true".to_string(),
+            )]))
+            .build()
+            .unwrap();
+        assert!(client
+            .modify_message("thread_abc123", "msg_abc123", request)
+            .await
+            .is_ok());
+        assert!(
+            client
+                .delete_message("thread_abc123", "msg_abc123")
+                .await
+                .expect("expected delete response")
+                .deleted
+        );
     }
 
-    async fn create_image(&self, request: CreateImageRequest) -> OpenAiResult<ImageResult> {
-        let response = self
-            .post_request(&self.config.get_create_image_path(), request)
-            .await?;
-        self.unwrap_response(response)
+    fn run_response(status: &str) -> Value {
+        let mut run = json_response("run_response");
+        run["status"] = Value::from(status);
+        run
     }
-}
 
-#[cfg(test)]
-mod request_client {
-    use crate::*;
-    use serde_json::Value;
-    use std::fs::File;
-    use std::io::Read;
-    use wiremock::matchers::{body_json, method, path};
-    use wiremock::{Mock, MockServer, ResponseTemplate};
+    #[tokio::test]
+    async fn should_create_and_poll_run_until_completed() {
+        let (config, server) = create_test_server_config().await;
+        let request = CreateRunRequestBuilder::default()
+            .assistant_id("asst_abc123")
+            .build()
+            .unwrap();
+        let run_path = config.get_thread_run_path("thread_abc123", "run_abc123");
+
+        Mock::given(method("POST"))
+            .and(path(config.get_thread_runs_path("thread_abc123")))
+            .and(header("OpenAI-Beta", "assistants=v2"))
+            .and(body_json(
+                serde_json::json!({"assistant_id": "asst_abc123"}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(run_response("queued")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(run_path.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(run_response("in_progress")))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(run_path.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(run_response("completed")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let run = client
+            .create_run("thread_abc123", request)
+            .await
+            .expect("expected run");
+        assert_eq!(run.object, ObjectType::ThreadRun);
+        assert_eq!(run.status, RunStatus::Queued);
+        let run = client
+            .poll_run(&run.thread_id, &run.id, Duration::from_millis(1))
+            .await
+            .expect("expected completed run");
+        assert_eq!(run.status, RunStatus::Completed);
+    }
 
     #[tokio::test]
-    async fn should_give_http_error_for_invalid_response() {
+    async fn should_fail_polling_failed_run() {
         let (config, server) = create_test_server_config().await;
+        let mut failed = run_response("failed");
+        failed["last_error"] = serde_json::json!({
+            "code": "rate_limit_exceeded",
+            "message": "Rate limit reached"
+        });
         Mock::given(method("GET"))
-            .and(path(config.get_models_path()))
-            .respond_with(ResponseTemplate::new(200))
+            .and(path(
+                config.get_thread_run_path("thread_abc123", "run_abc123"),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(failed))
             .mount(&server)
             .await;
 
         let client = OpenAiClient::new(config);
-        match client.get_models().await {
-            Err(OpenAiError::HttpError(_)) => assert!(true),
-            _ => assert!(false, "expected response parsing error"),
+        match client
+            .poll_run("thread_abc123", "run_abc123", Duration::from_millis(1))
+            .await
+        {
+            Err(OpenAiError::RunFailed { status, error, .. }) => {
+                assert_eq!(status, RunStatus::Failed);
+                assert_eq!(error.unwrap().code, "rate_limit_exceeded");
+            }
+            other => panic!("expected failed run, got {:?}", other),
         }
     }
 
     #[tokio::test]
-    async fn should_return_model_success() {
+    async fn should_submit_tool_outputs_for_required_action() {
         let (config, server) = create_test_server_config().await;
+        let mut requires_action = run_response("requires_action");
+        requires_action["required_action"] = serde_json::json!({
+            "type": "submit_tool_outputs",
+            "submit_tool_outputs": {
+                "tool_calls": [{
+                    "id": "call_abc123",
+                    "type": "function",
+                    "function": {"name": "get_current_weather", "arguments": "{\"location\":\"Berlin\"}"}
+                }]
+            }
+        });
         Mock::given(method("GET"))
-            .and(path(config.get_model_path("text-davinci-003")))
-            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("model_response")))
+            .and(path(
+                config.get_thread_run_path("thread_abc123", "run_abc123"),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(requires_action))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_thread_run_tool_outputs_path(
+                "thread_abc123",
+                "run_abc123",
+            )))
+            .and(body_json(serde_json::json!({
+                "tool_outputs": [{"tool_call_id": "call_abc123", "output": "22C"}]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(run_response("queued")))
+            .expect(1)
             .mount(&server)
             .await;
 
         let client = OpenAiClient::new(config);
-        match client.get_model("text-davinci-003").await {
-            Ok(_) => assert!(true),
-            Err(_) => assert!(false, "expected success response"),
-        }
+        let run = client
+            .poll_run("thread_abc123", "run_abc123", Duration::from_millis(1))
+            .await
+            .expect("expected run requiring action")
+            .into_data();
+        let tool_calls = run
+            .required_action
+            .expect("expected required action")
+            .submit_tool_outputs
+            .tool_calls;
+        assert_eq!(tool_calls[0].function.name, "get_current_weather");
+        let request = SubmitToolOutputsRequest {
+            tool_outputs: vec![ToolOutput {
+                tool_call_id: tool_calls[0].id.clone(),
+                output: "22C".to_string(),
+            }],
+        };
+        let run = client
+            .submit_tool_outputs("thread_abc123", "run_abc123", request)
+            .await
+            .expect("expected run");
+        assert_eq!(run.status, RunStatus::Queued);
     }
 
     #[tokio::test]
-    async fn should_return_models_success() {
+    async fn should_list_and_cancel_runs() {
         let (config, server) = create_test_server_config().await;
         Mock::given(method("GET"))
-            .and(path(config.get_models_path()))
-            .respond_with(
-                ResponseTemplate::new(200).set_body_json(json_response("models_response")),
-            )
+            .and(path(config.get_thread_runs_path("thread_abc123")))
+            .and(query_param("limit", "10"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [run_response("completed")],
+                "first_id": "run_abc123",
+                "last_id": "run_abc123",
+                "has_more": false
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(
+                config.get_thread_run_cancel_path("thread_abc123", "run_abc123"),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(run_response("cancelling")))
             .mount(&server)
             .await;
 
         let client = OpenAiClient::new(config);
-        match client.get_models().await {
-            Ok(_) => assert!(true),
-            Err(_) => assert!(false, "expected success response"),
-        }
+        let params = ListParams {
+            limit: Some(10),
+            ..Default::default()
+        };
+        let runs = client
+            .list_runs("thread_abc123", params)
+            .await
+            .expect("expected runs")
+            .into_data();
+        assert_eq!(runs.data[0].tools.len(), 1);
+        let run = client
+            .cancel_run("thread_abc123", "run_abc123")
+            .await
+            .expect("expected run");
+        assert!(!run.status.is_terminal());
     }
 
     #[tokio::test]
-    async fn should_return_edit_response() {
+    async fn should_create_and_retrieve_batch() {
         let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_batches_path()))
+            .and(body_json(serde_json::json!({
+                "input_file_id": "file-abc123",
+                "endpoint": "/v1/chat/completions",
+                "completion_window": "24h"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("batch_response")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(config.get_batch_path("batch_abc123")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("batch_response")))
+            .mount(&server)
+            .await;
 
-        let request = EditRequestBuilder::default()
-            .model("text-davinci-edit-001")
-            .input("What day of the wek is it?")
-            .instruction("Fix the spelling mistakes")
+        let client = OpenAiClient::new(config);
+        let request = CreateBatchRequestBuilder::default()
+            .input_file_id("file-abc123")
+            .endpoint(BatchEndpoint::ChatCompletions)
             .build()
             .unwrap();
+        let batch = client.create_batch(request).await.expect("expected batch");
+        assert_eq!(batch.object, ObjectType::Batch);
+        assert_eq!(batch.status, BatchStatus::Validating);
+        let batch = client
+            .retrieve_batch("batch_abc123")
+            .await
+            .expect("expected batch");
+        assert_eq!(batch.input_file_id, "file-abc123");
+        assert_eq!(batch.request_counts.total, 0);
+    }
 
-        let json = serde_json::to_value(&request).expect("request serialized");
-
+    #[tokio::test]
+    async fn should_list_and_cancel_batches() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_batches_path()))
+            .and(query_param("after", "batch_abc122"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [json_response("batch_response")],
+                "first_id": "batch_abc123",
+                "last_id": "batch_abc123",
+                "has_more": false
+            })))
+            .mount(&server)
+            .await;
         Mock::given(method("POST"))
-            .and(path(config.get_edit_path()))
-            .and(body_json(json))
-            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("edit_response")))
+            .and(path(config.get_batch_cancel_path("batch_abc123")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("batch_response")))
             .mount(&server)
             .await;
 
         let client = OpenAiClient::new(config);
-        match client.create_edit(request).await {
-            Ok(_) => assert!(true),
-            Err(e) => {
-                println!("ERR: {:?}", e);
-                assert!(false, "expected success response")
-            }
-        }
+        let params = ListParams {
+            after: Some("batch_abc122".to_string()),
+            ..Default::default()
+        };
+        let batches = client
+            .list_batches(params)
+            .await
+            .expect("expected batches")
+            .into_data();
+        assert_eq!(batches.data.len(), 1);
+        assert!(!batches.has_more);
+        client
+            .cancel_batch("batch_abc123")
+            .await
+            .expect("expected batch");
     }
 
     #[tokio::test]
-    async fn should_return_completion_response() {
+    async fn should_apply_completion_defaults_from_config() {
         let (config, server) = create_test_server_config().await;
+        let config = config.with_completion_defaults(CompletionDefaults {
+            max_tokens: Some(2048),
+            temperature: Some(0.7),
+            ..Default::default()
+        });
 
         let request = CompletionRequestBuilder::default()
             .model("text-davinci-003")
             .prompt("I am so tired I could")
+            .max_tokens(16)
             .build()
             .unwrap();
 
-        let json = serde_json::to_value(&request).expect("request serialized");
+        let json = serde_json::json!({
+            "model": "text-davinci-003",
+            "prompt": "I am so tired I could",
+            "max_tokens": 16,
+            "temperature": 0.7,
+        });
 
         Mock::given(method("POST"))
             .and(path(config.get_completion_path()))
@@ -215,12 +3613,8 @@ mod request_client {
             .await;
 
         let client = OpenAiClient::new(config);
-        match client.create_completion(request).await {
-            Ok(_) => assert!(true),
-            Err(e) => {
-                println!("ERR: {:?}", e);
-                assert!(false, "expected success response")
-            }
+        if let Err(e) = client.create_completion(request).await {
+            panic!("expected success response, got {:?}", e)
         }
     }
 
@@ -1,15 +1,225 @@
 use crate::types::TextResult;
 use crate::{
-    CompletionRequest, CreateImageRequest, EditRequest, ImageResult, OpenAiModel,
-    OpenAiModelResponse, OpenAiResult,
+    ApiResponse, Assistant, Batch, ChatCompletionRequest, ChatCompletionResponse, ChatStreamChunk,
+    CompletionRequest, CreateAssistantRequest, CreateBatchRequest, CreateFineTuningJobRequest,
+    CreateImageRequest, CreateMessageRequest, CreateRunRequest, CreateThreadRequest,
+    DeleteResponse, EditImageRequest, EditRequest, EmbeddingRequest, EmbeddingResponse, FileList,
+    FileObject, FineTuningEventList, FineTuningJob, FineTuningJobList, ImageResult,
+    ImageVariationRequest, ListParams, ListResponse, ModerationRequest, ModerationResponse,
+    ModifyAssistantRequest, ModifyMessageRequest, ModifyThreadRequest, OpenAiError, OpenAiModel,
+    OpenAiModelResponse, OpenAiResult, OpenAiStream, Run, RunStatus, SpeechRequest, StreamChunk,
+    SubmitToolOutputsRequest, Thread, ThreadMessageObject, TranscriptionRequest,
+    TranscriptionResponse, TranslationRequest, TranslationResponse, UploadFileRequest,
 };
 use async_trait::async_trait;
+use bytes::Bytes;
+use std::time::Duration;
 
 #[async_trait]
-pub trait ClientApi {
-    async fn create_completion(&self, request: CompletionRequest) -> OpenAiResult<TextResult>;
-    async fn create_edit(&self, request: EditRequest) -> OpenAiResult<TextResult>;
-    async fn get_models(&self) -> OpenAiResult<OpenAiModelResponse>;
-    async fn get_model(&self, model: &str) -> OpenAiResult<OpenAiModel>;
-    async fn create_image(&self, request: CreateImageRequest) -> OpenAiResult<ImageResult>;
+pub trait ClientApi: Send + Sync {
+    async fn create_completion(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<TextResult>>;
+    /// Creates a completion streamed as server sent events. The `stream`
+    /// flag of the request is set automatically.
+    async fn create_completion_stream(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<StreamChunk>>>;
+    async fn create_chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<ChatCompletionResponse>>;
+    /// Creates a chat completion streamed as server sent events. The
+    /// `stream` flag of the request is set automatically.
+    async fn create_chat_completion_stream(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<ChatStreamChunk>>>;
+    async fn create_edit(&self, request: EditRequest) -> OpenAiResult<ApiResponse<TextResult>>;
+    async fn create_embedding(
+        &self,
+        request: EmbeddingRequest,
+    ) -> OpenAiResult<ApiResponse<EmbeddingResponse>>;
+    async fn create_moderation(
+        &self,
+        request: ModerationRequest,
+    ) -> OpenAiResult<ApiResponse<ModerationResponse>>;
+    /// Generates speech from text and returns the raw audio.
+    async fn create_speech(&self, request: SpeechRequest) -> OpenAiResult<ApiResponse<Bytes>>;
+    async fn transcribe_audio(
+        &self,
+        request: TranscriptionRequest,
+    ) -> OpenAiResult<ApiResponse<TranscriptionResponse>>;
+    /// Translates the audio into English text.
+    async fn translate_audio(
+        &self,
+        request: TranslationRequest,
+    ) -> OpenAiResult<ApiResponse<TranslationResponse>>;
+    async fn upload_file(
+        &self,
+        request: UploadFileRequest,
+    ) -> OpenAiResult<ApiResponse<FileObject>>;
+    async fn list_files(&self) -> OpenAiResult<ApiResponse<FileList>>;
+    async fn retrieve_file(&self, id: &str) -> OpenAiResult<ApiResponse<FileObject>>;
+    async fn delete_file(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>>;
+    /// Returns the raw content of a file.
+    async fn retrieve_file_content(&self, id: &str) -> OpenAiResult<ApiResponse<Bytes>>;
+    async fn create_fine_tuning_job(
+        &self,
+        request: CreateFineTuningJobRequest,
+    ) -> OpenAiResult<ApiResponse<FineTuningJob>>;
+    async fn list_fine_tuning_jobs(&self) -> OpenAiResult<ApiResponse<FineTuningJobList>>;
+    async fn retrieve_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>>;
+    async fn cancel_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>>;
+    /// Lists the events of a job, starting after the given event id.
+    async fn list_fine_tuning_events(
+        &self,
+        id: &str,
+        after: Option<&str>,
+    ) -> OpenAiResult<ApiResponse<FineTuningEventList>>;
+    async fn create_assistant(
+        &self,
+        request: CreateAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>>;
+    async fn retrieve_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<Assistant>>;
+    async fn modify_assistant(
+        &self,
+        id: &str,
+        request: ModifyAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>>;
+    async fn delete_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>>;
+    async fn list_assistants(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Assistant>>>;
+    async fn create_thread(
+        &self,
+        request: CreateThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>>;
+    async fn retrieve_thread(&self, id: &str) -> OpenAiResult<ApiResponse<Thread>>;
+    async fn modify_thread(
+        &self,
+        id: &str,
+        request: ModifyThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>>;
+    async fn delete_thread(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>>;
+    async fn create_message(
+        &self,
+        thread_id: &str,
+        request: CreateMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>>;
+    async fn list_messages(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<ThreadMessageObject>>>;
+    async fn retrieve_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>>;
+    async fn modify_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+        request: ModifyMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>>;
+    async fn delete_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<DeleteResponse>>;
+    async fn create_run(
+        &self,
+        thread_id: &str,
+        request: CreateRunRequest,
+    ) -> OpenAiResult<ApiResponse<Run>>;
+    async fn retrieve_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>>;
+    async fn list_runs(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Run>>>;
+    async fn cancel_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>>;
+    async fn submit_tool_outputs(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        request: SubmitToolOutputsRequest,
+    ) -> OpenAiResult<ApiResponse<Run>>;
+
+    /// Retrieves the run every interval until it is terminal or requires an
+    /// action. Failed and expired runs are returned as
+    /// [OpenAiError::RunFailed].
+    async fn poll_run(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        interval: Duration,
+    ) -> OpenAiResult<ApiResponse<Run>> {
+        loop {
+            let response = self.retrieve_run(thread_id, run_id).await?;
+            match response.status {
+                RunStatus::Failed | RunStatus::Expired => {
+                    let run = response.into_data();
+                    return Err(OpenAiError::RunFailed {
+                        run_id: run.id,
+                        status: run.status,
+                        error: run.last_error,
+                    });
+                }
+                status if status.is_terminal() || status == RunStatus::RequiresAction => {
+                    return Ok(response)
+                }
+                _ => tokio::time::sleep(interval).await,
+            }
+        }
+    }
+
+    async fn create_batch(&self, request: CreateBatchRequest) -> OpenAiResult<ApiResponse<Batch>>;
+    async fn retrieve_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>>;
+    async fn cancel_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>>;
+    async fn list_batches(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Batch>>>;
+    async fn get_models(&self) -> OpenAiResult<ApiResponse<OpenAiModelResponse>>;
+    async fn get_model(&self, model: &str) -> OpenAiResult<ApiResponse<OpenAiModel>>;
+    async fn create_image(
+        &self,
+        request: CreateImageRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>>;
+    async fn edit_image(&self, request: EditImageRequest)
+        -> OpenAiResult<ApiResponse<ImageResult>>;
+    async fn create_image_variation(
+        &self,
+        request: ImageVariationRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>>;
+}
+
+#[cfg(test)]
+mod send_sync {
+    use super::*;
+    use crate::{AggregatingClient, CircuitBreakerClient, MultiKeyClient, OpenAiClient};
+    use std::sync::Arc;
+
+    const fn assert_send_sync<T: Send + Sync + ?Sized>() {}
+
+    const _: () = assert_send_sync::<OpenAiClient>();
+    const _: () = assert_send_sync::<MultiKeyClient>();
+    const _: () = assert_send_sync::<AggregatingClient<OpenAiClient>>();
+    const _: () = assert_send_sync::<CircuitBreakerClient<OpenAiClient>>();
+    const _: () = assert_send_sync::<Box<dyn ClientApi>>();
+    const _: () = assert_send_sync::<Arc<dyn ClientApi>>();
+
+    #[tokio::test]
+    async fn should_use_client_in_spawned_task() {
+        let client: Arc<dyn ClientApi> = Arc::new(OpenAiClient::default());
+        let handle = tokio::spawn(async move {
+            let _ = &client;
+        });
+        assert!(handle.await.is_ok());
+    }
 }
@@ -0,0 +1,433 @@
+use crate::{CompletionRequest, OpenAiError, OpenAiResult};
+
+/// Rough number of characters per token for english text.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Number of completion tokens the Api uses if max_tokens is not set.
+const DEFAULT_MAX_TOKENS: u32 = 16;
+
+/// Known context window sizes in tokens by model name prefix. More specific
+/// prefixes need to come first.
+const CONTEXT_WINDOWS: &[(&str, u32)] = &[
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4-32k", 32_768),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo-instruct", 4_096),
+    ("gpt-3.5-turbo", 16_385),
+    ("text-davinci-003", 4_097),
+    ("text-davinci-002", 4_097),
+    ("code-davinci-002", 8_001),
+    ("davinci-002", 16_384),
+    ("babbage-002", 16_384),
+    ("text-curie-001", 2_049),
+    ("text-babbage-001", 2_049),
+    ("text-ada-001", 2_049),
+];
+
+/// Returns the context window in tokens of a known model.
+pub fn context_window(model: &str) -> Option<u32> {
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, size)| *size)
+}
+
+/// Roughly estimates the number of tokens of a text without a tokenizer.
+pub fn estimate_tokens(text: &str) -> u32 {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN) as u32
+}
+
+/// Pre-flight check whether the estimated prompt tokens plus the requested
+/// completion tokens exceed the context window of the model. The prompt
+/// size is only estimated, so this is no guarantee the Api accepts the
+/// request.
+pub fn would_exceed_context(request: &CompletionRequest, model: &str) -> OpenAiResult<bool> {
+    let window = context_window(model).ok_or_else(|| OpenAiError::InvalidRequestParameter {
+        name: "model".to_string(),
+        reason: format!("unknown context window for model {}", model),
+    })?;
+    let max_tokens = request
+        .max_tokens
+        .map_or(u64::from(DEFAULT_MAX_TOKENS), |t| t.max(0) as u64);
+    Ok(u64::from(estimate_prompt_tokens(request)) + max_tokens > u64::from(window))
+}
+
+/// Checks the estimated prompt tokens plus max_tokens against the context
+/// window of the request model and fails with
+/// [OpenAiError::ContextWindowExceeded] if the window is exceeded. The
+/// prompt size is only estimated, so an unset max_tokens is left to the Api
+/// default instead of being derived from the estimate. Requests for models
+/// with unknown context window are returned unchanged.
+pub(crate) fn check_context_window(request: CompletionRequest) -> OpenAiResult<CompletionRequest> {
+    let Some(window) = context_window(&request.model) else {
+        return Ok(request);
+    };
+    let prompt_tokens = estimate_prompt_tokens(&request);
+    let max_tokens = request.max_tokens.map_or(0, |t| t.max(0) as u64);
+    if u64::from(prompt_tokens) + max_tokens > u64::from(window) || prompt_tokens >= window {
+        return Err(OpenAiError::ContextWindowExceeded {
+            model: request.model,
+            max_tokens: window,
+            prompt_tokens,
+        });
+    }
+    Ok(request)
+}
+
+/// A total token budget split between the prompt and the completion of a
+/// request. Prompt tokens are estimated with [estimate_tokens], so the budget
+/// is approximate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenBudget {
+    pub total_budget: u32,
+    /// The model the budget is used with. A budget larger than the known
+    /// context window of the model is capped to the window.
+    pub model: String,
+}
+
+impl TokenBudget {
+    pub fn new(total_budget: u32, model: &str) -> Self {
+        TokenBudget {
+            total_budget,
+            model: model.to_string(),
+        }
+    }
+
+    /// Returns the tokens left for the completion of the given prompt. Fails
+    /// with [OpenAiError::InvalidRequestParameter] if the prompt alone uses
+    /// up the budget.
+    pub fn remaining_for_completion(&self, prompt: &str) -> OpenAiResult<u32> {
+        self.remaining(estimate_tokens(prompt))
+    }
+
+    /// Sets max_tokens of the request to the budget left after its prompt.
+    /// Fails with [OpenAiError::InvalidRequestParameter] if the prompt alone
+    /// uses up the budget.
+    pub fn apply_to_request(&self, request: &mut CompletionRequest) -> OpenAiResult<()> {
+        let remaining = self.remaining(estimate_prompt_tokens(request))?;
+        request.max_tokens = Some(i64::from(remaining));
+        Ok(())
+    }
+
+    fn remaining(&self, prompt_tokens: u32) -> OpenAiResult<u32> {
+        let budget = context_window(&self.model)
+            .map_or(self.total_budget, |window| window.min(self.total_budget));
+        match budget.checked_sub(prompt_tokens) {
+            Some(remaining) if remaining > 0 => Ok(remaining),
+            _ => Err(OpenAiError::InvalidRequestParameter {
+                name: "prompt".to_string(),
+                reason: format!(
+                    "estimated {} prompt tokens exceed the token budget of {}",
+                    prompt_tokens, budget
+                ),
+            }),
+        }
+    }
+}
+
+impl CompletionRequest {
+    /// Estimates the tokens billed for the request. The Api generates
+    /// `best_of` completions server side, each using the prompt and up to
+    /// max_tokens completion tokens.
+    pub fn estimated_tokens_billed(&self, prompt_tokens: u32) -> u32 {
+        let completions = self.best_of.or(self.n).map_or(1, |n| n.max(1) as u32);
+        let max_tokens = self
+            .max_tokens
+            .map_or(DEFAULT_MAX_TOKENS, |t| t.max(0) as u32);
+        completions.saturating_mul(prompt_tokens.saturating_add(max_tokens))
+    }
+}
+
+/// Returns a warning if the estimated billed tokens of the request exceed
+/// the threshold.
+pub fn warn_if_expensive(
+    request: &CompletionRequest,
+    prompt_tokens: u32,
+    threshold_tokens: u32,
+) -> Option<String> {
+    let billed = request.estimated_tokens_billed(prompt_tokens);
+    (billed > threshold_tokens).then(|| {
+        format!(
+            "completion request for model {} may bill up to {} tokens (threshold {}), check n, best_of and max_tokens",
+            request.model, billed, threshold_tokens
+        )
+    })
+}
+
+pub(crate) fn estimate_prompt_tokens(request: &CompletionRequest) -> u32 {
+    request
+        .prompt
+        .iter()
+        .flat_map(|p| p.iter())
+        .map(estimate_tokens)
+        .sum()
+}
+
+/// Converts an Api error about an exceeded context length into the
+/// structured [OpenAiError::ContextWindowExceeded]. Other errors are
+/// returned unchanged.
+pub(crate) fn map_context_window_error(err: OpenAiError, model: &str) -> OpenAiError {
+    let OpenAiError::ApiErrorResponse(details) = &err else {
+        return err;
+    };
+    if !details.message.contains("maximum context length") {
+        return err;
+    }
+    let context_window = number_after(&details.message, "maximum context length is");
+    let prompt_tokens = number_before(&details.message, "in your prompt")
+        .or_else(|| number_before(&details.message, "in the messages"))
+        .or_else(|| number_after(&details.message, "resulted in"));
+    match (context_window, prompt_tokens) {
+        (Some(context_window), Some(prompt_tokens)) => OpenAiError::ContextWindowExceeded {
+            model: model.to_string(),
+            max_tokens: context_window,
+            prompt_tokens,
+        },
+        _ => err,
+    }
+}
+
+fn number_after(text: &str, marker: &str) -> Option<u32> {
+    let rest = &text[text.find(marker)? + marker.len()..];
+    let digits: String = rest
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+fn number_before(text: &str, marker: &str) -> Option<u32> {
+    let before = text[..text.find(marker)?].trim_end();
+    let digits: String = before
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod context_window {
+    use super::*;
+    use crate::{CompletionRequestBuilder, OpenAiErrorDetails};
+
+    fn api_error(message: &str) -> OpenAiError {
+        OpenAiError::ApiErrorResponse(OpenAiErrorDetails {
+            code: Some("context_length_exceeded".to_string()),
+            message: message.to_string(),
+            param: None,
+            error_type: Some("invalid_request_error".to_string()),
+        })
+    }
+
+    #[test]
+    fn should_parse_completion_context_error() {
+        let err = api_error(
+            "This model's maximum context length is 4097 tokens, however you requested 5021 \
+             tokens (4921 in your prompt; 100 for the completion). Please reduce your prompt; \
+             or completion length.",
+        );
+        match map_context_window_error(err, "text-davinci-003") {
+            OpenAiError::ContextWindowExceeded {
+                model,
+                max_tokens,
+                prompt_tokens,
+            } => {
+                assert_eq!(model, "text-davinci-003");
+                assert_eq!(max_tokens, 4097);
+                assert_eq!(prompt_tokens, 4921);
+            }
+            _ => panic!("expected context window error"),
+        }
+    }
+
+    #[test]
+    fn should_parse_messages_and_completion_context_error() {
+        let err = api_error(
+            "This model's maximum context length is 8192 tokens. However, you requested 10000 \
+             tokens (9000 in the messages, 1000 in the completion). Please reduce the length \
+             of the messages or completion.",
+        );
+        match map_context_window_error(err, "gpt-4") {
+            OpenAiError::ContextWindowExceeded {
+                max_tokens,
+                prompt_tokens,
+                ..
+            } => {
+                assert_eq!(max_tokens, 8192);
+                assert_eq!(prompt_tokens, 9000);
+            }
+            _ => panic!("expected context window error"),
+        }
+    }
+
+    #[test]
+    fn should_keep_context_error_without_prompt_tokens() {
+        let err = api_error(
+            "This model's maximum context length is 8192 tokens. However, you requested 10000 \
+             tokens.",
+        );
+        assert!(matches!(
+            map_context_window_error(err, "gpt-4"),
+            OpenAiError::ApiErrorResponse(_)
+        ));
+    }
+
+    #[test]
+    fn should_parse_messages_context_error() {
+        let err = api_error(
+            "This model's maximum context length is 8192 tokens. However, your messages \
+             resulted in 9000 tokens. Please reduce the length of the messages.",
+        );
+        match map_context_window_error(err, "gpt-4") {
+            OpenAiError::ContextWindowExceeded {
+                max_tokens,
+                prompt_tokens,
+                ..
+            } => {
+                assert_eq!(max_tokens, 8192);
+                assert_eq!(prompt_tokens, 9000);
+            }
+            _ => panic!("expected context window error"),
+        }
+    }
+
+    #[test]
+    fn should_keep_other_errors() {
+        match map_context_window_error(OpenAiError::invalid_api_key(), "gpt-4") {
+            OpenAiError::ApiErrorResponse(details) => {
+                assert_eq!(details.code, Some("invalid_api_key".to_string()))
+            }
+            _ => panic!("expected api error response"),
+        }
+        match map_context_window_error(api_error("maximum context length unknown"), "gpt-4") {
+            OpenAiError::ApiErrorResponse(_) => {}
+            _ => panic!("expected api error response"),
+        }
+    }
+
+    #[test]
+    fn should_check_context_before_request() {
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("a".repeat(8000).as_str())
+            .max_tokens(3000)
+            .build()
+            .unwrap();
+        assert!(would_exceed_context(&request, "text-davinci-003").unwrap());
+        assert!(!would_exceed_context(&request, "gpt-4").unwrap());
+        if would_exceed_context(&request, "unknown-model").is_ok() {
+            panic!("expected unknown model error")
+        }
+    }
+
+    #[test]
+    fn should_not_overflow_on_large_max_tokens() {
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("a".repeat(40).as_str())
+            .max_tokens(i64::MAX)
+            .build()
+            .unwrap();
+        assert!(would_exceed_context(&request, "text-davinci-003").unwrap());
+        match check_context_window(request) {
+            Err(OpenAiError::ContextWindowExceeded { max_tokens, .. }) => {
+                assert_eq!(max_tokens, 4_097)
+            }
+            _ => panic!("expected context window error"),
+        }
+    }
+
+    #[test]
+    fn should_keep_unset_max_tokens() {
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("a".repeat(40).as_str())
+            .build()
+            .unwrap();
+        assert_eq!(check_context_window(request).unwrap().max_tokens, None);
+    }
+
+    #[test]
+    fn should_lookup_context_windows() {
+        assert_eq!(context_window("gpt-4-0613"), Some(8_192));
+        assert_eq!(context_window("gpt-4-32k-0613"), Some(32_768));
+        assert_eq!(context_window("gpt-3.5-turbo-instruct"), Some(4_096));
+        assert_eq!(context_window("unknown"), None);
+        assert_eq!(estimate_tokens("abcdefgh"), 2);
+        assert_eq!(estimate_tokens("abcdefghi"), 3);
+    }
+
+    #[test]
+    fn should_estimate_tokens_billed() {
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .n(10)
+            .best_of(10)
+            .max_tokens(100)
+            .build()
+            .unwrap();
+        assert_eq!(request.estimated_tokens_billed(50), 1_500);
+
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .n(2)
+            .build()
+            .unwrap();
+        assert_eq!(request.estimated_tokens_billed(4), 40);
+    }
+
+    #[test]
+    fn should_warn_for_expensive_requests() {
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .best_of(10)
+            .max_tokens(100)
+            .build()
+            .unwrap();
+        let warning = warn_if_expensive(&request, 50, 1_000).unwrap();
+        assert!(warning.contains("1500 tokens"));
+        assert!(warn_if_expensive(&request, 50, 1_500).is_none());
+    }
+
+    #[test]
+    fn should_compute_remaining_budget() {
+        let budget = TokenBudget::new(100, "text-davinci-003");
+        assert_eq!(
+            budget.remaining_for_completion(&"a".repeat(40)).unwrap(),
+            90
+        );
+        assert_eq!(budget.remaining_for_completion("").unwrap(), 100);
+        assert!(matches!(
+            budget.remaining_for_completion(&"a".repeat(400)),
+            Err(OpenAiError::InvalidRequestParameter { .. })
+        ));
+        let budget = TokenBudget::new(10_000, "text-davinci-003");
+        assert_eq!(budget.remaining_for_completion("").unwrap(), 4_097);
+    }
+
+    #[test]
+    fn should_apply_budget_to_request() {
+        let budget = TokenBudget::new(50, "unknown-model");
+        let mut request = CompletionRequestBuilder::default()
+            .model("unknown-model")
+            .prompt("a".repeat(80).as_str())
+            .max_tokens(1_000)
+            .build()
+            .unwrap();
+        budget.apply_to_request(&mut request).unwrap();
+        assert_eq!(request.max_tokens, Some(30));
+
+        let budget = TokenBudget::new(20, "unknown-model");
+        assert!(matches!(
+            budget.apply_to_request(&mut request),
+            Err(OpenAiError::InvalidRequestParameter { .. })
+        ));
+        assert_eq!(request.max_tokens, Some(30));
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{ObjectType, Serializable, StringOrListParam, Usage};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Encoding of the returned embeddings.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingFormat {
+    Float,
+    Base64,
+}
+
+/// Json data required for creating embeddings of one or multiple inputs.
+#[derive(Serialize, Deserialize, Builder, Debug, Clone, PartialEq)]
+#[builder(setter(strip_option, into))]
+pub struct EmbeddingRequest {
+    pub model: String,
+    pub input: StringOrListParam,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_format: Option<EmbeddingFormat>,
+    /// Number of dimensions of the embeddings, only supported by
+    /// text-embedding-3 and later models.
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+impl Serializable for EmbeddingRequest {}
+
+/// The embedding of a single input.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EmbeddingItem {
+    pub index: usize,
+    /// Base64 encoded embeddings are decoded when deserialized.
+    #[serde(deserialize_with = "deserialize_embedding")]
+    pub embedding: Vec<f64>,
+}
+
+/// Response of an embedding request with one item per input.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EmbeddingResponse {
+    pub object: ObjectType,
+    pub data: Vec<EmbeddingItem>,
+    pub model: String,
+    #[serde(default)]
+    pub usage: Usage,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EncodedEmbedding {
+    Float(Vec<f64>),
+    Base64(String),
+}
+
+/// Base64 embeddings are little endian f32 values.
+fn deserialize_embedding<'de, D>(deserializer: D) -> Result<Vec<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match EncodedEmbedding::deserialize(deserializer)? {
+        EncodedEmbedding::Float(values) => Ok(values),
+        EncodedEmbedding::Base64(encoded) => {
+            let bytes = STANDARD.decode(encoded).map_err(serde::de::Error::custom)?;
+            if bytes.len() % 4 != 0 {
+                return Err(serde::de::Error::custom(
+                    "base64 embedding is not a list of f32 values",
+                ));
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64)
+                .collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod embeddings {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_serialize_embedding_request() {
+        let request = EmbeddingRequestBuilder::default()
+            .model("text-embedding-3-small")
+            .input(StringOrListParam::ListParam(vec![
+                "first".to_string(),
+                "second".to_string(),
+            ]))
+            .encoding_format(EmbeddingFormat::Float)
+            .dimensions(256u32)
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "model": "text-embedding-3-small",
+                "input": ["first", "second"],
+                "encoding_format": "float",
+                "dimensions": 256
+            })
+        );
+    }
+
+    #[test]
+    fn should_decode_base64_embedding() {
+        let bytes: Vec<u8> = [0.5f32, -1.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let item: EmbeddingItem = serde_json::from_value(json!({
+            "object": "embedding",
+            "index": 0,
+            "embedding": STANDARD.encode(bytes)
+        }))
+        .unwrap();
+        assert_eq!(item.embedding, vec![0.5, -1.0]);
+
+        assert!(serde_json::from_value::<EmbeddingItem>(json!({
+            "index": 0,
+            "embedding": "AAA="
+        }))
+        .is_err());
+    }
+}
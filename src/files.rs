@@ -0,0 +1,64 @@
+use crate::multipart::FormExt;
+use crate::ObjectType;
+use reqwest::multipart::Form;
+use serde::{Deserialize, Serialize};
+
+/// Intended use of an uploaded file.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilePurpose {
+    #[serde(rename = "fine-tune")]
+    FineTune,
+    #[serde(rename = "assistants")]
+    Assistants,
+    #[serde(rename = "batch")]
+    Batch,
+    #[serde(rename = "vision")]
+    Vision,
+}
+
+impl FilePurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilePurpose::FineTune => "fine-tune",
+            FilePurpose::Assistants => "assistants",
+            FilePurpose::Batch => "batch",
+            FilePurpose::Vision => "vision",
+        }
+    }
+}
+
+/// Data required for uploading a file. It is sent as multipart form.
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(into))]
+pub struct UploadFileRequest {
+    pub file_bytes: Vec<u8>,
+    pub file_name: String,
+    pub purpose: FilePurpose,
+}
+
+impl UploadFileRequest {
+    pub(crate) fn form(&self) -> Form {
+        Form::new()
+            .text("purpose", self.purpose.as_str().to_string())
+            .file_bytes("file", &self.file_name, &self.file_bytes)
+    }
+}
+
+/// Metadata of an uploaded file. The purpose is kept as string as the Api
+/// also returns purposes of generated files like `fine-tune-results`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileObject {
+    pub id: String,
+    pub bytes: u64,
+    pub created_at: i64,
+    pub filename: String,
+    pub object: ObjectType,
+    pub purpose: String,
+}
+
+/// Files list response.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileList {
+    pub data: Vec<FileObject>,
+    pub object: ObjectType,
+}
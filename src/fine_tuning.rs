@@ -0,0 +1,183 @@
+use crate::{ObjectType, Serializable};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// A hyperparameter that is either chosen by the Api or set explicitly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HyperparameterValue<T> {
+    Auto,
+    Value(T),
+}
+
+impl<T: Serialize> Serialize for HyperparameterValue<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            HyperparameterValue::Auto => serializer.serialize_str("auto"),
+            HyperparameterValue::Value(value) => value.serialize(serializer),
+        }
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for HyperparameterValue<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::String(value) if value == "auto" => Ok(HyperparameterValue::Auto),
+            value => serde_json::from_value(value)
+                .map(HyperparameterValue::Value)
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// Hyperparameters of a fine-tuning job.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Hyperparameters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n_epochs: Option<HyperparameterValue<u32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub learning_rate_multiplier: Option<HyperparameterValue<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<HyperparameterValue<u32>>,
+}
+
+/// Json data required for creating a fine-tuning job from uploaded files.
+#[derive(Serialize, Deserialize, Builder, Debug, Clone, PartialEq)]
+#[builder(setter(strip_option, into))]
+pub struct CreateFineTuningJobRequest {
+    pub training_file: String,
+    pub model: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation_file: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hyperparameters: Option<Hyperparameters>,
+    /// Up to 18 characters added to the fine-tuned model name.
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+}
+
+impl Serializable for CreateFineTuningJobRequest {}
+
+/// Status of a fine-tuning job.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FineTuningJobStatus {
+    ValidatingFiles,
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl FineTuningJobStatus {
+    /// Returns true if the job will not change anymore.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            FineTuningJobStatus::Succeeded
+                | FineTuningJobStatus::Failed
+                | FineTuningJobStatus::Cancelled
+        )
+    }
+}
+
+/// Reason of a failed fine-tuning job.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FineTuningJobError {
+    pub code: String,
+    pub message: String,
+    pub param: Option<String>,
+}
+
+/// A fine-tuning job.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FineTuningJob {
+    pub id: String,
+    pub object: ObjectType,
+    pub created_at: i64,
+    pub finished_at: Option<i64>,
+    pub model: String,
+    pub fine_tuned_model: Option<String>,
+    pub organization_id: String,
+    pub status: FineTuningJobStatus,
+    pub hyperparameters: Hyperparameters,
+    pub training_file: String,
+    pub validation_file: Option<String>,
+    #[serde(default)]
+    pub result_files: Vec<String>,
+    pub trained_tokens: Option<i64>,
+    #[serde(default)]
+    pub error: Option<FineTuningJobError>,
+    #[serde(default)]
+    pub estimated_finish: Option<i64>,
+}
+
+/// Fine-tuning jobs list response.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FineTuningJobList {
+    pub object: ObjectType,
+    pub data: Vec<FineTuningJob>,
+    pub has_more: bool,
+}
+
+/// A status update of a fine-tuning job.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FineTuningEvent {
+    pub id: String,
+    pub object: ObjectType,
+    pub created_at: i64,
+    pub level: String,
+    pub message: String,
+    #[serde(default, rename = "type")]
+    pub event_type: Option<String>,
+    #[serde(default)]
+    pub data: Option<Value>,
+}
+
+/// A page of fine-tuning events.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FineTuningEventList {
+    pub object: ObjectType,
+    pub data: Vec<FineTuningEvent>,
+    pub has_more: bool,
+}
+
+impl FineTuningEventList {
+    /// Returns the cursor for requesting the next page, `None` on the last
+    /// page.
+    pub fn after(&self) -> Option<&str> {
+        self.data
+            .last()
+            .filter(|_| self.has_more)
+            .map(|event| event.id.as_str())
+    }
+}
+
+#[cfg(test)]
+mod hyperparameters {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_serialize_auto_and_explicit_values() {
+        let params = Hyperparameters {
+            n_epochs: Some(HyperparameterValue::Value(3)),
+            learning_rate_multiplier: Some(HyperparameterValue::Auto),
+            batch_size: None,
+        };
+        let value = serde_json::to_value(&params).unwrap();
+        assert_eq!(
+            value,
+            json!({"n_epochs": 3, "learning_rate_multiplier": "auto"})
+        );
+        assert_eq!(
+            serde_json::from_value::<Hyperparameters>(value).unwrap(),
+            params
+        );
+        assert!(serde_json::from_value::<Hyperparameters>(json!({"n_epochs": "many"})).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! Conversions between the OpenAi types of this crate and protobuf compatible
+//! structures as generated from `proto/openai.proto`. This allows proxying
+//! OpenAi requests through gRPC services.
+
+use crate::{CompletionRequest, ResponseFormat, StringOrListParam, TextChoice, TextResult, Usage};
+
+const RESPONSE_FORMAT_TEXT: &str = "text";
+const RESPONSE_FORMAT_JSON_OBJECT: &str = "json_object";
+const RESPONSE_FORMAT_JSON_SCHEMA: &str = "json_schema";
+
+/// Protobuf compatible mirrors of the OpenAi types, following the shape of
+/// code generated for `proto/openai.proto`.
+pub mod openai_proto {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct StringList {
+        pub values: Vec<String>,
+    }
+
+    pub mod completion_request {
+        use super::StringList;
+
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Prompt {
+            PromptText(String),
+            PromptList(StringList),
+        }
+
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Stop {
+            StopText(String),
+            StopList(StringList),
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct CompletionRequest {
+        pub model: String,
+        pub prompt: Option<completion_request::Prompt>,
+        pub suffix: Option<String>,
+        pub max_tokens: Option<i64>,
+        pub temperature: Option<f64>,
+        pub top_p: Option<f64>,
+        pub n: Option<i64>,
+        pub stream: Option<bool>,
+        pub logprobs: Option<i64>,
+        pub echo: Option<bool>,
+        pub stop: Option<completion_request::Stop>,
+        pub presence_penalty: Option<f64>,
+        pub frequency_penalty: Option<f64>,
+        pub best_of: Option<i64>,
+        pub logit_bias: HashMap<String, i64>,
+        pub user: Option<String>,
+        pub seed: Option<i64>,
+        pub response_format: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct TextResult {
+        pub id: Option<String>,
+        pub object: String,
+        pub created: i64,
+        pub model: Option<String>,
+        pub choices: Vec<TextChoice>,
+        pub usage: Option<Usage>,
+        pub system_fingerprint: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct TextChoice {
+        pub text: String,
+        pub index: i64,
+        pub logprobs: Option<i64>,
+        pub finish_reason: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Usage {
+        pub prompt_tokens: i64,
+        pub completion_tokens: Option<i64>,
+        pub total_tokens: i64,
+    }
+}
+
+use openai_proto::completion_request::{Prompt, Stop};
+use openai_proto::StringList;
+
+impl From<openai_proto::CompletionRequest> for CompletionRequest {
+    fn from(value: openai_proto::CompletionRequest) -> Self {
+        CompletionRequest {
+            model: value.model,
+            prompt: value.prompt.map(|p| match p {
+                Prompt::PromptText(text) => StringOrListParam::StringParam(text),
+                Prompt::PromptList(list) => StringOrListParam::ListParam(list.values),
+            }),
+            suffix: value.suffix,
+            max_tokens: value.max_tokens,
+            temperature: value.temperature,
+            top_p: value.top_p,
+            n: value.n,
+            stream: value.stream,
+            logprobs: value.logprobs,
+            echo: value.echo,
+            stop: value.stop.map(|s| match s {
+                Stop::StopText(text) => StringOrListParam::StringParam(text),
+                Stop::StopList(list) => StringOrListParam::ListParam(list.values),
+            }),
+            presence_penalty: value.presence_penalty,
+            frequency_penalty: value.frequency_penalty,
+            best_of: value.best_of,
+            logit_bias: Some(value.logit_bias).filter(|b| !b.is_empty()),
+            user: value.user,
+            seed: value.seed,
+            response_format: value.response_format.and_then(|f| match f.as_str() {
+                RESPONSE_FORMAT_TEXT => Some(ResponseFormat::Text),
+                RESPONSE_FORMAT_JSON_OBJECT => Some(ResponseFormat::JsonObject),
+                _ => None,
+            }),
+        }
+    }
+}
+
+impl From<CompletionRequest> for openai_proto::CompletionRequest {
+    fn from(value: CompletionRequest) -> Self {
+        openai_proto::CompletionRequest {
+            model: value.model,
+            prompt: value.prompt.map(|p| match p {
+                StringOrListParam::StringParam(text) => Prompt::PromptText(text),
+                StringOrListParam::ListParam(values) => Prompt::PromptList(StringList { values }),
+            }),
+            suffix: value.suffix,
+            max_tokens: value.max_tokens,
+            temperature: value.temperature,
+            top_p: value.top_p,
+            n: value.n,
+            stream: value.stream,
+            logprobs: value.logprobs,
+            echo: value.echo,
+            stop: value.stop.map(|s| match s {
+                StringOrListParam::StringParam(text) => Stop::StopText(text),
+                StringOrListParam::ListParam(values) => Stop::StopList(StringList { values }),
+            }),
+            presence_penalty: value.presence_penalty,
+            frequency_penalty: value.frequency_penalty,
+            best_of: value.best_of,
+            logit_bias: value.logit_bias.unwrap_or_default(),
+            user: value.user,
+            seed: value.seed,
+            response_format: value.response_format.map(|f| {
+                match f {
+                    ResponseFormat::Text => RESPONSE_FORMAT_TEXT,
+                    ResponseFormat::JsonObject => RESPONSE_FORMAT_JSON_OBJECT,
+                    ResponseFormat::JsonSchema { .. } => RESPONSE_FORMAT_JSON_SCHEMA,
+                }
+                .to_string()
+            }),
+        }
+    }
+}
+
+impl From<Usage> for openai_proto::Usage {
+    fn from(value: Usage) -> Self {
+        openai_proto::Usage {
+            prompt_tokens: value.prompt_tokens,
+            completion_tokens: value.completion_tokens,
+            total_tokens: value.total_tokens,
+        }
+    }
+}
+
+impl From<openai_proto::Usage> for Usage {
+    fn from(value: openai_proto::Usage) -> Self {
+        Usage {
+            prompt_tokens: value.prompt_tokens,
+            completion_tokens: value.completion_tokens,
+            total_tokens: value.total_tokens,
+        }
+    }
+}
+
+impl From<TextChoice> for openai_proto::TextChoice {
+    fn from(value: TextChoice) -> Self {
+        openai_proto::TextChoice {
+            text: value.text,
+            index: value.index,
+            logprobs: value.logprobs,
+            finish_reason: value.finish_reason.map(Into::into),
+        }
+    }
+}
+
+impl From<openai_proto::TextChoice> for TextChoice {
+    fn from(value: openai_proto::TextChoice) -> Self {
+        TextChoice {
+            text: value.text,
+            index: value.index,
+            logprobs: value.logprobs,
+            finish_reason: value.finish_reason.map(Into::into),
+        }
+    }
+}
+
+impl From<TextResult> for openai_proto::TextResult {
+    fn from(value: TextResult) -> Self {
+        openai_proto::TextResult {
+            id: value.id,
+            object: value.object.into(),
+            created: value.created,
+            model: value.model,
+            choices: value.choices.into_iter().map(Into::into).collect(),
+            usage: Some(value.usage.into()),
+            system_fingerprint: value.system_fingerprint,
+        }
+    }
+}
+
+impl From<openai_proto::TextResult> for TextResult {
+    fn from(value: openai_proto::TextResult) -> Self {
+        TextResult {
+            id: value.id,
+            object: value.object.into(),
+            created: value.created,
+            model: value.model,
+            system_fingerprint: value.system_fingerprint,
+            choices: value.choices.into_iter().map(Into::into).collect(),
+            usage: value.usage.map(Into::into).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod proto_conversion {
+    use super::*;
+    use crate::CompletionRequestBuilder;
+    use std::collections::HashMap;
+
+    #[test]
+    fn must_round_trip_completion_request() {
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt(vec!["a", "b"])
+            .stop("\n")
+            .max_tokens(10)
+            .seed(42)
+            .response_format(ResponseFormat::JsonObject)
+            .logit_bias(HashMap::from([("50256".to_string(), -100)]))
+            .build()
+            .unwrap();
+        let proto: openai_proto::CompletionRequest = request.clone().into();
+        assert_eq!(
+            proto.prompt,
+            Some(Prompt::PromptList(StringList {
+                values: vec!["a".to_string(), "b".to_string()]
+            }))
+        );
+        assert_eq!(proto.stop, Some(Stop::StopText("\n".to_string())));
+        assert_eq!(proto.seed, Some(42));
+        assert_eq!(proto.response_format.as_deref(), Some("json_object"));
+        assert_eq!(CompletionRequest::from(proto), request);
+    }
+
+    #[test]
+    fn must_round_trip_text_result() {
+        let result: TextResult = serde_json::from_value(serde_json::json!({
+            "id": "cmpl-1",
+            "object": "text_completion",
+            "created": 1671717820,
+            "model": "text-davinci-003",
+            "system_fingerprint": "fp_44709d6fcb",
+            "choices": [
+                {"text": "a", "index": 0, "logprobs": null, "finish_reason": "stop"},
+                {"text": "b", "index": 1, "logprobs": null, "finish_reason": "length"}
+            ],
+            "usage": {"prompt_tokens": 6, "completion_tokens": 16, "total_tokens": 22}
+        }))
+        .unwrap();
+        let expected = serde_json::to_value(&result).unwrap();
+
+        let proto: openai_proto::TextResult = result.into();
+        assert_eq!(proto.choices.len(), 2);
+        assert_eq!(proto.usage.as_ref().unwrap().total_tokens, 22);
+        assert_eq!(proto.system_fingerprint.as_deref(), Some("fp_44709d6fcb"));
+        let result = TextResult::from(proto);
+        assert_eq!(serde_json::to_value(&result).unwrap(), expected);
+    }
+}
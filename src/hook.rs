@@ -0,0 +1,705 @@
+use crate::{
+    ApiResponse, Assistant, Batch, ChatCompletionRequest, ChatCompletionResponse, ChatStreamChunk,
+    ClientApi, CompletionRequest, CreateAssistantRequest, CreateBatchRequest,
+    CreateFineTuningJobRequest, CreateImageRequest, CreateMessageRequest, CreateRunRequest,
+    CreateThreadRequest, DeleteResponse, EditImageRequest, EditRequest, EmbeddingRequest,
+    EmbeddingResponse, FileList, FileObject, FineTuningEventList, FineTuningJob, FineTuningJobList,
+    ImageResult, ImageVariationRequest, ListParams, ListResponse, MessageContent,
+    ModerationRequest, ModerationResponse, ModifyAssistantRequest, ModifyMessageRequest,
+    ModifyThreadRequest, OpenAiModel, OpenAiModelResponse, OpenAiResult, OpenAiStream, Run,
+    SpeechRequest, StreamChunk, SubmitToolOutputsRequest, TextResult, Thread, ThreadMessageObject,
+    TranscriptionRequest, TranscriptionResponse, TranslationRequest, TranslationResponse,
+    UploadFileRequest,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+use std::sync::Arc;
+
+const REDACTED: &str = "[REDACTED]";
+const MIN_PHONE_DIGITS: usize = 7;
+const MAX_PHONE_DIGITS: usize = 15;
+
+/// Post processing applied to successful Api responses. All methods pass the
+/// response through unchanged by default.
+///
+/// Streamed responses are passed to the chunk hooks one chunk at a time, so
+/// hooks never see text split across chunks as a whole.
+pub trait ResponseHook {
+    fn on_text_result(&self, result: TextResult) -> OpenAiResult<TextResult> {
+        Ok(result)
+    }
+
+    fn on_chat_completion(
+        &self,
+        response: ChatCompletionResponse,
+    ) -> OpenAiResult<ChatCompletionResponse> {
+        Ok(response)
+    }
+
+    fn on_models(&self, models: OpenAiModelResponse) -> OpenAiResult<OpenAiModelResponse> {
+        Ok(models)
+    }
+
+    fn on_model(&self, model: OpenAiModel) -> OpenAiResult<OpenAiModel> {
+        Ok(model)
+    }
+
+    fn on_image_result(&self, result: ImageResult) -> OpenAiResult<ImageResult> {
+        Ok(result)
+    }
+
+    fn on_stream_chunk(&self, chunk: StreamChunk) -> OpenAiResult<StreamChunk> {
+        Ok(chunk)
+    }
+
+    fn on_chat_stream_chunk(&self, chunk: ChatStreamChunk) -> OpenAiResult<ChatStreamChunk> {
+        Ok(chunk)
+    }
+}
+
+/// Client applying response hooks in the order they were added.
+pub struct HookedClient<C: ClientApi> {
+    inner: C,
+    hooks: Vec<Arc<dyn ResponseHook + Send + Sync>>,
+}
+
+impl<C: ClientApi> HookedClient<C> {
+    pub fn new(inner: C) -> Self {
+        HookedClient {
+            inner,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Adds a hook that runs after all previously added hooks.
+    pub fn with_hook(mut self, hook: impl ResponseHook + Send + Sync + 'static) -> Self {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    fn apply<T>(
+        &self,
+        result: OpenAiResult<ApiResponse<T>>,
+        f: impl Fn(&dyn ResponseHook, T) -> OpenAiResult<T>,
+    ) -> OpenAiResult<ApiResponse<T>> {
+        result?.try_map(|data| {
+            self.hooks
+                .iter()
+                .try_fold(data, |res, hook| f(hook.as_ref(), res))
+        })
+    }
+
+    fn apply_stream<T: Send + 'static>(
+        &self,
+        stream: OpenAiResult<ApiResponse<OpenAiStream<T>>>,
+        f: fn(&dyn ResponseHook, T) -> OpenAiResult<T>,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<T>>> {
+        let hooks = self.hooks.clone();
+        Ok(stream?.map(|stream| -> OpenAiStream<T> {
+            Box::pin(stream.map(move |chunk| {
+                hooks
+                    .iter()
+                    .try_fold(chunk?, |chunk, hook| f(hook.as_ref(), chunk))
+            }))
+        }))
+    }
+}
+
+#[async_trait]
+impl<C: ClientApi> ClientApi for HookedClient<C> {
+    async fn create_completion(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<TextResult>> {
+        let result = self.inner.create_completion(request).await;
+        self.apply(result, |hook, res| hook.on_text_result(res))
+    }
+
+    async fn create_completion_stream(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<StreamChunk>>> {
+        let stream = self.inner.create_completion_stream(request).await;
+        self.apply_stream(stream, |hook, chunk| hook.on_stream_chunk(chunk))
+    }
+
+    async fn create_chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<ChatCompletionResponse>> {
+        let result = self.inner.create_chat_completion(request).await;
+        self.apply(result, |hook, res| hook.on_chat_completion(res))
+    }
+
+    async fn create_chat_completion_stream(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<ChatStreamChunk>>> {
+        let stream = self.inner.create_chat_completion_stream(request).await;
+        self.apply_stream(stream, |hook, chunk| hook.on_chat_stream_chunk(chunk))
+    }
+
+    async fn create_edit(&self, request: EditRequest) -> OpenAiResult<ApiResponse<TextResult>> {
+        let result = self.inner.create_edit(request).await;
+        self.apply(result, |hook, res| hook.on_text_result(res))
+    }
+
+    async fn create_embedding(
+        &self,
+        request: EmbeddingRequest,
+    ) -> OpenAiResult<ApiResponse<EmbeddingResponse>> {
+        self.inner.create_embedding(request).await
+    }
+
+    async fn create_moderation(
+        &self,
+        request: ModerationRequest,
+    ) -> OpenAiResult<ApiResponse<ModerationResponse>> {
+        self.inner.create_moderation(request).await
+    }
+
+    async fn create_speech(&self, request: SpeechRequest) -> OpenAiResult<ApiResponse<Bytes>> {
+        self.inner.create_speech(request).await
+    }
+
+    async fn transcribe_audio(
+        &self,
+        request: TranscriptionRequest,
+    ) -> OpenAiResult<ApiResponse<TranscriptionResponse>> {
+        self.inner.transcribe_audio(request).await
+    }
+
+    async fn translate_audio(
+        &self,
+        request: TranslationRequest,
+    ) -> OpenAiResult<ApiResponse<TranslationResponse>> {
+        self.inner.translate_audio(request).await
+    }
+
+    async fn upload_file(
+        &self,
+        request: UploadFileRequest,
+    ) -> OpenAiResult<ApiResponse<FileObject>> {
+        self.inner.upload_file(request).await
+    }
+
+    async fn list_files(&self) -> OpenAiResult<ApiResponse<FileList>> {
+        self.inner.list_files().await
+    }
+
+    async fn retrieve_file(&self, id: &str) -> OpenAiResult<ApiResponse<FileObject>> {
+        self.inner.retrieve_file(id).await
+    }
+
+    async fn delete_file(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.inner.delete_file(id).await
+    }
+
+    async fn retrieve_file_content(&self, id: &str) -> OpenAiResult<ApiResponse<Bytes>> {
+        self.inner.retrieve_file_content(id).await
+    }
+
+    async fn create_fine_tuning_job(
+        &self,
+        request: CreateFineTuningJobRequest,
+    ) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.inner.create_fine_tuning_job(request).await
+    }
+
+    async fn list_fine_tuning_jobs(&self) -> OpenAiResult<ApiResponse<FineTuningJobList>> {
+        self.inner.list_fine_tuning_jobs().await
+    }
+
+    async fn retrieve_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.inner.retrieve_fine_tuning_job(id).await
+    }
+
+    async fn cancel_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.inner.cancel_fine_tuning_job(id).await
+    }
+
+    async fn list_fine_tuning_events(
+        &self,
+        id: &str,
+        after: Option<&str>,
+    ) -> OpenAiResult<ApiResponse<FineTuningEventList>> {
+        self.inner.list_fine_tuning_events(id, after).await
+    }
+
+    async fn create_assistant(
+        &self,
+        request: CreateAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.inner.create_assistant(request).await
+    }
+
+    async fn retrieve_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.inner.retrieve_assistant(id).await
+    }
+
+    async fn modify_assistant(
+        &self,
+        id: &str,
+        request: ModifyAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.inner.modify_assistant(id, request).await
+    }
+
+    async fn delete_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.inner.delete_assistant(id).await
+    }
+
+    async fn list_assistants(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Assistant>>> {
+        self.inner.list_assistants(params).await
+    }
+
+    async fn create_thread(
+        &self,
+        request: CreateThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>> {
+        self.inner.create_thread(request).await
+    }
+
+    async fn retrieve_thread(&self, id: &str) -> OpenAiResult<ApiResponse<Thread>> {
+        self.inner.retrieve_thread(id).await
+    }
+
+    async fn modify_thread(
+        &self,
+        id: &str,
+        request: ModifyThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>> {
+        self.inner.modify_thread(id, request).await
+    }
+
+    async fn delete_thread(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.inner.delete_thread(id).await
+    }
+
+    async fn create_message(
+        &self,
+        thread_id: &str,
+        request: CreateMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.inner.create_message(thread_id, request).await
+    }
+
+    async fn list_messages(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<ThreadMessageObject>>> {
+        self.inner.list_messages(thread_id, params).await
+    }
+
+    async fn retrieve_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.inner.retrieve_message(thread_id, message_id).await
+    }
+
+    async fn modify_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+        request: ModifyMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.inner
+            .modify_message(thread_id, message_id, request)
+            .await
+    }
+
+    async fn delete_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.inner.delete_message(thread_id, message_id).await
+    }
+
+    async fn create_run(
+        &self,
+        thread_id: &str,
+        request: CreateRunRequest,
+    ) -> OpenAiResult<ApiResponse<Run>> {
+        self.inner.create_run(thread_id, request).await
+    }
+
+    async fn retrieve_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>> {
+        self.inner.retrieve_run(thread_id, run_id).await
+    }
+
+    async fn list_runs(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Run>>> {
+        self.inner.list_runs(thread_id, params).await
+    }
+
+    async fn cancel_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>> {
+        self.inner.cancel_run(thread_id, run_id).await
+    }
+
+    async fn submit_tool_outputs(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        request: SubmitToolOutputsRequest,
+    ) -> OpenAiResult<ApiResponse<Run>> {
+        self.inner
+            .submit_tool_outputs(thread_id, run_id, request)
+            .await
+    }
+
+    async fn create_batch(&self, request: CreateBatchRequest) -> OpenAiResult<ApiResponse<Batch>> {
+        self.inner.create_batch(request).await
+    }
+
+    async fn retrieve_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>> {
+        self.inner.retrieve_batch(id).await
+    }
+
+    async fn cancel_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>> {
+        self.inner.cancel_batch(id).await
+    }
+
+    async fn list_batches(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Batch>>> {
+        self.inner.list_batches(params).await
+    }
+
+    async fn get_models(&self) -> OpenAiResult<ApiResponse<OpenAiModelResponse>> {
+        let result = self.inner.get_models().await;
+        self.apply(result, |hook, res| hook.on_models(res))
+    }
+
+    async fn get_model(&self, model: &str) -> OpenAiResult<ApiResponse<OpenAiModel>> {
+        let result = self.inner.get_model(model).await;
+        self.apply(result, |hook, res| hook.on_model(res))
+    }
+
+    async fn create_image(
+        &self,
+        request: CreateImageRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        let result = self.inner.create_image(request).await;
+        self.apply(result, |hook, res| hook.on_image_result(res))
+    }
+
+    async fn edit_image(
+        &self,
+        request: EditImageRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        let result = self.inner.edit_image(request).await;
+        self.apply(result, |hook, res| hook.on_image_result(res))
+    }
+
+    async fn create_image_variation(
+        &self,
+        request: ImageVariationRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        let result = self.inner.create_image_variation(request).await;
+        self.apply(result, |hook, res| hook.on_image_result(res))
+    }
+}
+
+/// Replaces email addresses and phone numbers in choice texts and chat messages with
+/// `[REDACTED]`.
+///
+/// **Streamed responses are only redacted per chunk.** An address or number
+/// split across two chunks is not detected and passes unredacted. Use the
+/// non streaming methods, or collect the stream and redact the result, if
+/// no PII must leak.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PiiRedactionHook;
+
+impl PiiRedactionHook {
+    /// Returns the text with email addresses and phone numbers redacted.
+    pub fn redact(text: &str) -> String {
+        redact_phone_numbers(&redact_emails(text))
+    }
+}
+
+impl ResponseHook for PiiRedactionHook {
+    fn on_text_result(&self, mut result: TextResult) -> OpenAiResult<TextResult> {
+        for choice in result.choices.iter_mut() {
+            choice.text = PiiRedactionHook::redact(&choice.text);
+        }
+        Ok(result)
+    }
+
+    fn on_chat_completion(
+        &self,
+        mut response: ChatCompletionResponse,
+    ) -> OpenAiResult<ChatCompletionResponse> {
+        for choice in response.choices.iter_mut() {
+            if let Some(MessageContent::Text(text)) = &mut choice.message.content {
+                *text = PiiRedactionHook::redact(text);
+            }
+        }
+        Ok(response)
+    }
+
+    fn on_stream_chunk(&self, mut chunk: StreamChunk) -> OpenAiResult<StreamChunk> {
+        for choice in chunk.choices.iter_mut() {
+            choice.text = PiiRedactionHook::redact(&choice.text);
+        }
+        Ok(chunk)
+    }
+
+    fn on_chat_stream_chunk(&self, mut chunk: ChatStreamChunk) -> OpenAiResult<ChatStreamChunk> {
+        for choice in chunk.choices.iter_mut() {
+            if let Some(content) = &mut choice.delta.content {
+                *content = PiiRedactionHook::redact(content);
+            }
+        }
+        Ok(chunk)
+    }
+}
+
+fn redact_emails(text: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    for word in text.split_inclusive(char::is_whitespace) {
+        let part = word.trim_end_matches(char::is_whitespace);
+        let separator = &word[part.len()..];
+        let start = part
+            .find(|c: char| c.is_alphanumeric())
+            .unwrap_or(part.len());
+        let end = part
+            .rfind(|c: char| c.is_alphanumeric())
+            .map_or(start, |i| i + 1);
+        if start < end && is_email(&part[start..end]) {
+            redacted.push_str(&part[..start]);
+            redacted.push_str(REDACTED);
+            redacted.push_str(&part[end..]);
+        } else {
+            redacted.push_str(part);
+        }
+        redacted.push_str(separator);
+    }
+    redacted
+}
+
+fn is_email(word: &str) -> bool {
+    match word.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !local.contains(char::is_whitespace)
+                && domain
+                    .split_once('.')
+                    .is_some_and(|(name, tld)| !name.is_empty() && !tld.is_empty())
+        }
+        None => false,
+    }
+}
+
+fn redact_phone_numbers(text: &str) -> String {
+    let is_phone_char = |c: char| c.is_ascii_digit() || " -.()+".contains(c);
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit() || c == '+' || c == '(') {
+        let len = rest[start..]
+            .find(|c: char| !is_phone_char(c))
+            .unwrap_or(rest.len() - start);
+        let candidate = &rest[start..start + len];
+        let end = candidate
+            .rfind(|c: char| c.is_ascii_digit())
+            .map_or(0, |i| i + 1);
+        let digits = candidate.chars().filter(char::is_ascii_digit).count();
+        redacted.push_str(&rest[..start]);
+        if (MIN_PHONE_DIGITS..=MAX_PHONE_DIGITS).contains(&digits) && !is_date(&candidate[..end]) {
+            redacted.push_str(REDACTED);
+            redacted.push_str(&candidate[end..]);
+        } else {
+            redacted.push_str(candidate);
+        }
+        rest = &rest[start + len..];
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+/// Returns true for dates like `2023-10-16` or `16.10.2023`, which have as
+/// many digits as short phone numbers.
+fn is_date(text: &str) -> bool {
+    let parts: Vec<&str> = text.split(['-', '.']).collect();
+    let lengths: Vec<usize> = parts.iter().map(|p| p.len()).collect();
+    parts
+        .iter()
+        .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+        && (lengths == [4, 2, 2] || lengths == [2, 2, 4])
+}
+
+#[cfg(test)]
+mod hooked_client {
+    use super::*;
+    use crate::client::request_client::create_test_server_config;
+    use crate::{
+        collect_chat_stream, ChatCompletionRequestBuilder, ChatMessage, CompletionRequestBuilder,
+        OpenAiClient, OpenAiError,
+    };
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    struct UppercaseHook;
+
+    impl ResponseHook for UppercaseHook {
+        fn on_text_result(&self, mut result: TextResult) -> OpenAiResult<TextResult> {
+            for choice in result.choices.iter_mut() {
+                choice.text = choice.text.to_uppercase();
+            }
+            Ok(result)
+        }
+    }
+
+    #[test]
+    fn should_redact_emails_and_phone_numbers() {
+        assert_eq!(
+            PiiRedactionHook::redact("Mail john.doe@example.com, or call +1 (555) 123-4567."),
+            "Mail [REDACTED], or call [REDACTED]."
+        );
+        assert_eq!(
+            PiiRedactionHook::redact("In 2023 about 42 people met at 10:30 @home."),
+            "In 2023 about 42 people met at 10:30 @home."
+        );
+    }
+
+    #[test]
+    fn should_redact_emails_after_any_whitespace() {
+        assert_eq!(
+            PiiRedactionHook::redact("Contact:\njohn@x.com\tor\r\njane@example.org"),
+            "Contact:\n[REDACTED]\tor\r\n[REDACTED]"
+        );
+    }
+
+    #[test]
+    fn should_keep_dates_but_redact_phone_numbers() {
+        assert_eq!(
+            PiiRedactionHook::redact("Signed on 2023-10-16, valid until 16.10.2024."),
+            "Signed on 2023-10-16, valid until 16.10.2024."
+        );
+        assert_eq!(
+            PiiRedactionHook::redact("Call 555-123-4567 or 030.1234.5678."),
+            "Call [REDACTED] or [REDACTED]."
+        );
+    }
+
+    #[test]
+    fn should_redact_chat_messages() {
+        let response: ChatCompletionResponse = serde_json::from_value(serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1699180000,
+            "model": "gpt-3.5-turbo",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Write to jane@example.com"},
+                "finish_reason": "stop"
+            }]
+        }))
+        .unwrap();
+        let response = PiiRedactionHook.on_chat_completion(response).unwrap();
+        assert_eq!(
+            response.best_text(),
+            Some("Write to [REDACTED]".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn should_apply_hooks_in_order() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "cmpl-1",
+                "object": "text_completion",
+                "created": 1669646641,
+                "model": "text-davinci-003",
+                "choices": [{
+                    "text": "Contact me at jane@example.com",
+                    "index": 0,
+                    "logprobs": null,
+                    "finish_reason": "stop"
+                }],
+                "usage": {"prompt_tokens": 1, "completion_tokens": 5, "total_tokens": 6}
+            })))
+            .mount(&server)
+            .await;
+
+        let client = HookedClient::new(OpenAiClient::new(config))
+            .with_hook(PiiRedactionHook)
+            .with_hook(UppercaseHook);
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("Who can I contact?")
+            .build()
+            .unwrap();
+        let result = client.create_completion(request).await.unwrap();
+        assert_eq!(result.best_text(), Some("CONTACT ME AT [REDACTED]"));
+    }
+
+    #[tokio::test]
+    async fn should_apply_hooks_to_stream_chunks() {
+        let (config, server) = create_test_server_config().await;
+        let chunk = |content: &str| {
+            format!(
+                r#"data: {{"id":"chatcmpl-1","object":"chat.completion.chunk","created":1699180000,"model":"gpt-3.5-turbo","choices":[{{"index":0,"delta":{{"content":"{}"}},"finish_reason":null}}]}}"#,
+                content
+            )
+        };
+        let body = format!(
+            "{}\n\n{}\n\ndata: [DONE]\n\n",
+            chunk("Write to "),
+            chunk("jane@example.com")
+        );
+        Mock::given(method("POST"))
+            .and(path(config.get_chat_completion_path()))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+            .mount(&server)
+            .await;
+
+        let client = HookedClient::new(OpenAiClient::new(config)).with_hook(PiiRedactionHook);
+        let request = ChatCompletionRequestBuilder::default()
+            .model("gpt-3.5-turbo")
+            .messages(vec![ChatMessage::user("Who can I contact?")])
+            .build()
+            .unwrap();
+        let stream = client
+            .create_chat_completion_stream(request)
+            .await
+            .unwrap()
+            .into_data();
+        let response = collect_chat_stream(stream).await.unwrap();
+        assert_eq!(
+            response.best_text(),
+            Some("Write to [REDACTED]".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_apply_hooks_to_errors() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({
+                "error": {"message": "server error", "type": "server_error", "param": null, "code": null}
+            })))
+            .mount(&server)
+            .await;
+
+        let client = HookedClient::new(OpenAiClient::new(config)).with_hook(PiiRedactionHook);
+        assert!(matches!(
+            client.get_models().await,
+            Err(OpenAiError::ApiErrorResponse(_))
+        ));
+    }
+}
@@ -0,0 +1,275 @@
+use crate::multipart::FormExt;
+use crate::{ApiResponse, ClientApi, CreateImageRequest, ImageResult, OpenAiError, OpenAiResult};
+use reqwest::multipart::Form;
+
+/// Words removed from prompts by [conservative_prompt_rewriter].
+const SENSITIVE_WORDS: &[&str] = &[
+    "blood", "bloody", "gore", "gory", "kill", "killing", "dead", "death", "weapon", "gun",
+    "knife", "violent", "violence", "nude", "naked", "explicit", "drug", "drugs",
+];
+
+/// Image sizes supported by DALL-E 2.
+const DALL_E_2_SIZES: &[(u32, u32)] = &[(256, 256), (512, 512), (1024, 1024)];
+
+/// Image sizes supported by DALL-E 3.
+const DALL_E_3_SIZES: &[(u32, u32)] = &[(1024, 1024), (1792, 1024), (1024, 1792)];
+
+/// Image dimensions must be multiples of this value.
+const IMAGE_SIZE_STEP: u32 = 256;
+
+/// Data required for editing an image. It is sent as multipart form, the
+/// image and the optional mask must be square PNG files.
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(strip_option, into))]
+pub struct EditImageRequest {
+    pub image_bytes: Vec<u8>,
+    pub image_name: String,
+    /// Transparent areas of the mask mark where the image should be edited.
+    #[builder(default)]
+    pub mask_bytes: Option<Vec<u8>>,
+    pub prompt: String,
+    #[builder(default)]
+    pub n: Option<i64>,
+    #[builder(default)]
+    pub size: Option<String>,
+    #[builder(default)]
+    pub response_format: Option<String>,
+    #[builder(default)]
+    pub user: Option<String>,
+}
+
+impl EditImageRequest {
+    pub(crate) fn form(&self) -> Form {
+        let form = Form::new()
+            .file_bytes("image", &self.image_name, &self.image_bytes)
+            .text("prompt", self.prompt.clone());
+        let form = match &self.mask_bytes {
+            Some(mask) => form.file_bytes("mask", "mask.png", mask),
+            None => form,
+        };
+        form.optional_text("n", self.n.map(|n| n.to_string()))
+            .optional_text("size", self.size.as_ref())
+            .optional_text("response_format", self.response_format.as_ref())
+            .optional_text("user", self.user.as_ref())
+    }
+}
+
+/// Data required for creating variations of an image. It is sent as
+/// multipart form, the image must be a square PNG file.
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(strip_option, into))]
+pub struct ImageVariationRequest {
+    pub image_bytes: Vec<u8>,
+    pub image_name: String,
+    #[builder(default)]
+    pub n: Option<i64>,
+    #[builder(default)]
+    pub size: Option<String>,
+    #[builder(default)]
+    pub response_format: Option<String>,
+    #[builder(default)]
+    pub user: Option<String>,
+}
+
+impl ImageVariationRequest {
+    pub(crate) fn form(&self) -> Form {
+        Form::new()
+            .file_bytes("image", &self.image_name, &self.image_bytes)
+            .optional_text("n", self.n.map(|n| n.to_string()))
+            .optional_text("size", self.size.as_ref())
+            .optional_text("response_format", self.response_format.as_ref())
+            .optional_text("user", self.user.as_ref())
+    }
+}
+
+/// Parses an image size of the form `{width}x{height}`. Both dimensions must
+/// be positive multiples of 256.
+pub fn parse_image_size(size: &str) -> OpenAiResult<(u32, u32)> {
+    let invalid = |reason: &str| OpenAiError::InvalidRequestParameter {
+        name: "size".to_string(),
+        reason: format!("{}: {}", reason, size),
+    };
+    let (width, height) = size
+        .split_once('x')
+        .ok_or_else(|| invalid("expected {width}x{height}"))?;
+    let parse = |value: &str| {
+        value
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| invalid("dimensions must be positive integers"))
+    };
+    let (width, height) = (parse(width)?, parse(height)?);
+    if width == 0 || height == 0 || width % IMAGE_SIZE_STEP != 0 || height % IMAGE_SIZE_STEP != 0 {
+        return Err(invalid("dimensions must be multiples of 256"));
+    }
+    Ok((width, height))
+}
+
+/// Returns the image sizes supported by the given model. Models other than
+/// DALL-E 3 use the DALL-E 2 sizes.
+pub fn valid_image_sizes_for_model(model: &str) -> &'static [(u32, u32)] {
+    if model.starts_with("dall-e-3") {
+        DALL_E_3_SIZES
+    } else {
+        DALL_E_2_SIZES
+    }
+}
+
+/// Returns true if the model supports images of the given dimensions.
+pub fn is_valid_image_size(width: u32, height: u32, model: &str) -> bool {
+    valid_image_sizes_for_model(model).contains(&(width, height))
+}
+
+/// Creates an image and, if the prompt is rejected by the content policy,
+/// retries up to `max_retries` times with the prompt rewritten by
+/// `rewrite_prompt`. The rewriter receives the last rejected prompt.
+pub async fn create_image_safe<C, F>(
+    client: &C,
+    request: CreateImageRequest,
+    rewrite_prompt: F,
+    max_retries: u32,
+) -> OpenAiResult<ApiResponse<ImageResult>>
+where
+    C: ClientApi + ?Sized,
+    F: Fn(&str) -> String,
+{
+    let mut request = request;
+    let mut retries = 0;
+    loop {
+        match client.create_image(request.clone()).await {
+            Err(err) if err.is_content_policy_violation() && retries < max_retries => {
+                retries += 1;
+                request.prompt = rewrite_prompt(&request.prompt);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Removes potentially sensitive words from an image prompt.
+pub fn conservative_prompt_rewriter(prompt: &str) -> String {
+    prompt
+        .split_whitespace()
+        .filter(|word| {
+            let word = word
+                .trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase();
+            !SENSITIVE_WORDS.contains(&word.as_str())
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod image_utils {
+    use super::*;
+    use crate::client::request_client::create_test_server_config;
+    use crate::{CreateImageRequestBuilder, OpenAiClient};
+    use serde_json::json;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    fn content_policy_violation() -> ResponseTemplate {
+        ResponseTemplate::new(400).set_body_json(json!({
+            "error": {
+                "code": "content_policy_violation",
+                "message": "Your request was rejected as a result of our safety system.",
+                "param": null,
+                "type": "invalid_request_error"
+            }
+        }))
+    }
+
+    #[test]
+    fn should_parse_image_size() {
+        assert_eq!(parse_image_size("1024x1024").unwrap(), (1024, 1024));
+        assert_eq!(parse_image_size("1792x1024").unwrap(), (1792, 1024));
+        for size in ["1024", "1024x", "-256x256", "0x256", "1000x1000", "axb"] {
+            assert!(
+                matches!(
+                    parse_image_size(size),
+                    Err(OpenAiError::InvalidRequestParameter { .. })
+                ),
+                "{} should be invalid",
+                size
+            );
+        }
+    }
+
+    #[test]
+    fn should_validate_image_size_for_model() {
+        for size in ["1024x1024", "1792x1024", "1024x1792"] {
+            let (width, height) = parse_image_size(size).unwrap();
+            assert!(is_valid_image_size(width, height, "dall-e-3"));
+        }
+        assert!(!is_valid_image_size(256, 256, "dall-e-3"));
+        assert!(!is_valid_image_size(1792, 1024, "dall-e-2"));
+        assert!(is_valid_image_size(512, 512, "dall-e-2"));
+    }
+
+    #[test]
+    fn should_remove_sensitive_words() {
+        assert_eq!(
+            conservative_prompt_rewriter("A bloody knife, on a table"),
+            "A on a table"
+        );
+        assert_eq!(
+            conservative_prompt_rewriter("A cute baby sea otter"),
+            "A cute baby sea otter"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_retry_with_rewritten_prompt() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_create_image_path()))
+            .and(body_partial_json(json!({"prompt": "A bloody sea otter"})))
+            .respond_with(content_policy_violation())
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_create_image_path()))
+            .and(body_partial_json(json!({"prompt": "A sea otter"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "created": 1671811458,
+                "data": [{"url": "https://example.com/otter.png"}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = CreateImageRequestBuilder::default()
+            .prompt("A bloody sea otter")
+            .build()
+            .unwrap();
+        let result = create_image_safe(&client, request, conservative_prompt_rewriter, 1)
+            .await
+            .expect("expected success after rewrite")
+            .into_data();
+        assert_eq!(result.data.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_return_error_when_retries_exhausted() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_create_image_path()))
+            .respond_with(content_policy_violation())
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = CreateImageRequestBuilder::default()
+            .prompt("prompt")
+            .build()
+            .unwrap();
+        match create_image_safe(&client, request, |p| p.to_string(), 2).await {
+            Err(err) => assert!(err.is_content_policy_violation()),
+            Ok(_) => panic!("expected content policy violation"),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Tests running against the real OpenAi Api. These are ignored by default
+//! and only send requests if OPENAI_API_KEY is set and
+//! OPENAI_RUN_INTEGRATION_TESTS=1, as they use credits.
+//!
+//! Run with `cargo make integration_test`.
+
+use crate::*;
+use std::env;
+
+const ENV_RUN_INTEGRATION_TESTS: &str = "OPENAI_RUN_INTEGRATION_TESTS";
+
+/// Returns a client if integration tests are explicitly enabled.
+fn integration_client() -> Option<OpenAiClient> {
+    dotenv::dotenv().ok();
+    let enabled = env::var(ENV_RUN_INTEGRATION_TESTS).is_ok_and(|v| v == "1");
+    if enabled && env::var("OPENAI_API_KEY").is_ok() {
+        Some(OpenAiClient::default())
+    } else {
+        println!(
+            "skipping integration test, set OPENAI_API_KEY and OPENAI_RUN_INTEGRATION_TESTS=1"
+        );
+        None
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn should_get_models() {
+    let Some(client) = integration_client() else {
+        return;
+    };
+    let models = client
+        .get_models()
+        .await
+        .expect("models response")
+        .into_data();
+    assert!(!models.data.is_empty());
+}
+
+#[tokio::test]
+#[ignore]
+async fn should_create_completion() {
+    let Some(client) = integration_client() else {
+        return;
+    };
+    let request = CompletionRequestBuilder::default()
+        .model("gpt-3.5-turbo-instruct")
+        .prompt("Say hello")
+        .max_tokens(5)
+        .build()
+        .unwrap();
+    let result = client
+        .create_completion(request)
+        .await
+        .expect("completion response");
+    println!("completion usage: {:?}", result.usage);
+    assert!(!result.choices.is_empty());
+}
+
+#[tokio::test]
+#[ignore]
+async fn should_create_embedding() {
+    let Some(client) = integration_client() else {
+        return;
+    };
+    let request = EmbeddingRequestBuilder::default()
+        .model("text-embedding-3-small")
+        .input(StringOrListParam::StringParam(
+            "The food was delicious".to_string(),
+        ))
+        .build()
+        .unwrap();
+    let result = client
+        .create_embedding(request)
+        .await
+        .expect("embedding response")
+        .into_data();
+    println!("embedding usage: {:?}", result.usage);
+    assert_eq!(result.data.len(), 1);
+    assert!(!result.data[0].embedding.is_empty());
+}
+
+#[tokio::test]
+#[ignore]
+async fn should_create_moderation() {
+    let Some(client) = integration_client() else {
+        return;
+    };
+    let request = ModerationRequestBuilder::default()
+        .input(StringOrListParam::StringParam(
+            "I love sunny days".to_string(),
+        ))
+        .build()
+        .unwrap();
+    let result = client
+        .create_moderation(request)
+        .await
+        .expect("moderation response");
+    assert_eq!(result.results.len(), 1);
+}
@@ -45,7 +45,9 @@
 //!
 //! All currently supported operations have a builder for the request payload,
 //! can be configured via a config struct and return either a struct with the
-//! expected success response or an error of type OpenAiError.
+//! expected success response or an error of type OpenAiError. Success
+//! responses are wrapped in an `ApiResponse` carrying the rate limit headers
+//! of the response next to the data.
 //!
 //! ## Client
 //!
@@ -71,7 +73,7 @@
 //! let models = client.get_models().await?;
 //!
 //! // fetch a specific model
-//! let model: OpenAiModel = client.get_model("text-davinci-003").await?;
+//! let model: OpenAiModel = client.get_model("text-davinci-003").await?.into_data();
 //! # Ok(())}
 //! ```
 //!
@@ -90,7 +92,7 @@
 //!     .build()
 //!     .unwrap();
 //!
-//! let result: TextResult = client.create_edit(request).await?;
+//! let result: TextResult = client.create_edit(request).await?.into_data();
 //! assert!(!result.choices.is_empty());
 //! # Ok(())}
 //! ```
@@ -110,11 +112,74 @@
 //!     .build()
 //!     .unwrap();
 //!
-//! let result: TextResult = client.create_completion(request).await?;
+//! let result: TextResult = client.create_completion(request).await?.into_data();
 //! assert!(!result.choices.is_empty());
 //! # Ok(())}
 //! ```
 //!
+//! ### Streaming
+//! Completions can also be received as a stream of partial results.
+//!
+//! ```rust,no_run
+//! # use openai_client::ClientApi;
+//!  async fn run() -> openai_client::OpenAiResult<()> {
+//! # use openai_client::*;
+//! use futures::StreamExt;
+//! # let client = OpenAiClient::default();
+//! let request = CompletionRequestBuilder::default()
+//!     .model("text-davinci-003")
+//!     .prompt("I am so tired I could")
+//!     .build()
+//!     .unwrap();
+//!
+//! let mut stream = client.create_completion_stream(request).await?.into_data();
+//! while let Some(chunk) = stream.next().await {
+//!     print!("{}", chunk?.choices[0].text);
+//! }
+//! # Ok(())}
+//! ```
+//!
+//! ## Chat Completions
+//! Given a list of messages comprising a conversation, the model will return a response.
+//!
+//! ```rust,no_run
+//! # use openai_client::ClientApi;
+//!  async fn run() -> openai_client::OpenAiResult<()> {
+//! # use openai_client::*;
+//! # let client = OpenAiClient::default();
+//! let request = ChatCompletionRequestBuilder::default()
+//!     .model("gpt-3.5-turbo")
+//!     .messages(vec![
+//!         ChatMessage::system("You are a helpful assistant."),
+//!         ChatMessage::user("Hello!"),
+//!     ])
+//!     .build()
+//!     .unwrap();
+//!
+//! let result: ChatCompletionResponse = client.create_chat_completion(request).await?.into_data();
+//! assert!(!result.choices.is_empty());
+//! # Ok(())}
+//! ```
+//!
+//! ## Embeddings
+//! Get a vector representation of one or multiple inputs.
+//!
+//! ```rust,no_run
+//! # use openai_client::ClientApi;
+//!  async fn run() -> openai_client::OpenAiResult<()> {
+//! # use openai_client::*;
+//! # let client = OpenAiClient::default();
+//! let request = EmbeddingRequestBuilder::default()
+//!     .model("text-embedding-3-small")
+//!     .input(StringOrListParam::StringParam("The food was delicious".to_string()))
+//!     .build()
+//!     .unwrap();
+//!
+//! let result: EmbeddingResponse = client.create_embedding(request).await?.into_data();
+//! assert!(!result.data.is_empty());
+//! # Ok(())}
+//! ```
+//!
 //! ## Generate Image
 //! Creates an image given a prompt.
 //!
@@ -129,7 +194,7 @@
 //!     .n(2)
 //!     .build()
 //!     .unwrap();
-//! let result: ImageResult = client.create_image(request).await?;
+//! let result: ImageResult = client.create_image(request).await?.into_data();
 //! assert_eq!(!result.data.len(), 2);
 //! # Ok(())}
 //! ```
@@ -140,16 +205,115 @@ extern crate core;
 #[macro_use]
 extern crate derive_builder;
 
+mod assistant;
+mod audio;
+mod azure;
+mod batch;
+mod chat;
+mod circuit_breaker;
 mod client;
 mod client_api;
+mod context;
+mod embedding;
+mod files;
+mod fine_tuning;
+mod hook;
+mod image;
+mod moderation;
+mod multi_key;
+mod multipart;
+mod prompt;
+mod rate_limit;
+mod retry;
+mod run;
+mod streaming;
+mod thread;
+mod token;
+mod trace;
 mod types;
+mod usage;
+
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
+#[cfg(all(test, feature = "integration-tests"))]
+mod integration;
 
 pub use types::{
-    CompletionRequest, CompletionRequestBuilder, CreateImageRequest, CreateImageRequestBuilder,
-    EditRequest, EditRequestBuilder, ImageItem, ImageResult, OpenAiConfig, OpenAiError,
+    extract_json, ApiCompatibilityReport, ApiEnvironment, AuthScheme, CompletionDefaults,
+    CompletionRequest, CompletionRequestBuilder, ConfigDiagnostics, ConnectivityReport,
+    CreateImageRequest, CreateImageRequestBuilder, DeleteResponse, EditRequest, EditRequestBuilder,
+    EndpointStatus, FinishReason, ImageItem, ImageResult, ListOrder, ListParams, ListResponse,
+    ObjectType, OpenAiConfig, OpenAiError, OpenAiErrorDetails, OpenAiErrorDetailsBuilder,
     OpenAiErrorResponse, OpenAiModel, OpenAiModelPermission, OpenAiModelResponse, OpenAiResponse,
-    OpenAiResult, TextChoice, TextResult,
+    OpenAiResult, RetryConfigSummary, Serializable, StreamChunk, StringOrListParam, TextChoice,
+    TextResult, TokenSource, Usage,
 };
 
+pub use assistant::{
+    Assistant, AssistantTool, CodeInterpreterResources, CreateAssistantRequest,
+    CreateAssistantRequestBuilder, FileSearchResources, ModifyAssistantRequest,
+    ModifyAssistantRequestBuilder, ToolResources,
+};
+pub use audio::{
+    AudioFormat, SpeechRequest, SpeechRequestBuilder, TranscriptionFormat, TranscriptionRequest,
+    TranscriptionRequestBuilder, TranscriptionResponse, TranscriptionSegment, TranscriptionWord,
+    TranslationRequest, TranslationRequestBuilder, TranslationResponse, Voice,
+};
+pub use azure::AzureOpenAiConfig;
+pub use batch::{
+    build_batch_input_line, parse_batch_output, Batch, BatchEndpoint, BatchErrors, BatchInputLine,
+    BatchLineError, BatchLineResponse, BatchOutputLine, BatchRequestCounts, BatchStatus,
+    BatchValidationError, CreateBatchRequest, CreateBatchRequestBuilder,
+};
+pub use chat::{
+    ChatChoice, ChatCompletionRequest, ChatCompletionRequestBuilder, ChatCompletionResponse,
+    ChatCompletionTokenLogprob, ChatMessage, ChatMessageDelta, ChatRole, ChatStreamChoice,
+    ChatStreamChunk, ContentPart, FunctionCall, FunctionCallDelta, FunctionDefinition,
+    FunctionDefinitionBuilder, ImageDetail, ImageUrlContent, LogprobsContent, MessageContent,
+    ResponseFormat, Tool, ToolCall, ToolCallDelta, ToolChoice, ToolChoiceFunction, TopLogprob,
+};
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerClient, CircuitState};
 pub use client::OpenAiClient;
 pub use client_api::ClientApi;
+pub use context::{
+    context_window, estimate_tokens, warn_if_expensive, would_exceed_context, TokenBudget,
+};
+pub use embedding::{
+    EmbeddingFormat, EmbeddingItem, EmbeddingRequest, EmbeddingRequestBuilder, EmbeddingResponse,
+};
+pub use files::{FileList, FileObject, FilePurpose, UploadFileRequest, UploadFileRequestBuilder};
+pub use fine_tuning::{
+    CreateFineTuningJobRequest, CreateFineTuningJobRequestBuilder, FineTuningEvent,
+    FineTuningEventList, FineTuningJob, FineTuningJobError, FineTuningJobList, FineTuningJobStatus,
+    HyperparameterValue, Hyperparameters,
+};
+pub use hook::{HookedClient, PiiRedactionHook, ResponseHook};
+pub use image::{
+    conservative_prompt_rewriter, create_image_safe, is_valid_image_size, parse_image_size,
+    valid_image_sizes_for_model, EditImageRequest, EditImageRequestBuilder, ImageVariationRequest,
+    ImageVariationRequestBuilder,
+};
+pub use moderation::{
+    ModerationCategories, ModerationCategoryScores, ModerationRequest, ModerationRequestBuilder,
+    ModerationResponse, ModerationResult,
+};
+pub use multi_key::{KeyScope, MultiKeyClient, MultiKeyConfig};
+pub use prompt::{
+    clear_global_prompt_library, set_global_prompt_library, PromptLibrary, PromptTemplate,
+};
+pub use rate_limit::{ApiResponse, RateLimitInfo};
+pub use retry::RetryConfig;
+pub use run::{
+    CreateRunRequest, CreateRunRequestBuilder, RequiredAction, Run, RunError, RunStatus,
+    SubmitToolOutputs, SubmitToolOutputsRequest, ToolOutput,
+};
+pub use streaming::{collect_chat_stream, OpenAiStream};
+pub use thread::{
+    ContentBlock, CreateMessageRequest, CreateMessageRequestBuilder, CreateThreadRequest,
+    CreateThreadRequestBuilder, ImageFileContent, MessageRole, ModifyMessageRequest,
+    ModifyMessageRequestBuilder, ModifyThreadRequest, ModifyThreadRequestBuilder, TextContent,
+    Thread, ThreadMessage, ThreadMessageObject,
+};
+pub use token::RotatingTokenProvider;
+pub use usage::{AggregatingClient, UsageAggregator, UsageRecord};
@@ -0,0 +1,86 @@
+use crate::{Serializable, StringOrListParam};
+use serde::{Deserialize, Serialize};
+
+/// Json data required for classifying whether inputs violate the usage
+/// policies.
+#[derive(Serialize, Deserialize, Builder, Debug, Clone, PartialEq)]
+#[builder(setter(strip_option, into))]
+pub struct ModerationRequest {
+    pub input: StringOrListParam,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl Serializable for ModerationRequest {}
+
+/// Whether an input was flagged for a policy category.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModerationCategories {
+    pub hate: bool,
+    #[serde(rename = "hate/threatening")]
+    pub hate_threatening: bool,
+    #[serde(default)]
+    pub harassment: bool,
+    #[serde(default, rename = "harassment/threatening")]
+    pub harassment_threatening: bool,
+    #[serde(rename = "self-harm")]
+    pub self_harm: bool,
+    #[serde(default, rename = "self-harm/intent")]
+    pub self_harm_intent: bool,
+    #[serde(default, rename = "self-harm/instructions")]
+    pub self_harm_instructions: bool,
+    pub sexual: bool,
+    #[serde(rename = "sexual/minors")]
+    pub sexual_minors: bool,
+    pub violence: bool,
+    #[serde(rename = "violence/graphic")]
+    pub violence_graphic: bool,
+}
+
+/// Probability scores of an input per policy category.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ModerationCategoryScores {
+    pub hate: f64,
+    #[serde(rename = "hate/threatening")]
+    pub hate_threatening: f64,
+    #[serde(default)]
+    pub harassment: f64,
+    #[serde(default, rename = "harassment/threatening")]
+    pub harassment_threatening: f64,
+    #[serde(rename = "self-harm")]
+    pub self_harm: f64,
+    #[serde(default, rename = "self-harm/intent")]
+    pub self_harm_intent: f64,
+    #[serde(default, rename = "self-harm/instructions")]
+    pub self_harm_instructions: f64,
+    pub sexual: f64,
+    #[serde(rename = "sexual/minors")]
+    pub sexual_minors: f64,
+    pub violence: f64,
+    #[serde(rename = "violence/graphic")]
+    pub violence_graphic: f64,
+}
+
+/// Classification of a single input.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ModerationResult {
+    pub flagged: bool,
+    pub categories: ModerationCategories,
+    pub category_scores: ModerationCategoryScores,
+}
+
+/// Response of a moderation request with one result per input.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ModerationResponse {
+    pub id: String,
+    pub model: String,
+    pub results: Vec<ModerationResult>,
+}
+
+impl ModerationResponse {
+    /// Returns true if any of the inputs was flagged.
+    pub fn is_flagged(&self) -> bool {
+        self.results.iter().any(|result| result.flagged)
+    }
+}
@@ -0,0 +1,520 @@
+use crate::{
+    ApiResponse, Assistant, Batch, ChatCompletionRequest, ChatCompletionResponse, ChatStreamChunk,
+    ClientApi, CompletionRequest, CreateAssistantRequest, CreateBatchRequest,
+    CreateFineTuningJobRequest, CreateImageRequest, CreateMessageRequest, CreateRunRequest,
+    CreateThreadRequest, DeleteResponse, EditImageRequest, EditRequest, EmbeddingRequest,
+    EmbeddingResponse, FileList, FileObject, FineTuningEventList, FineTuningJob, FineTuningJobList,
+    ImageResult, ImageVariationRequest, ListParams, ListResponse, ModerationRequest,
+    ModerationResponse, ModifyAssistantRequest, ModifyMessageRequest, ModifyThreadRequest,
+    OpenAiClient, OpenAiConfig, OpenAiError, OpenAiModel, OpenAiModelResponse, OpenAiResult,
+    OpenAiStream, Run, SpeechRequest, StreamChunk, SubmitToolOutputsRequest, TextResult, Thread,
+    ThreadMessageObject, TranscriptionRequest, TranscriptionResponse, TranslationRequest,
+    TranslationResponse, UploadFileRequest,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::fmt;
+
+/// Describes what an Api key should be used for.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyScope {
+    pub teams: Vec<String>,
+    /// Models this key is used for. `None` matches no model explicitly.
+    pub models: Option<Vec<String>>,
+    pub max_monthly_spend: Option<f64>,
+}
+
+impl KeyScope {
+    fn matches_model(&self, model: &str) -> bool {
+        self.models
+            .as_ref()
+            .is_some_and(|models| models.iter().any(|m| m == model))
+    }
+}
+
+/// A set of Api keys with their scopes. The first key is the default.
+#[derive(Clone, Default, PartialEq)]
+pub struct MultiKeyConfig {
+    pub keys: Vec<(String, KeyScope)>,
+}
+
+impl MultiKeyConfig {
+    pub fn new() -> Self {
+        MultiKeyConfig::default()
+    }
+
+    /// Add an Api key with its scope.
+    pub fn key(mut self, key: &str, scope: KeyScope) -> Self {
+        self.keys.push((key.to_string(), scope));
+        self
+    }
+}
+
+/// Redacts the Api keys.
+impl fmt::Debug for MultiKeyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<_> = self
+            .keys
+            .iter()
+            .map(|(_, scope)| ("<redacted>", scope))
+            .collect();
+        f.debug_struct("MultiKeyConfig")
+            .field("keys", &keys)
+            .finish()
+    }
+}
+
+/// Client that selects the Api key based on the model of a request.
+#[derive(Clone)]
+pub struct MultiKeyClient {
+    keys: MultiKeyConfig,
+    clients: Vec<OpenAiClient>,
+}
+
+impl MultiKeyClient {
+    /// Creates a client per key from the given base configuration. A token
+    /// provider of the base configuration is not used, every client sends
+    /// its own key.
+    pub fn new(config: OpenAiConfig, keys: MultiKeyConfig) -> Self {
+        let config = config.without_token_provider();
+        let clients = keys
+            .keys
+            .iter()
+            .map(|(key, _)| OpenAiClient::new(config.clone().access_token(key)))
+            .collect();
+        MultiKeyClient { keys, clients }
+    }
+
+    /// Returns the first key whose scope includes the model or the first key
+    /// if none matches.
+    pub fn select_key(&self, model: &str) -> OpenAiResult<&str> {
+        self.select_index(model)
+            .map(|index| self.keys.keys[index].0.as_str())
+    }
+
+    fn select_index(&self, model: &str) -> OpenAiResult<usize> {
+        if self.keys.keys.is_empty() {
+            return Err(OpenAiError::MissingTokenError);
+        }
+        Ok(self
+            .keys
+            .keys
+            .iter()
+            .position(|(_, scope)| scope.matches_model(model))
+            .unwrap_or(0))
+    }
+
+    fn client(&self, model: &str) -> OpenAiResult<&OpenAiClient> {
+        self.select_index(model).map(|index| &self.clients[index])
+    }
+}
+
+#[async_trait]
+impl ClientApi for MultiKeyClient {
+    async fn create_completion(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<TextResult>> {
+        self.client(&request.model)?
+            .create_completion(request)
+            .await
+    }
+
+    async fn create_completion_stream(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<StreamChunk>>> {
+        self.client(&request.model)?
+            .create_completion_stream(request)
+            .await
+    }
+
+    async fn create_chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<ChatCompletionResponse>> {
+        self.client(&request.model)?
+            .create_chat_completion(request)
+            .await
+    }
+
+    async fn create_chat_completion_stream(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<ChatStreamChunk>>> {
+        self.client(&request.model)?
+            .create_chat_completion_stream(request)
+            .await
+    }
+
+    async fn create_edit(&self, request: EditRequest) -> OpenAiResult<ApiResponse<TextResult>> {
+        self.client(&request.model)?.create_edit(request).await
+    }
+
+    async fn create_embedding(
+        &self,
+        request: EmbeddingRequest,
+    ) -> OpenAiResult<ApiResponse<EmbeddingResponse>> {
+        self.client(&request.model)?.create_embedding(request).await
+    }
+
+    async fn create_moderation(
+        &self,
+        request: ModerationRequest,
+    ) -> OpenAiResult<ApiResponse<ModerationResponse>> {
+        let model = request.model.clone().unwrap_or_default();
+        self.client(&model)?.create_moderation(request).await
+    }
+
+    async fn create_speech(&self, request: SpeechRequest) -> OpenAiResult<ApiResponse<Bytes>> {
+        self.client(&request.model)?.create_speech(request).await
+    }
+
+    async fn transcribe_audio(
+        &self,
+        request: TranscriptionRequest,
+    ) -> OpenAiResult<ApiResponse<TranscriptionResponse>> {
+        self.client(&request.model)?.transcribe_audio(request).await
+    }
+
+    async fn translate_audio(
+        &self,
+        request: TranslationRequest,
+    ) -> OpenAiResult<ApiResponse<TranslationResponse>> {
+        self.client(&request.model)?.translate_audio(request).await
+    }
+
+    async fn upload_file(
+        &self,
+        request: UploadFileRequest,
+    ) -> OpenAiResult<ApiResponse<FileObject>> {
+        self.client("")?.upload_file(request).await
+    }
+
+    async fn list_files(&self) -> OpenAiResult<ApiResponse<FileList>> {
+        self.client("")?.list_files().await
+    }
+
+    async fn retrieve_file(&self, id: &str) -> OpenAiResult<ApiResponse<FileObject>> {
+        self.client("")?.retrieve_file(id).await
+    }
+
+    async fn delete_file(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.client("")?.delete_file(id).await
+    }
+
+    async fn retrieve_file_content(&self, id: &str) -> OpenAiResult<ApiResponse<Bytes>> {
+        self.client("")?.retrieve_file_content(id).await
+    }
+
+    async fn create_fine_tuning_job(
+        &self,
+        request: CreateFineTuningJobRequest,
+    ) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.client(&request.model)?
+            .create_fine_tuning_job(request)
+            .await
+    }
+
+    async fn list_fine_tuning_jobs(&self) -> OpenAiResult<ApiResponse<FineTuningJobList>> {
+        self.client("")?.list_fine_tuning_jobs().await
+    }
+
+    async fn retrieve_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.client("")?.retrieve_fine_tuning_job(id).await
+    }
+
+    async fn cancel_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.client("")?.cancel_fine_tuning_job(id).await
+    }
+
+    async fn list_fine_tuning_events(
+        &self,
+        id: &str,
+        after: Option<&str>,
+    ) -> OpenAiResult<ApiResponse<FineTuningEventList>> {
+        self.client("")?.list_fine_tuning_events(id, after).await
+    }
+
+    async fn create_assistant(
+        &self,
+        request: CreateAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.client(&request.model)?.create_assistant(request).await
+    }
+
+    async fn retrieve_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.client("")?.retrieve_assistant(id).await
+    }
+
+    async fn modify_assistant(
+        &self,
+        id: &str,
+        request: ModifyAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.client("")?.modify_assistant(id, request).await
+    }
+
+    async fn delete_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.client("")?.delete_assistant(id).await
+    }
+
+    async fn list_assistants(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Assistant>>> {
+        self.client("")?.list_assistants(params).await
+    }
+
+    async fn create_thread(
+        &self,
+        request: CreateThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>> {
+        self.client("")?.create_thread(request).await
+    }
+
+    async fn retrieve_thread(&self, id: &str) -> OpenAiResult<ApiResponse<Thread>> {
+        self.client("")?.retrieve_thread(id).await
+    }
+
+    async fn modify_thread(
+        &self,
+        id: &str,
+        request: ModifyThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>> {
+        self.client("")?.modify_thread(id, request).await
+    }
+
+    async fn delete_thread(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.client("")?.delete_thread(id).await
+    }
+
+    async fn create_message(
+        &self,
+        thread_id: &str,
+        request: CreateMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.client("")?.create_message(thread_id, request).await
+    }
+
+    async fn list_messages(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<ThreadMessageObject>>> {
+        self.client("")?.list_messages(thread_id, params).await
+    }
+
+    async fn retrieve_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.client("")?
+            .retrieve_message(thread_id, message_id)
+            .await
+    }
+
+    async fn modify_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+        request: ModifyMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.client("")?
+            .modify_message(thread_id, message_id, request)
+            .await
+    }
+
+    async fn delete_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.client("")?.delete_message(thread_id, message_id).await
+    }
+
+    async fn create_run(
+        &self,
+        thread_id: &str,
+        request: CreateRunRequest,
+    ) -> OpenAiResult<ApiResponse<Run>> {
+        self.client("")?.create_run(thread_id, request).await
+    }
+
+    async fn retrieve_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>> {
+        self.client("")?.retrieve_run(thread_id, run_id).await
+    }
+
+    async fn list_runs(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Run>>> {
+        self.client("")?.list_runs(thread_id, params).await
+    }
+
+    async fn cancel_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>> {
+        self.client("")?.cancel_run(thread_id, run_id).await
+    }
+
+    async fn submit_tool_outputs(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        request: SubmitToolOutputsRequest,
+    ) -> OpenAiResult<ApiResponse<Run>> {
+        self.client("")?
+            .submit_tool_outputs(thread_id, run_id, request)
+            .await
+    }
+
+    async fn create_batch(&self, request: CreateBatchRequest) -> OpenAiResult<ApiResponse<Batch>> {
+        self.client("")?.create_batch(request).await
+    }
+
+    async fn retrieve_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>> {
+        self.client("")?.retrieve_batch(id).await
+    }
+
+    async fn cancel_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>> {
+        self.client("")?.cancel_batch(id).await
+    }
+
+    async fn list_batches(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Batch>>> {
+        self.client("")?.list_batches(params).await
+    }
+
+    async fn get_models(&self) -> OpenAiResult<ApiResponse<OpenAiModelResponse>> {
+        self.client("")?.get_models().await
+    }
+
+    async fn get_model(&self, model: &str) -> OpenAiResult<ApiResponse<OpenAiModel>> {
+        self.client(model)?.get_model(model).await
+    }
+
+    async fn create_image(
+        &self,
+        request: CreateImageRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        self.client("")?.create_image(request).await
+    }
+
+    async fn edit_image(
+        &self,
+        request: EditImageRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        self.client("")?.edit_image(request).await
+    }
+
+    async fn create_image_variation(
+        &self,
+        request: ImageVariationRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        self.client("")?.create_image_variation(request).await
+    }
+}
+
+#[cfg(test)]
+mod multi_key_client {
+    use super::*;
+    use crate::client::request_client::{create_test_server_config, json_response};
+    use crate::{CompletionRequestBuilder, RotatingTokenProvider};
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    fn scope(models: &[&str]) -> KeyScope {
+        KeyScope {
+            teams: vec!["team".to_string()],
+            models: Some(models.iter().map(|m| m.to_string()).collect()),
+            max_monthly_spend: None,
+        }
+    }
+
+    fn keys() -> MultiKeyConfig {
+        MultiKeyConfig::new()
+            .key("key-default", KeyScope::default())
+            .key("key-davinci", scope(&["text-davinci-003"]))
+            .key(
+                "key-edit",
+                scope(&["text-davinci-edit-001", "code-davinci-002"]),
+            )
+    }
+
+    #[test]
+    fn should_select_key_by_model() {
+        let client = MultiKeyClient::new(OpenAiConfig::default(), keys());
+        assert_eq!(
+            client.select_key("text-davinci-003").unwrap(),
+            "key-davinci"
+        );
+        assert_eq!(client.select_key("code-davinci-002").unwrap(), "key-edit");
+        assert_eq!(client.select_key("text-ada-001").unwrap(), "key-default");
+    }
+
+    #[test]
+    fn should_fail_without_keys() {
+        let client = MultiKeyClient::new(OpenAiConfig::default(), MultiKeyConfig::new());
+        assert!(matches!(
+            client.select_key("text-davinci-003"),
+            Err(OpenAiError::MissingTokenError)
+        ));
+    }
+
+    #[test]
+    fn should_redact_keys_in_debug() {
+        let debug = format!("{:?}", keys());
+        assert!(!debug.contains("key-davinci"));
+        assert!(debug.contains("text-davinci-003"));
+    }
+
+    #[tokio::test]
+    async fn should_ignore_token_provider_of_base_config() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .and(header("Authorization", "Bearer key-davinci"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = config.token_provider(RotatingTokenProvider::from_env_var(
+            "MULTI_KEY_SHARED_TOKEN",
+        ));
+        let client = MultiKeyClient::new(config, keys());
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("Say hello")
+            .build()
+            .unwrap();
+        assert!(client.create_completion(request).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_send_request_with_selected_key() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .and(header("Authorization", "Bearer key-davinci"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = MultiKeyClient::new(config, keys());
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("Say hello")
+            .build()
+            .unwrap();
+        assert!(client.create_completion(request).await.is_ok());
+    }
+}
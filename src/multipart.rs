@@ -0,0 +1,27 @@
+use reqwest::multipart::{Form, Part};
+
+/// Shortcuts for building the multipart forms of requests.
+pub(crate) trait FormExt {
+    /// Adds a text field if the value is set.
+    fn optional_text(self, name: &'static str, value: Option<impl ToString>) -> Self;
+
+    /// Adds a file field with the given file name and content.
+    fn file_bytes(self, name: &'static str, file_name: &str, content: &[u8]) -> Self;
+}
+
+impl FormExt for Form {
+    fn optional_text(self, name: &'static str, value: Option<impl ToString>) -> Self {
+        match value {
+            Some(value) => self.text(name, value.to_string()),
+            None => self,
+        }
+    }
+
+    fn file_bytes(self, name: &'static str, file_name: &str, content: &[u8]) -> Self {
+        let part = Part::bytes(content.to_vec())
+            .file_name(file_name.to_string())
+            .mime_str("application/octet-stream")
+            .expect("valid mime type");
+        self.part(name, part)
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{CompletionRequest, CompletionRequestBuilder, OpenAiError, OpenAiResult};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::RwLock;
+
+const TEMPLATE_EXTENSION: &str = "txt";
+
+static GLOBAL_LIBRARY: RwLock<Option<PromptLibrary>> = RwLock::new(None);
+
+/// Sets the prompt library used by `CompletionRequestBuilder::from_template`
+/// for the whole process.
+pub fn set_global_prompt_library(library: PromptLibrary) {
+    *GLOBAL_LIBRARY.write().unwrap_or_else(|e| e.into_inner()) = Some(library);
+}
+
+/// Removes the global prompt library.
+pub fn clear_global_prompt_library() {
+    *GLOBAL_LIBRARY.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// A prompt text with `{{name}}` placeholders for variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    name: String,
+    template: String,
+}
+
+impl PromptTemplate {
+    pub fn new(name: &str, template: &str) -> Self {
+        PromptTemplate {
+            name: name.to_string(),
+            template: template.to_string(),
+        }
+    }
+
+    /// Returns the template name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the raw template text.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Replaces all placeholders with the given variables. Fails if a
+    /// placeholder has no matching variable.
+    pub fn render(&self, vars: &HashMap<&str, &str>) -> OpenAiResult<String> {
+        let mut rendered = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                break;
+            };
+            let key = rest[start + 2..start + end].trim();
+            let value = vars
+                .get(key)
+                .ok_or_else(|| OpenAiError::MissingRequestParameter {
+                    name: key.to_string(),
+                    request: self.name.clone(),
+                })?;
+            rendered.push_str(&rest[..start]);
+            rendered.push_str(value);
+            rest = &rest[start + end + 2..];
+        }
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
+}
+
+/// A collection of named prompt templates.
+#[derive(Debug, Clone, Default)]
+pub struct PromptLibrary {
+    templates: HashMap<String, PromptTemplate>,
+}
+
+impl PromptLibrary {
+    pub fn new() -> Self {
+        PromptLibrary::default()
+    }
+
+    /// Loads all `.txt` files in the directory as templates, named by their
+    /// file name without extension.
+    pub fn from_dir(dir: &Path) -> OpenAiResult<Self> {
+        let mut library = PromptLibrary::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(TEMPLATE_EXTENSION) {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                library.insert(PromptTemplate::new(name, &fs::read_to_string(&path)?));
+            }
+        }
+        Ok(library)
+    }
+
+    /// Adds a template, replacing an existing one with the same name.
+    pub fn insert(&mut self, template: PromptTemplate) {
+        self.templates.insert(template.name.clone(), template);
+    }
+
+    /// Returns the template with the given name.
+    pub fn get(&self, name: &str) -> Option<&PromptTemplate> {
+        self.templates.get(name)
+    }
+
+    /// Returns the names of all templates.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    /// Renders the named template and returns it as completion request
+    /// prompt for the given model.
+    pub fn render_to_request(
+        &self,
+        name: &str,
+        vars: &HashMap<&str, &str>,
+        model: &str,
+    ) -> OpenAiResult<CompletionRequest> {
+        let prompt = self.render(name, vars)?;
+        Ok(CompletionRequest {
+            model: model.to_string(),
+            prompt: Some(prompt.as_str().into()),
+            ..Default::default()
+        })
+    }
+
+    /// Renders the named template.
+    pub fn render(&self, name: &str, vars: &HashMap<&str, &str>) -> OpenAiResult<String> {
+        self.get(name)
+            .ok_or_else(|| OpenAiError::TemplateNotFound {
+                name: name.to_string(),
+            })?
+            .render(vars)
+    }
+}
+
+impl CompletionRequestBuilder {
+    /// Creates a builder with the prompt rendered from the named template of
+    /// the global prompt library. Fails with
+    /// [OpenAiError::PromptLibraryNotSet] if no global library is set.
+    pub fn from_template(name: &str, vars: HashMap<&str, &str>) -> OpenAiResult<Self> {
+        let global = GLOBAL_LIBRARY.read().unwrap_or_else(|e| e.into_inner());
+        match global.as_ref() {
+            Some(library) => Self::from_library_template(library, name, vars),
+            None => Err(OpenAiError::PromptLibraryNotSet),
+        }
+    }
+
+    /// Creates a builder with the prompt rendered from the named template of
+    /// the given library.
+    pub fn from_library_template(
+        library: &PromptLibrary,
+        name: &str,
+        vars: HashMap<&str, &str>,
+    ) -> OpenAiResult<Self> {
+        let prompt = library.render(name, &vars)?;
+        let mut builder = CompletionRequestBuilder::default();
+        builder.prompt(prompt.as_str());
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod prompt_library {
+    use super::*;
+    use crate::StringOrListParam;
+    use std::env;
+    use std::path::PathBuf;
+
+    fn create_template_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("openai_client_prompts_{}", name));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("summarize.txt"),
+            "Summarize the following {{kind}}:\n{{text}}",
+        )
+        .unwrap();
+        fs::write(dir.join("greet.txt"), "Say hello to {{ name }}").unwrap();
+        fs::write(dir.join("notes.md"), "not a template").unwrap();
+        dir
+    }
+
+    #[test]
+    fn should_load_templates_from_dir() {
+        let dir = create_template_dir("load");
+        let library = PromptLibrary::from_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        let library = library.unwrap();
+
+        let mut names: Vec<&str> = library.names().collect();
+        names.sort();
+        assert_eq!(names, vec!["greet", "summarize"]);
+        assert_eq!(
+            library.get("greet").unwrap().template(),
+            "Say hello to {{ name }}"
+        );
+        assert!(library.get("notes").is_none());
+    }
+
+    #[test]
+    fn should_render_template_to_request() {
+        let dir = create_template_dir("render");
+        let library = PromptLibrary::from_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        let library = library.unwrap();
+
+        let vars = HashMap::from([("kind", "article"), ("text", "Lorem ipsum")]);
+        let request = library
+            .render_to_request("summarize", &vars, "text-davinci-003")
+            .unwrap();
+        assert_eq!(request.model, "text-davinci-003");
+        assert_eq!(
+            request.prompt,
+            Some(StringOrListParam::StringParam(
+                "Summarize the following article:\nLorem ipsum".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn should_fail_on_missing_template_or_variable() {
+        let mut library = PromptLibrary::new();
+        library.insert(PromptTemplate::new("greet", "Say hello to {{name}}"));
+
+        match library.render("unknown", &HashMap::new()) {
+            Err(OpenAiError::TemplateNotFound { name }) => assert_eq!(name, "unknown"),
+            _ => panic!("expected template not found error"),
+        }
+        match library.render("greet", &HashMap::new()) {
+            Err(OpenAiError::MissingRequestParameter { name, request }) => {
+                assert_eq!(name, "name");
+                assert_eq!(request, "greet");
+            }
+            _ => panic!("expected missing parameter error"),
+        }
+    }
+
+    #[test]
+    fn should_keep_unclosed_placeholders() {
+        let template = PromptTemplate::new("t", "{{a}} and {{b");
+        let res = template.render(&HashMap::from([("a", "x")])).unwrap();
+        assert_eq!(res, "x and {{b");
+    }
+
+    #[test]
+    fn should_create_builder_from_global_template() {
+        let mut library = PromptLibrary::new();
+        library.insert(PromptTemplate::new("greet", "Say hello to {{name}}"));
+        set_global_prompt_library(library);
+
+        let request = std::thread::spawn(|| {
+            CompletionRequestBuilder::from_template("greet", HashMap::from([("name", "Bob")]))
+                .unwrap()
+                .model("text-davinci-003")
+                .build()
+                .unwrap()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(request.model, "text-davinci-003");
+        assert_eq!(
+            request.prompt,
+            Some(StringOrListParam::from("Say hello to Bob"))
+        );
+
+        assert!(matches!(
+            CompletionRequestBuilder::from_template("missing", HashMap::new()),
+            Err(OpenAiError::TemplateNotFound { .. })
+        ));
+
+        clear_global_prompt_library();
+        assert!(matches!(
+            CompletionRequestBuilder::from_template("greet", HashMap::from([("name", "Bob")])),
+            Err(OpenAiError::PromptLibraryNotSet)
+        ));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::OpenAiResult;
+use reqwest::header::HeaderMap;
+use std::ops::Deref;
+use std::time::Duration;
+
+const LIMIT_REQUESTS: &str = "x-ratelimit-limit-requests";
+const REMAINING_REQUESTS: &str = "x-ratelimit-remaining-requests";
+const RESET_REQUESTS: &str = "x-ratelimit-reset-requests";
+const LIMIT_TOKENS: &str = "x-ratelimit-limit-tokens";
+const REMAINING_TOKENS: &str = "x-ratelimit-remaining-tokens";
+const RESET_TOKENS: &str = "x-ratelimit-reset-tokens";
+
+/// Rate limit state sent by the Api with every response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    pub limit_requests: Option<u64>,
+    pub remaining_requests: Option<u64>,
+    /// Time until the request limit is reset.
+    pub reset_requests: Option<Duration>,
+    pub limit_tokens: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    /// Time until the token limit is reset.
+    pub reset_tokens: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// Parses the rate limit headers, `None` if none of them is set.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let text = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let number = |name: &str| text(name).and_then(|v| v.trim().parse().ok());
+        let duration = |name: &str| text(name).and_then(parse_reset_duration);
+        let info = RateLimitInfo {
+            limit_requests: number(LIMIT_REQUESTS),
+            remaining_requests: number(REMAINING_REQUESTS),
+            reset_requests: duration(RESET_REQUESTS),
+            limit_tokens: number(LIMIT_TOKENS),
+            remaining_tokens: number(REMAINING_TOKENS),
+            reset_tokens: duration(RESET_TOKENS),
+        };
+        Some(info).filter(|info| *info != RateLimitInfo::default())
+    }
+}
+
+/// A response together with the rate limit state sent with it. Derefs to the
+/// response data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse<T> {
+    pub data: T,
+    pub rate_limit: Option<RateLimitInfo>,
+}
+
+impl<T> ApiResponse<T> {
+    pub fn new(data: T, rate_limit: Option<RateLimitInfo>) -> Self {
+        ApiResponse { data, rate_limit }
+    }
+
+    /// Returns the response data.
+    pub fn into_data(self) -> T {
+        self.data
+    }
+
+    /// Maps the response data, keeping the rate limit state.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ApiResponse<U> {
+        ApiResponse::new(f(self.data), self.rate_limit)
+    }
+
+    /// Maps the response data with a fallible function, keeping the rate
+    /// limit state.
+    pub fn try_map<U>(self, f: impl FnOnce(T) -> OpenAiResult<U>) -> OpenAiResult<ApiResponse<U>> {
+        Ok(ApiResponse::new(f(self.data)?, self.rate_limit))
+    }
+}
+
+impl<T> Deref for ApiResponse<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.data
+    }
+}
+
+/// Parses reset durations like `20ms`, `1s`, `6m0s` or `1h2m3.5s`.
+fn parse_reset_duration(value: &str) -> Option<Duration> {
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .filter(|i| *i > 0)?;
+        let amount: f64 = rest[..split].parse().ok()?;
+        rest = &rest[split..];
+        let (seconds, unit_len) = if rest.starts_with("ms") {
+            (0.001, 2)
+        } else if rest.starts_with('h') {
+            (3600.0, 1)
+        } else if rest.starts_with('m') {
+            (60.0, 1)
+        } else if rest.starts_with('s') {
+            (1.0, 1)
+        } else {
+            return None;
+        };
+        total += amount * seconds;
+        rest = &rest[unit_len..];
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
+#[cfg(test)]
+mod headers {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn should_parse_reset_durations() {
+        let parse = parse_reset_duration;
+        assert_eq!(parse("20ms"), Some(Duration::from_millis(20)));
+        assert_eq!(parse("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse("1h2m3.5s"), Some(Duration::from_secs_f64(3723.5)));
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("soon"), None);
+        assert_eq!(parse("5"), None);
+        assert_eq!(parse("99999999999999999999999h"), None);
+    }
+
+    #[test]
+    fn should_parse_rate_limit_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(RateLimitInfo::from_headers(&headers), None);
+        headers.insert(LIMIT_REQUESTS, HeaderValue::from_static("60"));
+        headers.insert(REMAINING_REQUESTS, HeaderValue::from_static("59"));
+        headers.insert(RESET_REQUESTS, HeaderValue::from_static("1s"));
+        headers.insert(REMAINING_TOKENS, HeaderValue::from_static("invalid"));
+        assert_eq!(
+            RateLimitInfo::from_headers(&headers),
+            Some(RateLimitInfo {
+                limit_requests: Some(60),
+                remaining_requests: Some(59),
+                reset_requests: Some(Duration::from_secs(1)),
+                ..Default::default()
+            })
+        );
+    }
+}
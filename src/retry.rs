@@ -0,0 +1,115 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+use std::time::{Duration, SystemTime};
+
+/// Retry behaviour for rate limited (429) and failed (5xx) requests. Delays
+/// grow exponentially unless the Api sends a `Retry-After` header, all delays
+/// are capped at `max_delay`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub backoff_multiplier: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            backoff_multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Returns the exponential backoff delay before the retry following the
+    /// given zero based attempt.
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let factor = self.backoff_multiplier.max(1.0).powi(attempt as i32);
+        Duration::try_from_secs_f64(self.initial_delay.as_secs_f64() * factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+
+    /// Returns the delay before retrying after the given response.
+    pub(crate) fn delay(&self, response: &Response, attempt: u32) -> Duration {
+        retry_after(response)
+            .map(|delay| delay.min(self.max_delay))
+            .unwrap_or_else(|| self.backoff_delay(attempt))
+    }
+}
+
+/// Returns true for responses that can safely be retried.
+pub(crate) fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Reads the `Retry-After` header given in seconds or as http date.
+pub(crate) fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    parse_retry_after(value, SystemTime::now())
+}
+
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    match value.parse::<f64>() {
+        Ok(seconds) if seconds >= 0.0 => Duration::try_from_secs_f64(seconds).ok(),
+        Ok(_) => None,
+        Err(_) => httpdate::parse_http_date(value)
+            .ok()
+            .map(|date| date.duration_since(now).unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod backoff {
+    use super::*;
+
+    #[test]
+    fn should_grow_delay_exponentially_up_to_max() {
+        let config = RetryConfig {
+            max_retries: 10,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            backoff_multiplier: 2.0,
+        };
+        assert_eq!(config.backoff_delay(0), Duration::from_millis(100));
+        assert_eq!(config.backoff_delay(1), Duration::from_millis(200));
+        assert_eq!(config.backoff_delay(3), Duration::from_millis(800));
+        assert_eq!(config.backoff_delay(4), Duration::from_secs(1));
+        assert_eq!(config.backoff_delay(1000), Duration::from_secs(1));
+        let huge = RetryConfig {
+            initial_delay: Duration::MAX,
+            max_delay: Duration::from_secs(30),
+            ..config
+        };
+        assert_eq!(huge.backoff_delay(3), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn should_parse_retry_after_seconds_and_dates() {
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+        let parse = |value| parse_retry_after(value, now);
+        assert_eq!(parse("2"), Some(Duration::from_secs(2)));
+        assert_eq!(parse("0.5"), Some(Duration::from_millis(500)));
+        assert_eq!(parse("1e30"), None);
+        assert_eq!(parse("-1"), None);
+        assert_eq!(parse("inf"), None);
+        assert_eq!(
+            parse("Wed, 21 Oct 2015 07:28:30 GMT"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(parse("Wed, 21 Oct 2015 07:00:00 GMT"), Some(Duration::ZERO));
+        assert_eq!(parse("soon"), None);
+    }
+
+    #[test]
+    fn should_retry_rate_limits_and_server_errors() {
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+    }
+}
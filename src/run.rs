@@ -0,0 +1,143 @@
+use crate::{AssistantTool, ObjectType, Serializable, ToolCall, Usage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Status of a run.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Queued,
+    InProgress,
+    RequiresAction,
+    Cancelling,
+    Cancelled,
+    Failed,
+    Completed,
+    Incomplete,
+    Expired,
+}
+
+impl RunStatus {
+    /// Returns true if the run will not change anymore.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            RunStatus::Cancelled
+                | RunStatus::Failed
+                | RunStatus::Completed
+                | RunStatus::Incomplete
+                | RunStatus::Expired
+        )
+    }
+}
+
+impl std::fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            RunStatus::Queued => "queued",
+            RunStatus::InProgress => "in_progress",
+            RunStatus::RequiresAction => "requires_action",
+            RunStatus::Cancelling => "cancelling",
+            RunStatus::Cancelled => "cancelled",
+            RunStatus::Failed => "failed",
+            RunStatus::Completed => "completed",
+            RunStatus::Incomplete => "incomplete",
+            RunStatus::Expired => "expired",
+        };
+        f.write_str(status)
+    }
+}
+
+/// Json data required for running an assistant on a thread.
+#[derive(Serialize, Deserialize, Builder, Debug, Clone, PartialEq)]
+#[builder(setter(strip_option, into))]
+pub struct CreateRunRequest {
+    pub assistant_id: String,
+    /// Overrides the model of the assistant.
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Overrides the instructions of the assistant.
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    /// Overrides the tools of the assistant.
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<AssistantTool>>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+}
+
+impl Serializable for CreateRunRequest {}
+
+/// Tool calls whose outputs are required to continue a run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SubmitToolOutputs {
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// Action required to continue a run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RequiredAction {
+    #[serde(rename = "type")]
+    pub action_type: String,
+    pub submit_tool_outputs: SubmitToolOutputs,
+}
+
+/// Output of a tool call.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolOutput {
+    pub tool_call_id: String,
+    pub output: String,
+}
+
+/// Json data for submitting the outputs of the required tool calls.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SubmitToolOutputsRequest {
+    pub tool_outputs: Vec<ToolOutput>,
+}
+
+impl Serializable for SubmitToolOutputsRequest {}
+
+/// Reason of a failed run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RunError {
+    pub code: String,
+    pub message: String,
+}
+
+/// A run of an assistant on a thread.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Run {
+    pub id: String,
+    pub object: ObjectType,
+    pub created_at: i64,
+    pub thread_id: String,
+    pub assistant_id: String,
+    pub status: RunStatus,
+    #[serde(default)]
+    pub required_action: Option<RequiredAction>,
+    #[serde(default)]
+    pub last_error: Option<RunError>,
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+    #[serde(default)]
+    pub started_at: Option<i64>,
+    #[serde(default)]
+    pub cancelled_at: Option<i64>,
+    #[serde(default)]
+    pub failed_at: Option<i64>,
+    #[serde(default)]
+    pub completed_at: Option<i64>,
+    pub model: String,
+    #[serde(default)]
+    pub instructions: Option<String>,
+    #[serde(default)]
+    pub tools: Vec<AssistantTool>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+    #[serde(default)]
+    pub metadata: Option<HashMap<String, String>>,
+}
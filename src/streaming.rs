@@ -0,0 +1,288 @@
+use crate::{
+    ChatChoice, ChatCompletionResponse, ChatMessage, ChatRole, ChatStreamChunk, FunctionCall,
+    MessageContent, ObjectType, OpenAiError, OpenAiResponse, OpenAiResult, ToolCall, ToolCallDelta,
+    Usage,
+};
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use std::pin::Pin;
+
+/// Marker sent by the Api as the data of the last server sent event.
+const DONE_MARKER: &str = "[DONE]";
+
+/// A stream of server sent events parsed into `T`.
+pub type OpenAiStream<T> = Pin<Box<dyn Stream<Item = OpenAiResult<T>> + Send>>;
+
+struct SseState {
+    response: Response,
+    buffer: Vec<u8>,
+    limit: Option<usize>,
+    done: bool,
+}
+
+enum SseEvent {
+    Data(String),
+    Done,
+}
+
+/// Parses the server sent events of the response body. Every `data` event is
+/// deserialized into `T`, the stream ends on `[DONE]` or the end of the
+/// body. If set, `limit` bounds the size of a single buffered event.
+pub(crate) fn parse_sse_stream<T>(response: Response, limit: Option<usize>) -> OpenAiStream<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let state = SseState {
+        response,
+        buffer: Vec::new(),
+        limit,
+        done: false,
+    };
+    Box::pin(stream::unfold(state, |mut state| async move {
+        loop {
+            if state.done {
+                return None;
+            }
+            if let Some(event) = take_event(&mut state.buffer) {
+                match parse_event(&event) {
+                    Some(SseEvent::Data(data)) => return Some((parse_data(&data), state)),
+                    Some(SseEvent::Done) => return None,
+                    None => continue,
+                }
+            }
+            if let Some(limit) = state.limit.filter(|l| state.buffer.len() > *l) {
+                state.done = true;
+                let received = state.buffer.len();
+                return Some((
+                    Err(OpenAiError::ResponseTooLarge { received, limit }),
+                    state,
+                ));
+            }
+            match state.response.chunk().await {
+                Ok(Some(chunk)) => {
+                    state.buffer.extend(chunk.iter().filter(|b| **b != b'\r'));
+                }
+                Ok(None) => {
+                    state.done = true;
+                    let rest = String::from_utf8_lossy(&state.buffer).into_owned();
+                    return match parse_event(&rest) {
+                        Some(SseEvent::Data(data)) => Some((parse_data(&data), state)),
+                        _ => None,
+                    };
+                }
+                Err(err) => {
+                    state.done = true;
+                    return Some((Err(err.into()), state));
+                }
+            }
+        }
+    }))
+}
+
+/// Drains a chat completion stream and assembles the deltas of every choice
+/// into a complete response. Tool call argument fragments are joined by the
+/// index of the call. Streamed responses carry no usage, it is left at its
+/// default.
+pub async fn collect_chat_stream<S>(mut stream: S) -> OpenAiResult<ChatCompletionResponse>
+where
+    S: Stream<Item = OpenAiResult<ChatStreamChunk>> + Unpin,
+{
+    let mut response: Option<ChatCompletionResponse> = None;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        let response = response.get_or_insert_with(|| ChatCompletionResponse {
+            id: chunk.id.clone(),
+            object: ObjectType::ChatCompletion,
+            created: chunk.created,
+            model: chunk.model.clone(),
+            system_fingerprint: chunk.system_fingerprint.clone(),
+            choices: Vec::new(),
+            usage: Usage::default(),
+        });
+        for delta in chunk.choices {
+            let position = response.choices.iter().position(|c| c.index == delta.index);
+            let choice = match position {
+                Some(position) => &mut response.choices[position],
+                None => {
+                    response.choices.push(ChatChoice {
+                        index: delta.index,
+                        message: ChatMessage {
+                            role: ChatRole::Assistant,
+                            content: None,
+                            name: None,
+                            tool_calls: None,
+                            tool_call_id: None,
+                        },
+                        finish_reason: None,
+                        logprobs: None,
+                    });
+                    response.choices.last_mut().expect("choice was pushed")
+                }
+            };
+            if let Some(role) = delta.delta.role {
+                choice.message.role = role;
+            }
+            if let Some(content) = delta.delta.content {
+                match &mut choice.message.content {
+                    Some(MessageContent::Text(text)) => text.push_str(&content),
+                    _ => choice.message.content = Some(MessageContent::Text(content)),
+                }
+            }
+            for call in delta.delta.tool_calls.into_iter().flatten() {
+                merge_tool_call(choice.message.tool_calls.get_or_insert_with(Vec::new), call);
+            }
+            if delta.finish_reason.is_some() {
+                choice.finish_reason = delta.finish_reason;
+            }
+        }
+    }
+    let mut response = response.ok_or(OpenAiError::UnexpectedApiResponse)?;
+    response.choices.sort_by_key(|c| c.index);
+    Ok(response)
+}
+
+fn merge_tool_call(calls: &mut Vec<ToolCall>, delta: ToolCallDelta) {
+    while calls.len() <= delta.index {
+        calls.push(ToolCall {
+            id: String::new(),
+            tool_type: "function".to_string(),
+            function: FunctionCall {
+                name: String::new(),
+                arguments: String::new(),
+            },
+        });
+    }
+    let call = &mut calls[delta.index];
+    if let Some(id) = delta.id {
+        call.id = id;
+    }
+    if let Some(tool_type) = delta.tool_type {
+        call.tool_type = tool_type;
+    }
+    if let Some(function) = delta.function {
+        if let Some(name) = function.name {
+            call.function.name.push_str(&name);
+        }
+        if let Some(arguments) = function.arguments {
+            call.function.arguments.push_str(&arguments);
+        }
+    }
+}
+
+/// Removes the first complete event from the buffer.
+fn take_event(buffer: &mut Vec<u8>) -> Option<String> {
+    let end = buffer.windows(2).position(|w| w == b"\n\n")?;
+    let event: Vec<u8> = buffer.drain(..end + 2).collect();
+    Some(String::from_utf8_lossy(&event[..end]).into_owned())
+}
+
+/// Returns the data of an event, `None` for events without data like
+/// comments.
+fn parse_event(event: &str) -> Option<SseEvent> {
+    let data: Vec<&str> = event
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|data| data.strip_prefix(' ').unwrap_or(data))
+        .collect();
+    if data.is_empty() {
+        return None;
+    }
+    let data = data.join("\n");
+    if data.trim() == DONE_MARKER {
+        Some(SseEvent::Done)
+    } else {
+        Some(SseEvent::Data(data))
+    }
+}
+
+fn parse_data<T: DeserializeOwned>(data: &str) -> OpenAiResult<T> {
+    serde_json::from_str::<OpenAiResponse<T>>(data)?.into_result()
+}
+
+#[cfg(test)]
+mod sse {
+    use super::*;
+    use crate::FinishReason;
+
+    #[test]
+    fn should_take_complete_events() {
+        let mut buffer = b"data: {\"a\":1}\n\ndata: {\"a\"".to_vec();
+        assert_eq!(take_event(&mut buffer), Some("data: {\"a\":1}".to_string()));
+        assert_eq!(take_event(&mut buffer), None);
+        assert_eq!(buffer, b"data: {\"a\"".to_vec());
+    }
+
+    #[test]
+    fn should_parse_events() {
+        assert!(matches!(
+            parse_event("data: {\"a\":1}"),
+            Some(SseEvent::Data(data)) if data == "{\"a\":1}"
+        ));
+        assert!(matches!(
+            parse_event("event: message\ndata: line1\ndata: line2"),
+            Some(SseEvent::Data(data)) if data == "line1\nline2"
+        ));
+        assert!(matches!(parse_event("data: [DONE]"), Some(SseEvent::Done)));
+        assert!(parse_event(": keep-alive").is_none());
+    }
+
+    fn chat_chunk(index: i64, role: Option<ChatRole>, content: &str) -> ChatStreamChunk {
+        serde_json::from_value(serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "created": 1699180000,
+            "model": "gpt-3.5-turbo",
+            "choices": [{
+                "index": index,
+                "delta": {"role": role, "content": content},
+                "finish_reason": null
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_collect_chat_stream() {
+        let mut last = chat_chunk(0, None, "");
+        last.choices[0].finish_reason = Some(FinishReason::Stop);
+        let chunks = vec![
+            Ok(chat_chunk(0, Some(ChatRole::Assistant), "Hello")),
+            Ok(chat_chunk(1, Some(ChatRole::Assistant), "Hi")),
+            Ok(chat_chunk(0, None, " there")),
+            Ok(last),
+        ];
+        let response = collect_chat_stream(stream::iter(chunks)).await.unwrap();
+        assert_eq!(response.object, ObjectType::ChatCompletion);
+        assert_eq!(response.choices.len(), 2);
+        assert_eq!(response.best_text(), Some("Hello there".to_string()));
+        assert_eq!(response.choices[0].finish_reason, Some(FinishReason::Stop));
+        assert_eq!(response.choices[1].message.text(), Some("Hi".to_string()));
+    }
+
+    #[tokio::test]
+    async fn should_fail_collecting_failed_chat_stream() {
+        let chunks = vec![
+            Ok(chat_chunk(0, Some(ChatRole::Assistant), "Hello")),
+            Err(OpenAiError::UnexpectedApiResponse),
+        ];
+        assert!(collect_chat_stream(stream::iter(chunks)).await.is_err());
+        let empty: Vec<OpenAiResult<ChatStreamChunk>> = Vec::new();
+        assert!(collect_chat_stream(stream::iter(empty)).await.is_err());
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    struct Chunk {
+        a: i64,
+    }
+
+    #[test]
+    fn should_parse_data() {
+        let result: OpenAiResult<Chunk> = parse_data(r#"{"a": 1}"#);
+        assert_eq!(result.unwrap().a, 1);
+        let result: OpenAiResult<Chunk> = parse_data(
+            r#"{"error": {"message": "failed", "type": "server_error", "param": null, "code": null}}"#,
+        );
+        assert!(matches!(result, Err(OpenAiError::ApiErrorResponse(_))));
+    }
+}
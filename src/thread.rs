@@ -0,0 +1,239 @@
+use crate::types::validate_metadata;
+use crate::{
+    ImageUrlContent, MessageContent, ObjectType, OpenAiError, Serializable, ToolResources,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The author of a thread message.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageRole {
+    User,
+    Assistant,
+}
+
+/// A message added to a thread when it is created.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ThreadMessage {
+    pub role: MessageRole,
+    pub content: MessageContent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl ThreadMessage {
+    pub fn user(content: &str) -> Self {
+        ThreadMessage {
+            role: MessageRole::User,
+            content: MessageContent::Text(content.to_string()),
+            metadata: None,
+        }
+    }
+}
+
+/// Json data required for creating a thread.
+#[derive(Serialize, Deserialize, Builder, Debug, Default, Clone, PartialEq)]
+#[builder(
+    setter(strip_option, into),
+    build_fn(validate = "Self::validate", error = "OpenAiError")
+)]
+pub struct CreateThreadRequest {
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub messages: Option<Vec<ThreadMessage>>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_resources: Option<ToolResources>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl Serializable for CreateThreadRequest {}
+
+impl CreateThreadRequestBuilder {
+    fn validate(&self) -> Result<(), OpenAiError> {
+        validate_metadata(self.metadata.as_ref().and_then(Option::as_ref))
+    }
+}
+
+/// Json data for modifying the metadata of a thread.
+#[derive(Serialize, Deserialize, Builder, Debug, Default, Clone, PartialEq)]
+#[builder(
+    setter(strip_option, into),
+    build_fn(validate = "Self::validate", error = "OpenAiError")
+)]
+pub struct ModifyThreadRequest {
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl Serializable for ModifyThreadRequest {}
+
+impl ModifyThreadRequestBuilder {
+    fn validate(&self) -> Result<(), OpenAiError> {
+        validate_metadata(self.metadata.as_ref().and_then(Option::as_ref))
+    }
+}
+
+/// A conversation thread of the Assistants Api.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Thread {
+    pub id: String,
+    pub object: ObjectType,
+    pub created_at: i64,
+    #[serde(default)]
+    pub tool_resources: Option<ToolResources>,
+    #[serde(default)]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+/// Json data required for adding a message to a thread.
+#[derive(Serialize, Deserialize, Builder, Debug, Clone, PartialEq)]
+#[builder(
+    setter(strip_option, into),
+    build_fn(validate = "Self::validate", error = "OpenAiError")
+)]
+pub struct CreateMessageRequest {
+    pub role: MessageRole,
+    pub content: MessageContent,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl Serializable for CreateMessageRequest {}
+
+impl CreateMessageRequestBuilder {
+    fn validate(&self) -> Result<(), OpenAiError> {
+        validate_metadata(self.metadata.as_ref().and_then(Option::as_ref))
+    }
+}
+
+/// Json data for modifying the metadata of a thread message.
+#[derive(Serialize, Deserialize, Builder, Debug, Default, Clone, PartialEq)]
+#[builder(
+    setter(strip_option, into),
+    build_fn(validate = "Self::validate", error = "OpenAiError")
+)]
+pub struct ModifyMessageRequest {
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl Serializable for ModifyMessageRequest {}
+
+impl ModifyMessageRequestBuilder {
+    fn validate(&self) -> Result<(), OpenAiError> {
+        validate_metadata(self.metadata.as_ref().and_then(Option::as_ref))
+    }
+}
+
+/// Text of a thread message with its annotations like file citations.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TextContent {
+    pub value: String,
+    #[serde(default)]
+    pub annotations: Vec<Value>,
+}
+
+/// A file referenced by a thread message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ImageFileContent {
+    pub file_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// A content block of a thread message returned by the Api.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text { text: TextContent },
+    ImageFile { image_file: ImageFileContent },
+    ImageUrl { image_url: ImageUrlContent },
+}
+
+/// A message of a thread.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ThreadMessageObject {
+    pub id: String,
+    pub object: ObjectType,
+    pub created_at: i64,
+    pub thread_id: String,
+    pub role: MessageRole,
+    pub content: Vec<ContentBlock>,
+    #[serde(default)]
+    pub assistant_id: Option<String>,
+    #[serde(default)]
+    pub run_id: Option<String>,
+    #[serde(default)]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl ThreadMessageObject {
+    /// Returns the text blocks of the message joined by a new line.
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.value.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod thread_request {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_serialize_thread_request() {
+        let request = CreateThreadRequestBuilder::default()
+            .messages(vec![ThreadMessage::user("Hello")])
+            .metadata(HashMap::from([("user".to_string(), "42".to_string())]))
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "messages": [{"role": "user", "content": "Hello"}],
+                "metadata": {"user": "42"}
+            })
+        );
+    }
+
+    #[test]
+    fn should_reject_metadata_exceeding_limits() {
+        let too_many: HashMap<String, String> = (0..17)
+            .map(|i| (format!("key{}", i), "value".to_string()))
+            .collect();
+        assert!(matches!(
+            CreateThreadRequestBuilder::default()
+                .metadata(too_many)
+                .build(),
+            Err(OpenAiError::InvalidRequestParameter { name, .. }) if name == "metadata"
+        ));
+
+        let too_long = HashMap::from([("key".to_string(), "a".repeat(513))]);
+        assert!(matches!(
+            ModifyThreadRequestBuilder::default()
+                .metadata(too_long)
+                .build(),
+            Err(OpenAiError::InvalidRequestParameter { name, .. }) if name == "metadata"
+        ));
+
+        let max = HashMap::from([("key".to_string(), "a".repeat(512))]);
+        assert!(ModifyThreadRequestBuilder::default()
+            .metadata(max)
+            .build()
+            .is_ok());
+    }
+}
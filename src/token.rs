@@ -0,0 +1,109 @@
+use crate::{OpenAiError, OpenAiResult};
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const ENV_TOKEN: &str = "OPENAI_API_KEY";
+
+/// Resolves the access token from an environment variable on every request,
+/// so keys can be rotated without restarting the service. Optionally the
+/// token can be cached for a limited duration.
+#[derive(Debug)]
+pub struct RotatingTokenProvider {
+    env_var: String,
+    cache_duration: Option<Duration>,
+    cached: Mutex<Option<(String, Instant)>>,
+    refresh_count: AtomicU64,
+}
+
+impl RotatingTokenProvider {
+    /// Creates a provider reading env OPENAI_API_KEY.
+    pub fn new() -> Self {
+        RotatingTokenProvider::from_env_var(ENV_TOKEN)
+    }
+
+    /// Creates a provider reading the given env var.
+    pub fn from_env_var(env_var: &str) -> Self {
+        RotatingTokenProvider {
+            env_var: env_var.to_string(),
+            cache_duration: None,
+            cached: Mutex::new(None),
+            refresh_count: AtomicU64::new(0),
+        }
+    }
+
+    /// Cache the token for the given duration before re-reading the env.
+    pub fn with_cache_duration(mut self, duration: Duration) -> Self {
+        self.cache_duration = Some(duration);
+        self
+    }
+
+    /// Returns the current token, re-reading the env var if no cached
+    /// token is available.
+    pub fn get_token(&self) -> OpenAiResult<String> {
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        if let (Some(duration), Some((token, read_at))) = (self.cache_duration, cached.as_ref()) {
+            if read_at.elapsed() < duration {
+                return Ok(token.clone());
+            }
+        }
+
+        let token = env::var(&self.env_var).map_err(|_| OpenAiError::MissingTokenError)?;
+        self.refresh_count.fetch_add(1, Ordering::Relaxed);
+        if self.cache_duration.is_some() {
+            *cached = Some((token.clone(), Instant::now()));
+        }
+        Ok(token)
+    }
+
+    /// Returns how often the token has been read from the env.
+    pub fn refresh_count(&self) -> u64 {
+        self.refresh_count.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for RotatingTokenProvider {
+    fn default() -> Self {
+        RotatingTokenProvider::new()
+    }
+}
+
+#[cfg(test)]
+mod rotating_token {
+    use super::*;
+
+    #[test]
+    fn should_reread_token_on_every_call() {
+        let var = "OPENAI_TEST_ROTATING_TOKEN";
+        let provider = RotatingTokenProvider::from_env_var(var);
+        env::set_var(var, "first");
+        assert_eq!(provider.get_token().unwrap(), "first");
+        env::set_var(var, "second");
+        assert_eq!(provider.get_token().unwrap(), "second");
+        env::remove_var(var);
+        assert_eq!(provider.refresh_count(), 2);
+    }
+
+    #[test]
+    fn should_cache_token_for_duration() {
+        let var = "OPENAI_TEST_CACHED_TOKEN";
+        let provider =
+            RotatingTokenProvider::from_env_var(var).with_cache_duration(Duration::from_secs(60));
+        env::set_var(var, "first");
+        assert_eq!(provider.get_token().unwrap(), "first");
+        env::set_var(var, "second");
+        assert_eq!(provider.get_token().unwrap(), "first");
+        env::remove_var(var);
+        assert_eq!(provider.refresh_count(), 1);
+    }
+
+    #[test]
+    fn should_fail_on_missing_env() {
+        let provider = RotatingTokenProvider::from_env_var("OPENAI_TEST_MISSING_TOKEN");
+        match provider.get_token() {
+            Err(OpenAiError::MissingTokenError) => {}
+            _ => panic!("expected missing token error"),
+        }
+    }
+}
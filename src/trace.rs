@@ -0,0 +1,228 @@
+//! Spans and events for requests, no-ops unless the `tracing` feature is
+//! enabled.
+use crate::OpenAiResult;
+use reqwest::{RequestBuilder, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use std::future::Future;
+
+/// Runs the request future in an `openai_request` span and records an error
+/// event if it fails.
+#[cfg(feature = "tracing")]
+#[tracing::instrument(name = "openai_request", skip_all, fields(endpoint = %endpoint), err)]
+pub(crate) async fn traced<T>(
+    endpoint: &str,
+    future: impl Future<Output = OpenAiResult<T>>,
+) -> OpenAiResult<T> {
+    future.await
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) async fn traced<T>(
+    _endpoint: &str,
+    future: impl Future<Output = OpenAiResult<T>>,
+) -> OpenAiResult<T> {
+    future.await
+}
+
+/// Returns the url path of a prepared request to name its span.
+#[cfg(feature = "tracing")]
+pub(crate) fn endpoint(builder: &RequestBuilder) -> String {
+    builder
+        .try_clone()
+        .and_then(|b| b.build().ok())
+        .map(|r| r.url().path().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn endpoint(_builder: &RequestBuilder) -> String {
+    String::new()
+}
+
+/// Records the model of a request body.
+#[cfg(feature = "tracing")]
+pub(crate) fn request_body<R: Serialize>(body: &R) {
+    if let Ok(value) = serde_json::to_value(body) {
+        let model = value
+            .get("model")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        tracing::debug!(model, "sending openai request");
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn request_body<R: Serialize>(_body: &R) {}
+
+/// Records the http status of a response.
+#[cfg(feature = "tracing")]
+pub(crate) fn response_status(status: StatusCode) {
+    tracing::debug!(status = status.as_u16(), "received openai response");
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn response_status(_status: StatusCode) {}
+
+/// Records the token usage or the error of a json response body.
+#[cfg(feature = "tracing")]
+pub(crate) fn response_body(value: &Value) {
+    if let Some(error) = value.get("error").filter(|e| !e.is_null()) {
+        tracing::error!(error = %error, "openai api returned error");
+    }
+    if let Some(usage) = value.get("usage").filter(|u| !u.is_null()) {
+        let tokens = |name: &str| usage.get(name).and_then(Value::as_i64).unwrap_or_default();
+        let model = value
+            .get("model")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        tracing::debug!(
+            model,
+            prompt_tokens = tokens("prompt_tokens"),
+            completion_tokens = tokens("completion_tokens"),
+            total_tokens = tokens("total_tokens"),
+            "openai token usage"
+        );
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn response_body(_value: &Value) {}
+
+#[cfg(all(test, feature = "tracing"))]
+mod spans {
+    use crate::client::request_client::{create_test_server_config, json_response};
+    use crate::{ClientApi, CompletionRequestBuilder, OpenAiClient};
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::Attributes;
+    use tracing::{Event, Id, Subscriber};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::Layer;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    /// Records spans and events as `name key=value ...` lines.
+    #[derive(Clone, Default)]
+    struct CapturingLayer {
+        lines: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct LineVisitor(String);
+
+    impl Visit for LineVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push_str(&format!(" {}={}", field.name(), value));
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for CapturingLayer {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            let mut visitor = LineVisitor(format!("span {}", attrs.metadata().name()));
+            attrs.record(&mut visitor);
+            self.lines.lock().unwrap().push(visitor.0);
+        }
+
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let mut visitor = LineVisitor(format!("event {}", event.metadata().level()));
+            event.record(&mut visitor);
+            self.lines.lock().unwrap().push(visitor.0);
+        }
+    }
+
+    impl CapturingLayer {
+        fn contains(&self, parts: &[&str]) -> bool {
+            self.lines
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|line| parts.iter().all(|part| line.contains(part)))
+        }
+    }
+
+    #[tokio::test]
+    async fn should_trace_request_span_status_and_usage() {
+        let layer = CapturingLayer::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(layer.clone()));
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config.clone());
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("Say hello")
+            .build()
+            .unwrap();
+        client.create_completion(request).await.unwrap();
+
+        let endpoint = format!("endpoint={}", config.get_completion_path());
+        assert!(layer.contains(&["span openai_request", &endpoint]));
+        assert!(layer.contains(&["event DEBUG", "sending openai request", "text-davinci-003"]));
+        assert!(layer.contains(&["event DEBUG", "received openai response", "status=200"]));
+        assert!(layer.contains(&["event DEBUG", "openai token usage", "total_tokens="]));
+    }
+
+    async fn completion_warnings(threshold: u32) -> CapturingLayer {
+        let layer = CapturingLayer::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(layer.clone()));
+        let (config, server) = create_test_server_config().await;
+        let config = config.with_cost_warning_threshold(threshold);
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        let request = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt("Say hello")
+            .best_of(10)
+            .max_tokens(100)
+            .build()
+            .unwrap();
+        client.create_completion(request).await.unwrap();
+        layer
+    }
+
+    #[tokio::test]
+    async fn should_warn_about_expensive_completion() {
+        let layer = completion_warnings(1_000).await;
+        assert!(layer.contains(&["event WARN", "may bill up to 1030 tokens"]));
+        let layer = completion_warnings(2_000).await;
+        assert!(!layer.contains(&["event WARN"]));
+    }
+
+    #[tokio::test]
+    async fn should_trace_failed_request() {
+        let layer = CapturingLayer::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(layer.clone()));
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("GET"))
+            .and(path(config.get_models_path()))
+            .respond_with(ResponseTemplate::new(503).set_body_raw("unavailable", "text/plain"))
+            .mount(&server)
+            .await;
+
+        let client = OpenAiClient::new(config);
+        assert!(client.get_models().await.is_err());
+        assert!(layer.contains(&["span openai_request"]));
+        assert!(layer.contains(&["event ERROR", "status 503"]));
+    }
+}
@@ -1,7 +1,19 @@
+use crate::{RateLimitInfo, ResponseFormat, RetryConfig, RunError, RunStatus};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::token::RotatingTokenProvider;
+use crate::OpenAiClient;
+use reqwest::header::HeaderValue;
+use reqwest::Method;
 use std::string::ToString;
 
 use thiserror::Error;
@@ -9,6 +21,16 @@ use thiserror::Error;
 const BASE_URL: &str = "https://api.openai.com";
 const DEFAULT_VERSION: &str = "v1";
 const ENV_TOKEN: &str = "OPENAI_API_KEY";
+const ENV_STAGING_TOKEN: &str = "OPENAI_STAGING_API_KEY";
+const ENV_USER_AGENT: &str = "OPENAI_USER_AGENT";
+const DEFAULT_MAX_RESPONSE_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// Characters encoded in query parameters, everything except unreserved ones.
+const QUERY_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
 
 pub type OpenAiResult<R> = Result<R, OpenAiError>;
 
@@ -20,33 +42,181 @@ pub enum OpenAiError {
     #[error("missing required parameter {name} in request {request}")]
     MissingRequestParameter { name: String, request: String },
 
+    #[error("invalid request parameter {name}: {reason}")]
+    InvalidRequestParameter { name: String, reason: String },
+
     #[error("openAi API returned unexpected response body")]
     UnexpectedApiResponse,
 
-    #[error("openAi API returned error")]
+    #[error("openAi API returned error: {0}")]
     ApiErrorResponse(OpenAiErrorDetails),
 
     #[error("openAi API returned unexpected json")]
     UnexpectedJsonResponse(Value),
 
     #[error("failed to execute openAi request")]
-    HttpError(#[from] reqwest::Error),
+    HttpError(reqwest::Error),
+
+    #[error("openAi request timed out")]
+    TimeoutError(reqwest::Error),
+
+    #[error("openAi API rate limit reached")]
+    RateLimitError {
+        retry_after: Option<Duration>,
+        details: Option<OpenAiErrorDetails>,
+        rate_limit: Option<Box<RateLimitInfo>>,
+    },
+
+    #[error("openAi API returned non json response with status {status}")]
+    HttpStatus { status: u16, body: Option<String> },
 
     #[error("failed to parse or encode json")]
     JsonEncodeError(#[from] serde_json::Error),
+
+    #[error("model {model} not found and no fallback model configured")]
+    NoFallbackAvailable { model: String },
+
+    #[error("openAi API returned no text after {attempts} attempts")]
+    EmptyResponse { attempts: u32 },
+
+    #[error("prompt of {prompt_tokens} tokens exceeds the context window of {max_tokens} tokens of model {model}")]
+    ContextWindowExceeded {
+        model: String,
+        /// The context window of the model in tokens.
+        max_tokens: u32,
+        prompt_tokens: u32,
+    },
+
+    #[error("prompt template {name} not found")]
+    TemplateNotFound { name: String },
+
+    #[error("no global prompt library set")]
+    PromptLibraryNotSet,
+
+    #[error("circuit breaker is open, openAi API requests are rejected")]
+    CircuitOpen,
+
+    #[error("response body exceeded limit of {limit} bytes after {received} bytes")]
+    ResponseTooLarge { received: usize, limit: usize },
+
+    #[error("run {run_id} ended with status {status}")]
+    RunFailed {
+        run_id: String,
+        status: RunStatus,
+        error: Option<RunError>,
+    },
+
+    #[error("failed to read or write file")]
+    IoError(#[from] std::io::Error),
+}
+
+impl OpenAiError {
+    fn api_error(message: &str, error_type: &str, code: &str) -> Self {
+        OpenAiError::ApiErrorResponse(OpenAiErrorDetails {
+            code: Some(code.to_string()),
+            message: message.to_string(),
+            param: None,
+            error_type: Some(error_type.to_string()),
+        })
+    }
+
+    /// Api error as returned when the rate limit is reached.
+    pub fn rate_limit() -> Self {
+        OpenAiError::api_error(
+            "Rate limit reached for requests",
+            "requests",
+            "rate_limit_exceeded",
+        )
+    }
+
+    /// Api error as returned for an invalid access token.
+    pub fn invalid_api_key() -> Self {
+        OpenAiError::api_error(
+            "Incorrect API key provided",
+            "invalid_request_error",
+            "invalid_api_key",
+        )
+    }
+
+    /// Api error as returned when requesting an unknown model.
+    pub fn model_not_found(model: &str) -> Self {
+        OpenAiError::api_error(
+            &format!("The model `{}` does not exist", model),
+            "invalid_request_error",
+            "model_not_found",
+        )
+    }
+
+    /// Returns true if this is an Api error caused by an unknown or
+    /// unavailable model.
+    pub fn is_model_not_found(&self) -> bool {
+        match self {
+            OpenAiError::ApiErrorResponse(details) => {
+                details.code.as_deref() == Some("model_not_found")
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true if this is an Api error caused by a prompt that was
+    /// rejected by the content policy.
+    pub fn is_content_policy_violation(&self) -> bool {
+        match self {
+            OpenAiError::ApiErrorResponse(details) => {
+                details.code.as_deref() == Some("content_policy_violation")
+                    || details.message.contains("safety system")
+            }
+            _ => false,
+        }
+    }
+
+    /// Api error as returned when the prompt exceeds the context window of
+    /// the model.
+    pub fn context_length_exceeded() -> Self {
+        OpenAiError::api_error(
+            "This model's maximum context length was exceeded",
+            "invalid_request_error",
+            "context_length_exceeded",
+        )
+    }
 }
 
+#[derive(Clone)]
 pub struct OpenAiConfig {
     base_url: String,
     version: String,
     access_token: String,
     model_path: String,
     completion_path: String,
+    chat_completion_path: String,
+    embedding_path: String,
+    moderation_path: String,
+    speech_path: String,
+    transcription_path: String,
+    translation_path: String,
+    file_path: String,
+    fine_tuning_path: String,
+    assistant_path: String,
+    thread_path: String,
+    batch_path: String,
     edit_path: String,
     image_path: String,
     image_create: String,
     image_edits: String,
     image_variations: String,
+    default_completion_params: Option<CompletionDefaults>,
+    token_provider: Option<Arc<RotatingTokenProvider>>,
+    model_fallbacks: Vec<(String, String)>,
+    user_agent: Option<String>,
+    max_response_body_bytes: Option<usize>,
+    organization: Option<String>,
+    project: Option<String>,
+    auth_scheme: AuthScheme,
+    query_params: Vec<(String, String)>,
+    request_timeout: Option<Duration>,
+    retry: Option<RetryConfig>,
+    token_env: Option<&'static str>,
+    cost_warning_threshold: Option<u32>,
 }
 
 /// Basic configuration params for running requests against OpenAi Api.
@@ -62,11 +232,56 @@ impl OpenAiConfig {
             access_token: access_token.to_string(),
             model_path: "models".to_string(),
             completion_path: "completions".to_string(),
+            chat_completion_path: "chat/completions".to_string(),
+            embedding_path: "embeddings".to_string(),
+            moderation_path: "moderations".to_string(),
+            speech_path: "audio/speech".to_string(),
+            transcription_path: "audio/transcriptions".to_string(),
+            translation_path: "audio/translations".to_string(),
+            file_path: "files".to_string(),
+            fine_tuning_path: "fine_tuning/jobs".to_string(),
+            assistant_path: "assistants".to_string(),
+            thread_path: "threads".to_string(),
+            batch_path: "batches".to_string(),
             edit_path: "edits".to_string(),
             image_path: "images".to_string(),
             image_create: "generations".to_string(),
             image_edits: "edits".to_string(),
             image_variations: "variations".to_string(),
+            default_completion_params: None,
+            token_provider: None,
+            model_fallbacks: Vec::new(),
+            user_agent: None,
+            max_response_body_bytes: Some(DEFAULT_MAX_RESPONSE_BODY_BYTES),
+            organization: None,
+            project: None,
+            auth_scheme: AuthScheme::Bearer,
+            query_params: Vec::new(),
+            request_timeout: None,
+            retry: None,
+            token_env: Some(ENV_TOKEN),
+            cost_warning_threshold: None,
+        }
+    }
+
+    /// Creates a config billing requests to the given organization.
+    pub fn new_with_org(access_token: &str, organization: &str) -> Self {
+        OpenAiConfig::new(access_token).organization(organization)
+    }
+
+    /// Creates a config for the given deployment environment. The access token
+    /// for [ApiEnvironment::Staging] is only read from env
+    /// OPENAI_STAGING_API_KEY, it never falls back to the production token.
+    /// All other environments resolve it from OPENAI_API_KEY.
+    pub fn for_environment(environment: ApiEnvironment) -> Self {
+        match environment {
+            ApiEnvironment::Production => OpenAiConfig::default(),
+            ApiEnvironment::Staging(base_url) => OpenAiConfig::new("")
+                .token_env(Some(ENV_STAGING_TOKEN))
+                .base_url(&base_url),
+            ApiEnvironment::Local(port) => {
+                OpenAiConfig::default().base_url(&format!("http://localhost:{}", port))
+            }
         }
     }
 
@@ -89,9 +304,206 @@ impl OpenAiConfig {
         self
     }
 
-    /// Returns the Api url for given path.
+    /// Set the env var the access token is read from if none is set, `None`
+    /// disables the env fallback.
+    pub(crate) fn token_env(mut self, var: Option<&'static str>) -> Self {
+        self.token_env = var;
+        self
+    }
+
+    /// Resolve the access token from the given provider on every request
+    /// instead of using a static token.
+    pub fn token_provider(mut self, provider: RotatingTokenProvider) -> Self {
+        self.token_provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// Removes the token provider, so the static or env token is used.
+    pub(crate) fn without_token_provider(mut self) -> Self {
+        self.token_provider = None;
+        self
+    }
+
+    /// Set the organization sent as OpenAI-Organization header with all
+    /// requests.
+    pub fn organization(mut self, organization: &str) -> Self {
+        self.organization = Some(organization.to_string());
+        self
+    }
+
+    /// Returns the configured organization.
+    pub fn get_organization(&self) -> Option<&str> {
+        self.organization.as_deref()
+    }
+
+    /// Set the project sent as OpenAI-Project header with all requests.
+    pub fn project(mut self, project: &str) -> Self {
+        self.project = Some(project.to_string());
+        self
+    }
+
+    /// Returns the configured project.
+    pub fn get_project(&self) -> Option<&str> {
+        self.project.as_deref()
+    }
+
+    /// Set the timeout of whole requests, from connecting until the response
+    /// body is read. This includes streamed responses. No timeout is set by
+    /// default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the request timeout.
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.request_timeout
+    }
+
+    /// Retry rate limited and failed requests. Requests are not retried by
+    /// default.
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Returns the retry config.
+    pub fn get_retry(&self) -> Option<&RetryConfig> {
+        self.retry.as_ref()
+    }
+
+    /// Set how the access token is sent to the Api.
+    pub fn auth_scheme(mut self, auth_scheme: AuthScheme) -> Self {
+        self.auth_scheme = auth_scheme;
+        self
+    }
+
+    /// Returns how the access token is sent to the Api.
+    pub fn get_auth_scheme(&self) -> AuthScheme {
+        self.auth_scheme
+    }
+
+    /// Add a query param sent with all requests.
+    pub fn with_query_param(mut self, key: &str, value: &str) -> Self {
+        self.query_params.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Returns the query params sent with all requests.
+    pub fn get_query_params(&self) -> &[(String, String)] {
+        &self.query_params
+    }
+
+    /// Set the User-Agent header sent with all requests.
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Returns the User-Agent to use. Falls back to env OPENAI_USER_AGENT and
+    /// then to the crate name and version. Values that are not valid header
+    /// values are skipped.
+    pub fn get_user_agent(&self) -> String {
+        let valid = |user_agent: &String| HeaderValue::from_str(user_agent).is_ok();
+        self.user_agent
+            .clone()
+            .filter(valid)
+            .or_else(|| env::var(ENV_USER_AGENT).ok().filter(valid))
+            .unwrap_or_else(|| format!("openai-client-rust/{}", env!("CARGO_PKG_VERSION")))
+    }
+
+    /// Set the maximum size of response bodies, `None` disables the limit.
+    /// Defaults to 10MB. Downloaded file contents are not limited.
+    pub fn with_max_response_body_bytes(mut self, limit: Option<usize>) -> Self {
+        self.max_response_body_bytes = limit;
+        self
+    }
+
+    /// Returns the maximum size of response bodies.
+    pub fn get_max_response_body_bytes(&self) -> Option<usize> {
+        self.max_response_body_bytes
+    }
+
+    /// Add a fallback model to use when the primary model is not available.
+    pub fn model_fallback(mut self, primary: &str, fallback: &str) -> Self {
+        self.model_fallbacks
+            .push((primary.to_string(), fallback.to_string()));
+        self
+    }
+
+    /// Returns the fallback model configured for the given model.
+    pub fn get_model_fallback(&self, model: &str) -> Option<&str> {
+        self.model_fallbacks
+            .iter()
+            .find(|(primary, _)| primary == model)
+            .map(|(_, fallback)| fallback.as_str())
+    }
+
+    /// Set default params that are applied to every completion request. Values
+    /// set on the request itself take precedence over these defaults.
+    pub fn with_completion_defaults(mut self, defaults: CompletionDefaults) -> Self {
+        self.default_completion_params = Some(defaults);
+        self
+    }
+
+    /// Returns the configured completion request defaults.
+    pub fn get_completion_defaults(&self) -> Option<&CompletionDefaults> {
+        self.default_completion_params.as_ref()
+    }
+
+    /// Warn via `tracing` about completion requests that may bill more than
+    /// the given number of tokens, see [crate::warn_if_expensive]. Requires
+    /// the `tracing` feature, no warnings are emitted by default.
+    pub fn with_cost_warning_threshold(mut self, threshold_tokens: u32) -> Self {
+        self.cost_warning_threshold = Some(threshold_tokens);
+        self
+    }
+
+    /// Returns the billed tokens above which completion requests are warned
+    /// about.
+    pub fn get_cost_warning_threshold(&self) -> Option<u32> {
+        self.cost_warning_threshold
+    }
+
+    /// Returns the Api url for given path. Surplus slashes between base url
+    /// and path are removed.
     pub fn api_url(&self, path: &str) -> String {
-        format!("{}/{}", self.base_url, path)
+        format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+
+    /// Returns the Api url for given path with the url encoded query params
+    /// appended.
+    pub fn api_url_with_params(&self, path: &str, params: &[(&str, &str)]) -> String {
+        let url = self.api_url(path);
+        if params.is_empty() {
+            return url;
+        }
+        let query = params
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "{}={}",
+                    utf8_percent_encode(key, QUERY_ENCODE_SET),
+                    utf8_percent_encode(value, QUERY_ENCODE_SET)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        format!("{}?{}", url, query)
+    }
+
+    /// Returns the configured Api version.
+    pub fn get_version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns the given path prefixed with the Api version.
+    pub fn get_version_path(&self, path: &str) -> String {
+        self.add_path_segment(&self.version, path.trim_start_matches('/'))
     }
 
     /// Returns the models path.
@@ -129,6 +541,157 @@ impl OpenAiConfig {
         self.add_path_segment(&self.version, &self.completion_path)
     }
 
+    /// Returns the chat completions path
+    pub fn get_chat_completion_path(&self) -> String {
+        self.add_path_segment(&self.version, &self.chat_completion_path)
+    }
+
+    /// Returns the embeddings path
+    pub fn get_embedding_path(&self) -> String {
+        self.add_path_segment(&self.version, &self.embedding_path)
+    }
+
+    /// Returns the moderations path
+    pub fn get_moderation_path(&self) -> String {
+        self.add_path_segment(&self.version, &self.moderation_path)
+    }
+
+    /// Returns the text to speech path
+    pub fn get_speech_path(&self) -> String {
+        self.add_path_segment(&self.version, &self.speech_path)
+    }
+
+    /// Returns the audio transcriptions path
+    pub fn get_transcription_path(&self) -> String {
+        self.add_path_segment(&self.version, &self.transcription_path)
+    }
+
+    /// Returns the audio translations path
+    pub fn get_translation_path(&self) -> String {
+        self.add_path_segment(&self.version, &self.translation_path)
+    }
+
+    /// Returns the files path
+    pub fn get_files_path(&self) -> String {
+        self.add_path_segment(&self.version, &self.file_path)
+    }
+
+    /// Returns the path for a specific file
+    pub fn get_file_path(&self, id: &str) -> String {
+        self.add_path_segment(&self.get_files_path(), id)
+    }
+
+    /// Returns the content path for a specific file
+    pub fn get_file_content_path(&self, id: &str) -> String {
+        self.add_path_segment(&self.get_file_path(id), "content")
+    }
+
+    /// Returns the fine-tuning jobs path
+    pub fn get_fine_tuning_jobs_path(&self) -> String {
+        self.add_path_segment(&self.version, &self.fine_tuning_path)
+    }
+
+    /// Returns the path for a specific fine-tuning job
+    pub fn get_fine_tuning_job_path(&self, id: &str) -> String {
+        self.add_path_segment(&self.get_fine_tuning_jobs_path(), id)
+    }
+
+    /// Returns the cancel path for a specific fine-tuning job
+    pub fn get_fine_tuning_job_cancel_path(&self, id: &str) -> String {
+        self.add_path_segment(&self.get_fine_tuning_job_path(id), "cancel")
+    }
+
+    /// Returns the events path for a specific fine-tuning job
+    pub fn get_fine_tuning_job_events_path(&self, id: &str) -> String {
+        self.add_path_segment(&self.get_fine_tuning_job_path(id), "events")
+    }
+
+    /// Returns the assistants path
+    pub fn get_assistants_path(&self) -> String {
+        self.add_path_segment(&self.version, &self.assistant_path)
+    }
+
+    /// Returns the path for a specific assistant
+    pub fn get_assistant_path(&self, id: &str) -> String {
+        self.add_path_segment(&self.get_assistants_path(), id)
+    }
+
+    /// Returns the threads path
+    pub fn get_threads_path(&self) -> String {
+        self.add_path_segment(&self.version, &self.thread_path)
+    }
+
+    /// Returns the path for a specific thread
+    pub fn get_thread_path(&self, id: &str) -> String {
+        self.add_path_segment(&self.get_threads_path(), id)
+    }
+
+    /// Returns the messages path of a thread
+    pub fn get_thread_messages_path(&self, thread_id: &str) -> String {
+        self.add_path_segment(&self.get_thread_path(thread_id), "messages")
+    }
+
+    /// Returns the path for a specific message of a thread
+    pub fn get_thread_message_path(&self, thread_id: &str, message_id: &str) -> String {
+        self.add_path_segment(&self.get_thread_messages_path(thread_id), message_id)
+    }
+
+    /// Returns the runs path of a thread
+    pub fn get_thread_runs_path(&self, thread_id: &str) -> String {
+        self.add_path_segment(&self.get_thread_path(thread_id), "runs")
+    }
+
+    /// Returns the path for a specific run of a thread
+    pub fn get_thread_run_path(&self, thread_id: &str, run_id: &str) -> String {
+        self.add_path_segment(&self.get_thread_runs_path(thread_id), run_id)
+    }
+
+    /// Returns the cancel path for a specific run of a thread
+    pub fn get_thread_run_cancel_path(&self, thread_id: &str, run_id: &str) -> String {
+        self.add_path_segment(&self.get_thread_run_path(thread_id, run_id), "cancel")
+    }
+
+    /// Returns the tool outputs path for a specific run of a thread
+    pub fn get_thread_run_tool_outputs_path(&self, thread_id: &str, run_id: &str) -> String {
+        self.add_path_segment(
+            &self.get_thread_run_path(thread_id, run_id),
+            "submit_tool_outputs",
+        )
+    }
+
+    /// Returns the batches path
+    pub fn get_batches_path(&self) -> String {
+        self.add_path_segment(&self.version, &self.batch_path)
+    }
+
+    /// Returns the path for a specific batch
+    pub fn get_batch_path(&self, id: &str) -> String {
+        self.add_path_segment(&self.get_batches_path(), id)
+    }
+
+    /// Returns the cancel path for a specific batch
+    pub fn get_batch_cancel_path(&self, id: &str) -> String {
+        self.add_path_segment(&self.get_batch_path(id), "cancel")
+    }
+
+    /// Prefixes the paths of the deployment scoped endpoints, all other paths
+    /// stay relative to the base url.
+    pub(crate) fn deployment_paths(mut self, prefix: &str) -> Self {
+        for path in [
+            &mut self.completion_path,
+            &mut self.chat_completion_path,
+            &mut self.embedding_path,
+            &mut self.edit_path,
+            &mut self.image_path,
+            &mut self.speech_path,
+            &mut self.transcription_path,
+            &mut self.translation_path,
+        ] {
+            *path = format!("{}/{}", prefix, path);
+        }
+        self
+    }
+
     fn image_path(&self, segment: &str) -> String {
         format!("{}/{}/{}", self.version, self.image_path, segment)
     }
@@ -139,15 +702,62 @@ impl OpenAiConfig {
 
     /// Returns the OpenAi Api access token
     pub fn get_access_token(&self) -> OpenAiResult<String> {
-        if self.access_token.is_empty() {
-            match env::var(ENV_TOKEN) {
-                Ok(token) => Ok(token),
-                Err(_) => Err(OpenAiError::MissingTokenError),
-            }
+        if let Some(provider) = &self.token_provider {
+            provider.get_token()
+        } else if self.access_token.is_empty() {
+            self.token_env
+                .and_then(|var| env::var(var).ok())
+                .ok_or(OpenAiError::MissingTokenError)
         } else {
             Ok(self.access_token.to_string())
         }
     }
+
+    /// Sends minimal authenticated requests to the models, completion and
+    /// image endpoints and reports the status of each. Completion and image
+    /// requests are sent with an empty body, so they are rejected by the Api
+    /// without creating anything.
+    pub async fn validate_connectivity(&self, client: &OpenAiClient) -> ConnectivityReport {
+        let (models_path, completion_path, image_path) = (
+            self.get_models_path(),
+            self.get_completion_path(),
+            self.get_create_image_path(),
+        );
+        let (models_endpoint, completion_endpoint, image_endpoint) = tokio::join!(
+            client.probe_connectivity(Method::GET, &models_path),
+            client.probe_connectivity(Method::POST, &completion_path),
+            client.probe_connectivity(Method::POST, &image_path),
+        );
+        ConnectivityReport {
+            models_endpoint,
+            completion_endpoint,
+            image_endpoint,
+        }
+    }
+
+    /// Returns a summary of the configuration for troubleshooting. Secrets
+    /// are not included.
+    pub fn diagnostics(&self) -> ConfigDiagnostics {
+        let token_source = if self.token_provider.is_some() {
+            Some(TokenSource::Provider)
+        } else if !self.access_token.is_empty() {
+            Some(TokenSource::Direct)
+        } else if self.token_env.is_some_and(|var| env::var(var).is_ok()) {
+            Some(TokenSource::EnvVar)
+        } else {
+            None
+        };
+        ConfigDiagnostics {
+            base_url: self.base_url.clone(),
+            version: self.version.clone(),
+            has_access_token: self.get_access_token().is_ok(),
+            token_source,
+            organization: self.organization.clone(),
+            timeout_ms: self.request_timeout.map(|t| t.as_millis() as u64),
+            retry_config: self.retry.as_ref().map(RetryConfigSummary::from),
+            ping_ms: None,
+        }
+    }
 }
 
 impl Default for OpenAiConfig {
@@ -156,8 +766,166 @@ impl Default for OpenAiConfig {
     }
 }
 
+/// The environment an [OpenAiConfig] is created for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiEnvironment {
+    /// The official OpenAi Api.
+    Production,
+    /// A staging deployment reachable under the given base url.
+    Staging(String),
+    /// A local server listening on the given port.
+    Local(u16),
+}
+
+/// How the access token is sent to the Api.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthScheme {
+    /// `Authorization: Bearer {token}` as used by OpenAi.
+    Bearer,
+    /// `api-key: {token}` as used by Azure OpenAI.
+    ApiKey,
+}
+
+/// Where the access token of a config is resolved from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenSource {
+    /// Token set on the config.
+    Direct,
+    /// Token read from env OPENAI_API_KEY.
+    EnvVar,
+    /// Token resolved from a [RotatingTokenProvider].
+    Provider,
+}
+
+/// Summary of an [OpenAiConfig] without secrets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDiagnostics {
+    pub base_url: String,
+    pub version: String,
+    pub has_access_token: bool,
+    /// `None` if no access token could be found.
+    pub token_source: Option<TokenSource>,
+    pub organization: Option<String>,
+    pub timeout_ms: Option<u64>,
+    pub retry_config: Option<RetryConfigSummary>,
+    /// Round trip duration of a ping, only set by a client self test.
+    pub ping_ms: Option<u64>,
+}
+
+impl std::fmt::Display for ConfigDiagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "base url:     {}", self.base_url)?;
+        writeln!(f, "api version:  {}", self.version)?;
+        writeln!(f, "access token: {}", self.has_access_token)?;
+        match self.token_source {
+            Some(source) => writeln!(f, "token source: {:?}", source)?,
+            None => writeln!(f, "token source: none")?,
+        }
+        match &self.organization {
+            Some(organization) => writeln!(f, "organization: {}", organization)?,
+            None => writeln!(f, "organization: none")?,
+        }
+        match self.timeout_ms {
+            Some(timeout) => writeln!(f, "timeout:      {}ms", timeout)?,
+            None => writeln!(f, "timeout:      none")?,
+        }
+        match &self.retry_config {
+            Some(retry) => writeln!(
+                f,
+                "retries:      {} (delay {}ms to {}ms)",
+                retry.max_retries, retry.initial_delay_ms, retry.max_delay_ms
+            )?,
+            None => writeln!(f, "retries:      none")?,
+        }
+        match self.ping_ms {
+            Some(ping) => write!(f, "ping:         {}ms", ping),
+            None => write!(f, "ping:         not run"),
+        }
+    }
+}
+
+/// The retry settings of a [ConfigDiagnostics].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryConfigSummary {
+    pub max_retries: u32,
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl From<&RetryConfig> for RetryConfigSummary {
+    fn from(retry: &RetryConfig) -> Self {
+        RetryConfigSummary {
+            max_retries: retry.max_retries,
+            initial_delay_ms: retry.initial_delay.as_millis() as u64,
+            max_delay_ms: retry.max_delay.as_millis() as u64,
+        }
+    }
+}
+
+/// Status of a single endpoint in a [ConnectivityReport].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EndpointStatus {
+    Reachable { latency_ms: u64 },
+    Unreachable { reason: String },
+}
+
+impl EndpointStatus {
+    pub fn is_reachable(&self) -> bool {
+        matches!(self, EndpointStatus::Reachable { .. })
+    }
+}
+
+/// Reachability of the configured Api endpoints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectivityReport {
+    pub models_endpoint: EndpointStatus,
+    pub completion_endpoint: EndpointStatus,
+    pub image_endpoint: EndpointStatus,
+}
+
+/// Describes which optional endpoints an OpenAi compatible Api supports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiCompatibilityReport {
+    pub version: String,
+    pub supports_chat: bool,
+    pub supports_embeddings: bool,
+    pub supports_fine_tuning: bool,
+    pub supports_assistants: bool,
+}
+
+/// Default values for completion requests that can be set once on the
+/// config instead of on every request.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CompletionDefaults {
+    pub max_tokens: Option<i64>,
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub n: Option<i64>,
+    pub presence_penalty: Option<f64>,
+    pub frequency_penalty: Option<f64>,
+    pub best_of: Option<i64>,
+    pub user: Option<String>,
+}
+
+impl CompletionDefaults {
+    /// Fills all unset fields of the request with the default values.
+    pub fn apply(&self, request: CompletionRequest) -> CompletionRequest {
+        CompletionRequest {
+            max_tokens: request.max_tokens.or(self.max_tokens),
+            temperature: request.temperature.or(self.temperature),
+            top_p: request.top_p.or(self.top_p),
+            n: request.n.or(self.n),
+            presence_penalty: request.presence_penalty.or(self.presence_penalty),
+            frequency_penalty: request.frequency_penalty.or(self.frequency_penalty),
+            best_of: request.best_of.or(self.best_of),
+            user: request.user.or_else(|| self.user.clone()),
+            ..request
+        }
+    }
+}
+
 /// A wrapper around the OpenAi response payload.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum OpenAiResponse<T> {
     Success(T),
@@ -165,24 +933,87 @@ pub enum OpenAiResponse<T> {
     Other(Value),
 }
 
+impl<T> OpenAiResponse<T> {
+    /// Transforms a success payload, passing errors and other responses through.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> OpenAiResponse<U> {
+        match self {
+            OpenAiResponse::Success(res) => OpenAiResponse::Success(f(res)),
+            OpenAiResponse::Error(err) => OpenAiResponse::Error(err),
+            OpenAiResponse::Other(value) => OpenAiResponse::Other(value),
+        }
+    }
+
+    /// Calls `f` with a success payload, passing errors and other responses through.
+    pub fn and_then<U, F: FnOnce(T) -> OpenAiResponse<U>>(self, f: F) -> OpenAiResponse<U> {
+        match self {
+            OpenAiResponse::Success(res) => f(res),
+            OpenAiResponse::Error(err) => OpenAiResponse::Error(err),
+            OpenAiResponse::Other(value) => OpenAiResponse::Other(value),
+        }
+    }
+
+    /// Converts the response into a result, mapping api errors and unexpected
+    /// json to the matching `OpenAiError`.
+    pub fn into_result(self) -> OpenAiResult<T> {
+        match self {
+            OpenAiResponse::Success(res) => Ok(res),
+            OpenAiResponse::Error(err) => Err(OpenAiError::ApiErrorResponse(err.error)),
+            OpenAiResponse::Other(value) => Err(OpenAiError::UnexpectedJsonResponse(value)),
+        }
+    }
+}
+
 /// The payload of an OpenAi error response.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
 pub struct OpenAiErrorResponse {
     pub error: OpenAiErrorDetails,
 }
 
 /// The error details provided by OpenAi error responses.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Builder, Debug, Clone, PartialEq)]
+#[builder(setter(strip_option, into))]
 pub struct OpenAiErrorDetails {
+    /// Machine readable error code e.g. `invalid_api_key`.
+    #[builder(default)]
     pub code: Option<String>,
+    /// Human readable description of the error.
     pub message: String,
+    /// The request parameter that caused the error, if any.
+    #[builder(default)]
     pub param: Option<String>,
-    #[serde(rename = "type")]
-    pub r#type: Option<String>,
+    /// The error category e.g. `invalid_request_error`.
+    #[builder(default)]
+    #[serde(rename = "type", alias = "error_type")]
+    pub error_type: Option<String>,
+}
+
+impl OpenAiErrorDetails {
+    /// Returns the error type. Kept for compatibility with the former
+    /// `r#type` field.
+    pub fn r#type(&self) -> Option<&str> {
+        self.error_type.as_deref()
+    }
+}
+
+impl std::fmt::Display for OpenAiErrorDetails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(error_type) = &self.error_type {
+            write!(f, " (type: {})", error_type)?;
+        }
+        if let Some(code) = &self.code {
+            write!(f, " (code: {})", code)?;
+        }
+        if let Some(param) = &self.param {
+            write!(f, " (param: {})", param)?;
+        }
+        Ok(())
+    }
 }
 
 /// Model permissions response.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct OpenAiModelPermission {
     pub allow_create_engine: bool,
     pub allow_fine_tuning: bool,
@@ -194,16 +1025,16 @@ pub struct OpenAiModelPermission {
     pub group: Option<String>,
     pub id: String,
     pub is_blocking: bool,
-    pub object: String,
+    pub object: ObjectType,
     pub organization: String,
 }
 
 /// Model properties response
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct OpenAiModel {
     pub created: i64,
     pub id: String,
-    pub object: String,
+    pub object: ObjectType,
     pub owned_by: String,
     pub parent: Option<String>,
     pub permission: Vec<OpenAiModelPermission>,
@@ -211,20 +1042,264 @@ pub struct OpenAiModel {
 }
 
 /// Models list response.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct OpenAiModelResponse {
     pub data: Vec<OpenAiModel>,
-    pub object: Option<String>,
+    pub object: Option<ObjectType>,
 }
 
-/// Multi type response that can either be a string or
-/// a list of strings.
-#[derive(Serialize, Deserialize, Debug)]
-#[cfg_attr(test, derive(PartialEq))]
-#[serde(untagged)]
-pub enum StringOrListParam {
-    StringParam(String),
-    ListParam(Vec<String>),
+/// Sort order of list requests by creation time.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ListOrder {
+    Asc,
+    Desc,
+}
+
+impl ListOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListOrder::Asc => "asc",
+            ListOrder::Desc => "desc",
+        }
+    }
+}
+
+/// Cursor pagination params of list requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListParams {
+    pub limit: Option<u32>,
+    pub order: Option<ListOrder>,
+    /// Object id to start the list after.
+    pub after: Option<String>,
+    /// Object id to end the list before.
+    pub before: Option<String>,
+}
+
+impl ListParams {
+    /// Returns the set params as query params.
+    pub fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(limit) = self.limit {
+            query.push(("limit", limit.to_string()));
+        }
+        if let Some(order) = self.order {
+            query.push(("order", order.as_str().to_string()));
+        }
+        if let Some(after) = &self.after {
+            query.push(("after", after.clone()));
+        }
+        if let Some(before) = &self.before {
+            query.push(("before", before.clone()));
+        }
+        query
+    }
+}
+
+/// A page of a cursor paginated list.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ListResponse<T> {
+    pub object: ObjectType,
+    pub data: Vec<T>,
+    #[serde(default)]
+    pub first_id: Option<String>,
+    #[serde(default)]
+    pub last_id: Option<String>,
+    pub has_more: bool,
+}
+
+impl<T> ListResponse<T> {
+    /// Returns the params for requesting the next page, `None` on the last
+    /// page.
+    pub fn next_page(&self, params: &ListParams) -> Option<ListParams> {
+        if !self.has_more {
+            return None;
+        }
+        self.last_id.as_ref().map(|last_id| ListParams {
+            after: Some(last_id.clone()),
+            before: None,
+            ..params.clone()
+        })
+    }
+}
+
+/// Response of deleting an object.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeleteResponse {
+    pub id: String,
+    pub object: ObjectType,
+    pub deleted: bool,
+}
+
+/// The type of object returned by the Api.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum ObjectType {
+    TextCompletion,
+    ChatCompletion,
+    ChatCompletionChunk,
+    Edit,
+    Embedding,
+    List,
+    Model,
+    ModelPermission,
+    File,
+    FineTuningJob,
+    FineTuningJobEvent,
+    Assistant,
+    Thread,
+    ThreadMessage,
+    ThreadRun,
+    Batch,
+    /// An object type not known to this client.
+    Unknown(String),
+}
+
+impl ObjectType {
+    /// Returns the Api name of the object type.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ObjectType::TextCompletion => "text_completion",
+            ObjectType::ChatCompletion => "chat.completion",
+            ObjectType::ChatCompletionChunk => "chat.completion.chunk",
+            ObjectType::Edit => "edit",
+            ObjectType::Embedding => "embedding",
+            ObjectType::List => "list",
+            ObjectType::Model => "model",
+            ObjectType::ModelPermission => "model_permission",
+            ObjectType::File => "file",
+            ObjectType::FineTuningJob => "fine_tuning.job",
+            ObjectType::FineTuningJobEvent => "fine_tuning.job.event",
+            ObjectType::Assistant => "assistant",
+            ObjectType::Thread => "thread",
+            ObjectType::ThreadMessage => "thread.message",
+            ObjectType::ThreadRun => "thread.run",
+            ObjectType::Batch => "batch",
+            ObjectType::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<String> for ObjectType {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "text_completion" => ObjectType::TextCompletion,
+            "chat.completion" => ObjectType::ChatCompletion,
+            "chat.completion.chunk" => ObjectType::ChatCompletionChunk,
+            "edit" => ObjectType::Edit,
+            "embedding" => ObjectType::Embedding,
+            "list" => ObjectType::List,
+            "model" => ObjectType::Model,
+            "model_permission" => ObjectType::ModelPermission,
+            "file" => ObjectType::File,
+            "fine_tuning.job" => ObjectType::FineTuningJob,
+            "fine_tuning.job.event" => ObjectType::FineTuningJobEvent,
+            "assistant" => ObjectType::Assistant,
+            "thread" => ObjectType::Thread,
+            "thread.message" => ObjectType::ThreadMessage,
+            "thread.run" => ObjectType::ThreadRun,
+            "batch" => ObjectType::Batch,
+            _ => ObjectType::Unknown(value),
+        }
+    }
+}
+
+impl From<reqwest::Error> for OpenAiError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            OpenAiError::TimeoutError(err)
+        } else {
+            OpenAiError::HttpError(err)
+        }
+    }
+}
+
+impl From<derive_builder::UninitializedFieldError> for OpenAiError {
+    fn from(err: derive_builder::UninitializedFieldError) -> Self {
+        OpenAiError::MissingRequestParameter {
+            name: err.field_name().to_string(),
+            request: "builder".to_string(),
+        }
+    }
+}
+
+impl From<ObjectType> for String {
+    fn from(value: ObjectType) -> Self {
+        match value {
+            ObjectType::Unknown(value) => value,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The reason the model stopped generating tokens.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum FinishReason {
+    Stop,
+    Length,
+    ContentFilter,
+    ToolCalls,
+    FunctionCall,
+    /// A finish reason not known to this client.
+    Unknown(String),
+}
+
+impl FinishReason {
+    /// Returns the Api name of the finish reason.
+    pub fn as_str(&self) -> &str {
+        match self {
+            FinishReason::Stop => "stop",
+            FinishReason::Length => "length",
+            FinishReason::ContentFilter => "content_filter",
+            FinishReason::ToolCalls => "tool_calls",
+            FinishReason::FunctionCall => "function_call",
+            FinishReason::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<String> for FinishReason {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "stop" => FinishReason::Stop,
+            "length" => FinishReason::Length,
+            "content_filter" => FinishReason::ContentFilter,
+            "tool_calls" => FinishReason::ToolCalls,
+            "function_call" => FinishReason::FunctionCall,
+            _ => FinishReason::Unknown(value),
+        }
+    }
+}
+
+impl From<FinishReason> for String {
+    fn from(value: FinishReason) -> Self {
+        match value {
+            FinishReason::Unknown(value) => value,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for FinishReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Multi type response that can either be a string or
+/// a list of strings.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum StringOrListParam {
+    StringParam(String),
+    ListParam(Vec<String>),
 }
 
 impl Clone for StringOrListParam {
@@ -236,6 +1311,38 @@ impl Clone for StringOrListParam {
     }
 }
 
+impl StringOrListParam {
+    /// Returns the number of items, a single string counts as one item.
+    pub fn len(&self) -> usize {
+        match self {
+            StringOrListParam::StringParam(_) => 1,
+            StringOrListParam::ListParam(list) => list.len(),
+        }
+    }
+
+    /// Returns true for an empty string or an empty list.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            StringOrListParam::StringParam(str) => str.is_empty(),
+            StringOrListParam::ListParam(list) => list.is_empty(),
+        }
+    }
+
+    /// Returns the total number of characters of all strings.
+    pub fn char_count(&self) -> usize {
+        self.iter().map(|s| s.chars().count()).sum()
+    }
+
+    /// Iterates over all strings.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        let items = match self {
+            StringOrListParam::StringParam(str) => std::slice::from_ref(str),
+            StringOrListParam::ListParam(list) => list.as_slice(),
+        };
+        items.iter().map(String::as_str)
+    }
+}
+
 impl From<&str> for StringOrListParam {
     fn from(value: &str) -> Self {
         StringOrListParam::StringParam(value.to_string())
@@ -255,51 +1362,323 @@ impl From<&Vec<&str>> for StringOrListParam {
 }
 
 /// The token usage as returned in some responses.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Usage {
     pub prompt_tokens: i64,
     pub completion_tokens: Option<i64>,
     pub total_tokens: i64,
 }
 
+impl Usage {
+    /// Returns true if no tokens have been used.
+    pub fn is_empty(&self) -> bool {
+        self.total_tokens == 0
+    }
+
+    /// Adds the token counts of both usages, saturating at the numeric bounds
+    /// instead of overflowing.
+    pub fn saturating_add(&self, other: &Usage) -> Usage {
+        let completion_tokens = match (self.completion_tokens, other.completion_tokens) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0).saturating_add(b.unwrap_or(0))),
+        };
+        Usage {
+            prompt_tokens: self.prompt_tokens.saturating_add(other.prompt_tokens),
+            completion_tokens,
+            total_tokens: self.total_tokens.saturating_add(other.total_tokens),
+        }
+    }
+}
+
+impl Default for Usage {
+    fn default() -> Self {
+        Usage {
+            prompt_tokens: 0,
+            completion_tokens: Some(0),
+            total_tokens: 0,
+        }
+    }
+}
+
+impl std::ops::Add for Usage {
+    type Output = Usage;
+
+    fn add(self, other: Usage) -> Usage {
+        self.saturating_add(&other)
+    }
+}
+
+impl std::ops::AddAssign for Usage {
+    fn add_assign(&mut self, other: Usage) {
+        *self = self.saturating_add(&other);
+    }
+}
+
+/// A partial text result sent per event of a streamed completion.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StreamChunk {
+    pub id: Option<String>,
+    pub object: ObjectType,
+    pub created: i64,
+    pub model: Option<String>,
+    pub choices: Vec<TextChoice>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
 /// Container for a text base result.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TextResult {
     pub id: Option<String>,
-    pub object: String,
+    pub object: ObjectType,
     pub created: i64,
     pub model: Option<String>,
+    /// Identifies the backend configuration the model ran with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_fingerprint: Option<String>,
     pub choices: Vec<TextChoice>,
     pub usage: Usage,
 }
 
+impl TextResult {
+    /// Returns the choice with the lowest index, which is the most likely one.
+    pub fn best_choice(&self) -> Option<&TextChoice> {
+        self.choices.iter().min()
+    }
+
+    /// Returns all choices ordered by index.
+    pub fn choices_sorted(&self) -> Vec<&TextChoice> {
+        let mut choices: Vec<&TextChoice> = self.choices.iter().collect();
+        choices.sort();
+        choices
+    }
+
+    /// Returns the text of the most likely choice.
+    pub fn best_text(&self) -> Option<&str> {
+        self.best_choice().map(|c| c.text.as_str())
+    }
+
+    /// Returns the text of the most likely choice with surrounding
+    /// whitespace removed.
+    pub fn best_text_trimmed(&self) -> Option<String> {
+        self.best_text().map(|t| t.trim().to_string())
+    }
+
+    /// Returns the trimmed texts of all choices.
+    pub fn all_texts_trimmed(&self) -> Vec<String> {
+        self.choices
+            .iter()
+            .map(|c| c.text.trim().to_string())
+            .collect()
+    }
+
+    /// Returns the text inserted by a fill-in-the-middle completion, removing
+    /// the prefix and suffix if the model repeated them.
+    pub fn extract_inserted_text(&self, prefix: &str, suffix: &str) -> Option<String> {
+        self.best_text().map(|text| {
+            let text = text.strip_prefix(prefix).unwrap_or(text);
+            let text = text.strip_suffix(suffix).unwrap_or(text);
+            text.to_string()
+        })
+    }
+
+    /// Parses the text of the most likely choice as json.
+    pub fn to_structured<T: DeserializeOwned>(&self) -> OpenAiResult<T> {
+        self.best_choice()
+            .ok_or(OpenAiError::UnexpectedApiResponse)?
+            .to_structured()
+    }
+
+    /// Parses the json embedded in the text of the most likely choice.
+    pub fn to_structured_lossy<T: DeserializeOwned>(&self) -> OpenAiResult<T> {
+        self.best_choice()
+            .ok_or(OpenAiError::UnexpectedApiResponse)?
+            .to_structured_lossy()
+    }
+
+    /// Returns true if at least one choice contains non whitespace text.
+    pub fn has_text(&self) -> bool {
+        self.choices.iter().any(|c| !c.text.trim().is_empty())
+    }
+
+    /// Returns true if the most likely choice finished because the model
+    /// reached a natural stop point.
+    pub fn is_complete(&self) -> bool {
+        self.best_choice()
+            .map(|c| c.finish_reason == Some(FinishReason::Stop))
+            .unwrap_or(false)
+    }
+}
+
+impl From<TextResult> for Vec<String> {
+    /// Collects the texts of all choices ordered by index.
+    fn from(value: TextResult) -> Self {
+        let mut choices = value.choices;
+        choices.sort();
+        choices.into_iter().map(|c| c.text).collect()
+    }
+}
+
+impl TryFrom<TextResult> for String {
+    type Error = OpenAiError;
+
+    /// Returns the text of the most likely choice.
+    fn try_from(value: TextResult) -> Result<Self, Self::Error> {
+        Vec::<String>::from(value)
+            .into_iter()
+            .next()
+            .ok_or(OpenAiError::UnexpectedApiResponse)
+    }
+}
+
+/// Returns the json object or array embedded in the given text, stripping
+/// any surrounding prose.
+pub fn extract_json(text: &str) -> Option<&str> {
+    let start = text.find(['{', '['])?;
+    let close = if text[start..].starts_with('{') {
+        '}'
+    } else {
+        ']'
+    };
+    let end = text.rfind(close)?;
+    if end < start {
+        return None;
+    }
+    Some(&text[start..=end])
+}
+
 /// A choice result for text based operations
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct TextChoice {
     pub text: String,
     pub index: i64,
     pub logprobs: Option<i64>,
-    pub finish_reason: Option<String>,
+    pub finish_reason: Option<FinishReason>,
+}
+
+impl PartialOrd for TextChoice {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Choices are ordered by their index, the remaining fields only break ties
+/// to stay consistent with equality.
+impl Ord for TextChoice {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.index
+            .cmp(&other.index)
+            .then_with(|| self.text.cmp(&other.text))
+            .then_with(|| self.logprobs.cmp(&other.logprobs))
+            .then_with(|| {
+                let reason = |c: &Self| c.finish_reason.as_ref().map(|r| r.as_str().to_string());
+                reason(self).cmp(&reason(other))
+            })
+    }
+}
+
+impl TextChoice {
+    /// Parses the choice text as json.
+    pub fn to_structured<T: DeserializeOwned>(&self) -> OpenAiResult<T> {
+        serde_json::from_str(&self.text).map_err(OpenAiError::JsonEncodeError)
+    }
+
+    /// Parses the json embedded in the choice text, ignoring any prose
+    /// before or after it.
+    pub fn to_structured_lossy<T: DeserializeOwned>(&self) -> OpenAiResult<T> {
+        let json = extract_json(&self.text).unwrap_or(&self.text);
+        serde_json::from_str(json).map_err(OpenAiError::JsonEncodeError)
+    }
 }
 
 /// A single image item
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct ImageItem {
     pub url: Option<String>,
     pub b64_json: Option<String>,
 }
 
+impl std::fmt::Debug for ImageItem {
+    /// Shows the size of base64 data instead of the potentially huge data.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImageItem")
+            .field("url", &self.url)
+            .field(
+                "b64_json",
+                &self
+                    .b64_json
+                    .as_ref()
+                    .map(|b64| format!("base64({} bytes)", b64.len())),
+            )
+            .finish()
+    }
+}
+
+impl std::fmt::Display for ImageItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.url, &self.b64_json) {
+            (Some(url), _) => write!(f, "{}", url),
+            (None, Some(b64)) => write!(f, "[base64 image, {} bytes]", b64.len()),
+            (None, None) => write!(f, "[empty image]"),
+        }
+    }
+}
+
 /// A result returned by image operations
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ImageResult {
     pub created: u64,
     pub data: Vec<ImageItem>,
 }
 
+impl From<ImageResult> for Vec<String> {
+    /// Collects the url, or if not available the base64 data, of all images.
+    fn from(value: ImageResult) -> Self {
+        value
+            .data
+            .into_iter()
+            .map(|item| item.url.or(item.b64_json).unwrap_or_default())
+            .collect()
+    }
+}
+
+impl std::fmt::Display for ImageResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ImageResult(n={}, created={})",
+            self.data.len(),
+            self.created
+        )
+    }
+}
+
+/// Json (de)serialization of requests, e.g. for storing request templates
+/// in config files.
+pub trait Serializable: Serialize + DeserializeOwned {
+    /// Returns the request as pretty printed json.
+    fn to_json_pretty(&self) -> OpenAiResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parses a request from a json string.
+    fn from_json(json: &str) -> OpenAiResult<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Reads and parses a request from a json file.
+    fn from_json_file(path: &Path) -> OpenAiResult<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+impl Serializable for CompletionRequest {}
+impl Serializable for EditRequest {}
+impl Serializable for CreateImageRequest {}
+
 /// Json data required for doing text completion requests.
-#[derive(Serialize, Deserialize, Builder, Debug, Default)]
+#[derive(Serialize, Deserialize, Builder, Debug, Default, Clone, PartialEq)]
 #[builder(setter(strip_option, into))]
-#[cfg_attr(test, derive(PartialEq))]
 pub struct CompletionRequest {
     pub model: String,
     #[builder(default)]
@@ -313,10 +1692,10 @@ pub struct CompletionRequest {
     pub max_tokens: Option<i64>,
     #[builder(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub temperature: Option<i64>,
+    pub temperature: Option<f64>,
     #[builder(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub top_p: Option<i64>,
+    pub top_p: Option<f64>,
     #[builder(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub n: Option<i64>,
@@ -334,11 +1713,13 @@ pub struct CompletionRequest {
     pub stop: Option<StringOrListParam>,
     #[builder(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub presence_penalty: Option<i64>,
+    pub presence_penalty: Option<f64>,
     #[builder(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub frequency_penalty: Option<i64>,
-    #[builder(default)]
+    pub frequency_penalty: Option<f64>,
+    /// Number of completions generated server side of which only the best
+    /// one is returned.
+    #[builder(default, setter(custom))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub best_of: Option<i64>,
     #[builder(default)]
@@ -347,12 +1728,156 @@ pub struct CompletionRequest {
     #[builder(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
+    /// Makes sampling mostly deterministic for equal requests.
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    /// Only `Text` and `JsonObject` are supported by text completions.
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+}
+
+pub(crate) fn invalid_parameter(name: &str, reason: &str) -> OpenAiError {
+    OpenAiError::InvalidRequestParameter {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+const MAX_METADATA_KEYS: usize = 16;
+const MAX_METADATA_VALUE_BYTES: usize = 512;
+
+/// Validates the limits of metadata attached to Assistants Api objects.
+pub(crate) fn validate_metadata(metadata: Option<&HashMap<String, String>>) -> OpenAiResult<()> {
+    let Some(metadata) = metadata else {
+        return Ok(());
+    };
+    if metadata.len() > MAX_METADATA_KEYS {
+        return Err(invalid_parameter(
+            "metadata",
+            &format!(
+                "metadata must not have more than {} keys",
+                MAX_METADATA_KEYS
+            ),
+        ));
+    }
+    match metadata
+        .iter()
+        .find(|(_, value)| value.len() > MAX_METADATA_VALUE_BYTES)
+    {
+        Some((key, _)) => Err(invalid_parameter(
+            "metadata",
+            &format!(
+                "value of {} must not exceed {} bytes",
+                key, MAX_METADATA_VALUE_BYTES
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn validate_range(
+    name: &str,
+    value: Option<f64>,
+    min: f64,
+    max: f64,
+) -> OpenAiResult<()> {
+    match value {
+        Some(value) if !(min..=max).contains(&value) => Err(invalid_parameter(
+            name,
+            &format!("{} must be between {} and {}", name, min, max),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Validates the sampling parameters shared by text and chat requests.
+pub(crate) fn validate_sampling(
+    temperature: Option<f64>,
+    top_p: Option<f64>,
+    presence_penalty: Option<f64>,
+    frequency_penalty: Option<f64>,
+) -> OpenAiResult<()> {
+    validate_range("temperature", temperature, 0.0, 2.0)?;
+    validate_range("top_p", top_p, 0.0, 1.0)?;
+    validate_range("presence_penalty", presence_penalty, -2.0, 2.0)?;
+    validate_range("frequency_penalty", frequency_penalty, -2.0, 2.0)
+}
+
+fn validate_n_best_of(n: Option<i64>, best_of: Option<i64>) -> OpenAiResult<()> {
+    if matches!(n, Some(n) if n < 1) {
+        return Err(invalid_parameter("n", "n must be at least 1"));
+    }
+    if matches!(best_of, Some(best_of) if best_of < 1) {
+        return Err(invalid_parameter("best_of", "best_of must be at least 1"));
+    }
+    match (n, best_of) {
+        (Some(n), Some(best_of)) if n > best_of => Err(invalid_parameter(
+            "n",
+            "n must be less than or equal to best_of",
+        )),
+        _ => Ok(()),
+    }
+}
+
+impl CompletionRequest {
+    /// Creates a fill-in-the-middle request where the model generates the
+    /// text between `prefix` and `suffix`. Only models supporting the suffix
+    /// parameter, like Codex based models or text-davinci-003, can be used.
+    pub fn with_fim(prefix: &str, suffix: &str, model: &str) -> CompletionRequest {
+        CompletionRequest {
+            model: model.to_string(),
+            prompt: Some(prefix.into()),
+            suffix: Some(suffix.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Validates constraints between request parameters that the Api
+    /// would otherwise reject.
+    pub fn validate(&self) -> OpenAiResult<()> {
+        validate_n_best_of(self.n, self.best_of)?;
+        if matches!(
+            self.response_format,
+            Some(ResponseFormat::JsonSchema { .. })
+        ) {
+            return Err(invalid_parameter(
+                "response_format",
+                "json_schema is only supported by chat completions",
+            ));
+        }
+        validate_sampling(
+            self.temperature,
+            self.top_p,
+            self.presence_penalty,
+            self.frequency_penalty,
+        )
+    }
+}
+
+impl CompletionRequestBuilder {
+    /// Sets the number of server side completions. Takes an unsigned integer
+    /// so that neither booleans nor negative values are accepted.
+    ///
+    /// ```compile_fail
+    /// openai_client::CompletionRequestBuilder::default().best_of(true);
+    /// ```
+    pub fn best_of(&mut self, best_of: u32) -> &mut Self {
+        self.best_of = Some(Some(best_of.into()));
+        self
+    }
+
+    /// Sets `n` and `best_of` together, ensuring that `best_of >= n`.
+    pub fn n_with_best_of(&mut self, n: u32, best_of: u32) -> OpenAiResult<&mut Self> {
+        validate_n_best_of(Some(n.into()), Some(best_of.into()))?;
+        Ok(self.n(n).best_of(best_of))
+    }
 }
 
 /// Json data required for doing text edit requests.
-#[derive(Serialize, Deserialize, Builder, Debug, Default)]
+#[derive(Serialize, Deserialize, Builder, Debug, Default, Clone, PartialEq)]
 #[builder(setter(strip_option, into))]
-#[cfg_attr(test, derive(PartialEq))]
 pub struct EditRequest {
     pub model: String,
     #[builder(default)]
@@ -364,16 +1889,23 @@ pub struct EditRequest {
     pub n: Option<i64>,
     #[builder(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub temperature: Option<i64>,
+    pub temperature: Option<f64>,
     #[builder(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub top_p: Option<i64>,
+    pub top_p: Option<f64>,
+}
+
+impl EditRequest {
+    /// Validates that the sampling parameters are within the documented
+    /// ranges.
+    pub fn validate(&self) -> OpenAiResult<()> {
+        validate_sampling(self.temperature, self.top_p, None, None)
+    }
 }
 
 /// Json data required for doing image generation requests.
-#[derive(Serialize, Deserialize, Builder, Debug, Default)]
+#[derive(Serialize, Deserialize, Builder, Debug, Default, Clone, PartialEq)]
 #[builder(setter(strip_option, into))]
-#[cfg_attr(test, derive(PartialEq))]
 pub struct CreateImageRequest {
     pub prompt: String,
     #[builder(default)]
@@ -390,39 +1922,194 @@ pub struct CreateImageRequest {
     pub user: Option<String>,
 }
 
-#[cfg(test)]
-mod image {
-    use crate::types::{CreateImageRequest, CreateImageRequestBuilder};
+fn fmt_json_pretty(value: &Value, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+        f,
+        "{}",
+        serde_json::to_string_pretty(value).map_err(|_| std::fmt::Error)?
+    )
+}
 
-    #[test]
-    fn should_build_an_image_create_request() {
-        let request = CreateImageRequestBuilder::default()
-            .prompt("A cute baby sea otter")
-            .size("256x256")
-            .build()
-            .unwrap();
-        let expected = CreateImageRequest {
-            prompt: "A cute baby sea otter".to_string(),
-            n: None,
-            size: Some("256x256".to_string()),
-            response_format: None,
-            user: None,
-        };
-        assert_eq!(request, expected);
+impl std::fmt::Display for CompletionRequest {
+    /// Renders the request as pretty printed json. The potentially large
+    /// logit_bias map is summarized by its number of entries.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut value = serde_json::to_value(self).map_err(|_| std::fmt::Error)?;
+        if let (Some(bias), Some(obj)) = (&self.logit_bias, value.as_object_mut()) {
+            obj.insert(
+                "logit_bias".to_string(),
+                Value::String(format!("{{{} entries}}", bias.len())),
+            );
+        }
+        fmt_json_pretty(&value, f)
+    }
+}
+
+impl std::fmt::Display for EditRequest {
+    /// Renders the request as pretty printed json.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_json_pretty(&serde_json::to_value(self).map_err(|_| std::fmt::Error)?, f)
     }
 }
+
+impl std::fmt::Display for CreateImageRequest {
+    /// Renders the request as pretty printed json.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_json_pretty(&serde_json::to_value(self).map_err(|_| std::fmt::Error)?, f)
+    }
+}
+
 #[cfg(test)]
-mod config {
-    use super::StringOrListParam::*;
+mod display {
     use super::*;
 
     #[test]
-    fn should_create_config_new() {
-        let token = "test";
-        let conf = OpenAiConfig::new(token);
-        assert_eq!(conf.base_url, BASE_URL);
-        assert_eq!(conf.version, DEFAULT_VERSION);
-        assert_eq!(conf.access_token, token);
+    fn completion_request_display_must_be_valid_json() {
+        let request = CompletionRequestBuilder::default()
+            .model("model")
+            .prompt("prompt")
+            .max_tokens(10)
+            .build()
+            .unwrap();
+        let parsed: CompletionRequest = serde_json::from_str(&request.to_string()).unwrap();
+        assert_eq!(parsed, request);
+    }
+
+    #[test]
+    fn completion_request_display_must_summarize_logit_bias() {
+        let request = CompletionRequestBuilder::default()
+            .model("model")
+            .logit_bias(HashMap::from([
+                ("1".to_string(), 10),
+                ("2".to_string(), -10),
+            ]))
+            .build()
+            .unwrap();
+        let parsed: Value = serde_json::from_str(&request.to_string()).unwrap();
+        assert_eq!(parsed["logit_bias"], "{2 entries}");
+        assert_eq!(parsed["model"], "model");
+    }
+
+    #[test]
+    fn edit_and_image_request_display_must_be_valid_json() {
+        let edit = EditRequestBuilder::default()
+            .model("model")
+            .instruction("instruction")
+            .build()
+            .unwrap();
+        let parsed: EditRequest = serde_json::from_str(&edit.to_string()).unwrap();
+        assert_eq!(parsed, edit);
+
+        let image = CreateImageRequestBuilder::default()
+            .prompt("prompt")
+            .n(2)
+            .build()
+            .unwrap();
+        let parsed: CreateImageRequest = serde_json::from_str(&image.to_string()).unwrap();
+        assert_eq!(parsed, image);
+    }
+}
+
+#[cfg(test)]
+mod image {
+    use crate::types::{CreateImageRequest, CreateImageRequestBuilder, ImageItem, ImageResult};
+
+    fn image_item(url: Option<&str>, b64_json: Option<&str>) -> ImageItem {
+        ImageItem {
+            url: url.map(|s| s.to_string()),
+            b64_json: b64_json.map(|s| s.to_string()),
+        }
+    }
+
+    #[test]
+    fn debug_must_truncate_base64_data() {
+        let b64 = "a".repeat(1_000_000);
+        let result = ImageResult {
+            created: 1671811458,
+            data: vec![image_item(None, Some(&b64)), image_item(None, Some(&b64))],
+        };
+        let debug = format!("{:?}", result);
+        assert!(debug.len() < 1000);
+        assert!(debug.contains("base64(1000000 bytes)"));
+    }
+
+    #[test]
+    fn should_display_image_result() {
+        let result = ImageResult {
+            created: 1671811458,
+            data: vec![
+                image_item(Some("https://example.com/a.png"), None),
+                image_item(None, Some("aGVsbG8=")),
+            ],
+        };
+        assert_eq!(result.to_string(), "ImageResult(n=2, created=1671811458)");
+        assert_eq!(result.data[0].to_string(), "https://example.com/a.png");
+        assert_eq!(result.data[1].to_string(), "[base64 image, 8 bytes]");
+    }
+
+    #[test]
+    fn should_convert_image_result_to_strings() {
+        let empty: Vec<String> = ImageResult {
+            created: 0,
+            data: vec![],
+        }
+        .into();
+        assert!(empty.is_empty());
+
+        let result: Vec<String> = ImageResult {
+            created: 0,
+            data: vec![
+                image_item(Some("https://example.com/a.png"), None),
+                image_item(None, Some("aGVsbG8=")),
+                image_item(None, None),
+            ],
+        }
+        .into();
+        assert_eq!(result, vec!["https://example.com/a.png", "aGVsbG8=", ""]);
+    }
+
+    #[test]
+    fn should_build_an_image_create_request() {
+        let request = CreateImageRequestBuilder::default()
+            .prompt("A cute baby sea otter")
+            .size("256x256")
+            .build()
+            .unwrap();
+        let expected = CreateImageRequest {
+            prompt: "A cute baby sea otter".to_string(),
+            n: None,
+            size: Some("256x256".to_string()),
+            response_format: None,
+            user: None,
+        };
+        assert_eq!(request, expected);
+    }
+}
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod config {
+    use super::StringOrListParam::*;
+    use super::*;
+
+    #[test]
+    fn should_create_config_new() {
+        let token = "test";
+        let conf = OpenAiConfig::new(token);
+        assert_eq!(conf.base_url, BASE_URL);
+        assert_eq!(conf.version, DEFAULT_VERSION);
+        assert_eq!(conf.access_token, token);
+    }
+
+    #[test]
+    fn should_create_config_with_org() {
+        let conf = OpenAiConfig::new_with_org("test", "org-abc123");
+        assert_eq!(conf.access_token, "test");
+        assert_eq!(conf.get_organization(), Some("org-abc123"));
+        assert_eq!(conf.get_project(), None);
+        assert_eq!(
+            conf.project("proj_abc123").get_project(),
+            Some("proj_abc123")
+        );
     }
 
     #[test]
@@ -436,6 +2123,185 @@ mod config {
         env::remove_var(ENV_TOKEN);
     }
 
+    #[test]
+    fn should_create_production_config() {
+        let conf = OpenAiConfig::for_environment(ApiEnvironment::Production);
+        assert_eq!(conf.base_url, BASE_URL);
+        assert_eq!(conf.version, DEFAULT_VERSION);
+    }
+
+    #[test]
+    fn should_create_staging_config() {
+        let conf = OpenAiConfig::for_environment(ApiEnvironment::Staging(
+            "https://staging.example.com".to_string(),
+        ));
+        assert_eq!(conf.base_url, "https://staging.example.com");
+        env::set_var(ENV_STAGING_TOKEN, "staging_token");
+        let token = conf.get_access_token();
+        env::remove_var(ENV_STAGING_TOKEN);
+        assert_eq!(token.unwrap(), "staging_token");
+        assert!(matches!(
+            conf.get_access_token(),
+            Err(OpenAiError::MissingTokenError)
+        ));
+    }
+
+    #[test]
+    fn should_create_local_config() {
+        let conf = OpenAiConfig::for_environment(ApiEnvironment::Local(8080));
+        assert_eq!(conf.base_url, "http://localhost:8080");
+        assert_eq!(
+            conf.api_url(&conf.get_models_path()),
+            "http://localhost:8080/v1/models"
+        );
+    }
+
+    #[test]
+    fn should_build_full_api_urls() {
+        let conf = OpenAiConfig::new("test");
+        assert_eq!(
+            conf.api_url(&conf.get_models_path()),
+            "https://api.openai.com/v1/models"
+        );
+        assert_eq!(
+            conf.api_url(&conf.get_model_path("text-davinci-003")),
+            "https://api.openai.com/v1/models/text-davinci-003"
+        );
+        assert_eq!(
+            conf.api_url(&conf.get_completion_path()),
+            "https://api.openai.com/v1/completions"
+        );
+        assert_eq!(
+            conf.api_url(&conf.get_edit_path()),
+            "https://api.openai.com/v1/edits"
+        );
+        assert_eq!(
+            conf.api_url(&conf.get_create_image_path()),
+            "https://api.openai.com/v1/images/generations"
+        );
+        assert_eq!(
+            conf.api_url(&conf.get_edit_image_path()),
+            "https://api.openai.com/v1/images/edits"
+        );
+        assert_eq!(
+            conf.api_url(&conf.get_image_variations_path()),
+            "https://api.openai.com/v1/images/variations"
+        );
+    }
+
+    #[test]
+    fn should_not_create_double_slashes_in_api_url() {
+        let conf = OpenAiConfig::new("test")
+            .base_url("http://localhost:8080/")
+            .version("v2");
+        let url = conf.api_url(&conf.get_completion_path());
+        assert_eq!(url, "http://localhost:8080/v2/completions");
+        assert_eq!(conf.api_url("/v2/edits"), "http://localhost:8080/v2/edits");
+        assert!(!url.trim_start_matches("http://").contains("//"));
+    }
+
+    #[test]
+    fn should_create_diagnostics() {
+        let diagnostics = OpenAiConfig::new("secret-token")
+            .base_url("http://localhost:8080")
+            .version("v2")
+            .diagnostics();
+        assert_eq!(
+            diagnostics,
+            ConfigDiagnostics {
+                base_url: "http://localhost:8080".to_string(),
+                version: "v2".to_string(),
+                has_access_token: true,
+                token_source: Some(TokenSource::Direct),
+                organization: None,
+                timeout_ms: None,
+                retry_config: None,
+                ping_ms: None,
+            }
+        );
+        assert!(!diagnostics.to_string().contains("secret-token"));
+        assert!(diagnostics.to_string().contains("token source: Direct"));
+
+        let diagnostics = OpenAiConfig::new("secret-token")
+            .organization("org-abc123")
+            .timeout(Duration::from_secs(30))
+            .retry(RetryConfig {
+                max_retries: 2,
+                initial_delay: Duration::from_millis(100),
+                max_delay: Duration::from_secs(5),
+                backoff_multiplier: 2.0,
+            })
+            .diagnostics();
+        assert_eq!(diagnostics.organization, Some("org-abc123".to_string()));
+        assert_eq!(diagnostics.timeout_ms, Some(30_000));
+        assert_eq!(
+            diagnostics.retry_config,
+            Some(RetryConfigSummary {
+                max_retries: 2,
+                initial_delay_ms: 100,
+                max_delay_ms: 5_000,
+            })
+        );
+        let report = diagnostics.to_string();
+        assert!(report.contains("organization: org-abc123"));
+        assert!(report.contains("timeout:      30000ms"));
+        assert!(report.contains("retries:      2 (delay 100ms to 5000ms)"));
+
+        let diagnostics = OpenAiConfig::new("")
+            .token_provider(RotatingTokenProvider::from_env_var(
+                "OPENAI_DIAGNOSTICS_TEST_API_KEY",
+            ))
+            .diagnostics();
+        assert_eq!(diagnostics.token_source, Some(TokenSource::Provider));
+        assert!(!diagnostics.has_access_token);
+    }
+
+    #[test]
+    fn should_resolve_user_agent() {
+        let conf = OpenAiConfig::new("test").with_user_agent("my-app/1.0");
+        assert_eq!(conf.get_user_agent(), "my-app/1.0");
+        assert_eq!(
+            OpenAiConfig::new("test")
+                .with_user_agent("my-app\n1.0")
+                .get_user_agent(),
+            OpenAiConfig::new("test").get_user_agent()
+        );
+        if env::var(ENV_USER_AGENT).is_err() {
+            assert!(OpenAiConfig::new("test")
+                .get_user_agent()
+                .starts_with("openai-client-rust/"));
+        }
+    }
+
+    #[test]
+    fn should_build_api_url_with_params() {
+        let conf = OpenAiConfig::new("test");
+        assert_eq!(
+            conf.api_url_with_params(&conf.get_models_path(), &[]),
+            "https://api.openai.com/v1/models"
+        );
+        assert_eq!(
+            conf.api_url_with_params(
+                &conf.get_models_path(),
+                &[("limit", "20"), ("after", "text-davinci-003")]
+            ),
+            "https://api.openai.com/v1/models?limit=20&after=text-davinci-003"
+        );
+    }
+
+    #[test]
+    fn should_encode_query_params_once() {
+        let conf = OpenAiConfig::new("test");
+        assert_eq!(
+            conf.api_url_with_params("v1/models", &[("q", "a b&c=d"), ("p", "50%")]),
+            "https://api.openai.com/v1/models?q=a%20b%26c%3Dd&p=50%25"
+        );
+        assert_eq!(
+            conf.api_url_with_params("v1/models", &[("after", "ft:gpt-3.5/x~y")]),
+            "https://api.openai.com/v1/models?after=ft%3Agpt-3.5%2Fx~y"
+        );
+    }
+
     #[test]
     fn must_serde_string() {
         let test: StringOrListParam = StringParam("test_string".to_string());
@@ -444,6 +2310,34 @@ mod config {
         assert_eq!(test, res);
     }
 
+    #[test]
+    fn must_count_string_param() {
+        let param = StringParam("héllo".to_string());
+        assert_eq!(param.len(), 1);
+        assert!(!param.is_empty());
+        assert_eq!(param.char_count(), 5);
+        assert_eq!(param.iter().collect::<Vec<_>>(), vec!["héllo"]);
+
+        let empty = StringParam(String::new());
+        assert_eq!(empty.len(), 1);
+        assert!(empty.is_empty());
+        assert_eq!(empty.char_count(), 0);
+    }
+
+    #[test]
+    fn must_count_list_param() {
+        let param: StringOrListParam = vec!["ab", "cde"].into();
+        assert_eq!(param.len(), 2);
+        assert!(!param.is_empty());
+        assert_eq!(param.char_count(), 5);
+        assert_eq!(param.iter().collect::<Vec<_>>(), vec!["ab", "cde"]);
+
+        let empty = ListParam(vec![]);
+        assert_eq!(empty.len(), 0);
+        assert!(empty.is_empty());
+        assert_eq!(empty.iter().count(), 0);
+    }
+
     #[test]
     fn must_serde_list() {
         let test: StringOrListParam =
@@ -459,6 +2353,7 @@ mod config {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod completion {
     use super::StringOrListParam::*;
     use super::*;
@@ -531,6 +2426,119 @@ mod completion {
         }
     }
 
+    #[test]
+    fn validate_must_accept_valid_n_and_best_of() {
+        let mut builder = CompletionRequestBuilder::default();
+        builder.model("model");
+        assert!(builder.build().unwrap().validate().is_ok());
+        assert!(builder.n(2).build().unwrap().validate().is_ok());
+        assert!(builder.n(2).best_of(2).build().unwrap().validate().is_ok());
+        assert!(builder.n(1).best_of(5).build().unwrap().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_must_reject_invalid_n_and_best_of() {
+        let mut builder = CompletionRequestBuilder::default();
+        builder.model("model");
+        for (n, best_of, name) in [(3, 2, "n"), (0, 2, "n"), (1, 0, "best_of")] {
+            match builder.n(n).best_of(best_of).build().unwrap().validate() {
+                Err(OpenAiError::InvalidRequestParameter { name: param, .. }) => {
+                    assert_eq!(param, name)
+                }
+                _ => panic!("expected invalid parameter error for {} {}", n, best_of),
+            }
+        }
+    }
+
+    #[test]
+    fn validate_must_reject_json_schema_response_format() {
+        let mut builder = CompletionRequestBuilder::default();
+        builder.model("model");
+        assert!(builder
+            .response_format(ResponseFormat::JsonObject)
+            .build()
+            .unwrap()
+            .validate()
+            .is_ok());
+        let request = builder
+            .response_format(ResponseFormat::JsonSchema {
+                name: "schema".to_string(),
+                description: None,
+                schema: Value::Null,
+                strict: None,
+            })
+            .build()
+            .unwrap();
+        match request.validate() {
+            Err(OpenAiError::InvalidRequestParameter { name, .. }) => {
+                assert_eq!(name, "response_format")
+            }
+            _ => panic!("expected invalid parameter error"),
+        }
+    }
+
+    #[test]
+    fn validate_must_check_sampling_ranges() {
+        let mut builder = CompletionRequestBuilder::default();
+        builder.model("model");
+        assert!(builder
+            .temperature(0.7)
+            .top_p(0.9)
+            .presence_penalty(-2.0)
+            .frequency_penalty(2.0)
+            .build()
+            .unwrap()
+            .validate()
+            .is_ok());
+        let invalid = [
+            ("temperature", builder.clone().temperature(2.5).build()),
+            ("top_p", builder.clone().top_p(1.1).build()),
+            (
+                "presence_penalty",
+                builder.clone().presence_penalty(-2.1).build(),
+            ),
+            (
+                "frequency_penalty",
+                builder.clone().frequency_penalty(3.0).build(),
+            ),
+        ];
+        for (name, request) in invalid {
+            match request.unwrap().validate() {
+                Err(OpenAiError::InvalidRequestParameter { name: param, .. }) => {
+                    assert_eq!(param, name)
+                }
+                _ => panic!("expected invalid parameter error for {}", name),
+            }
+        }
+    }
+
+    #[test]
+    fn builder_must_set_n_with_best_of() {
+        let req = CompletionRequestBuilder::default()
+            .model("model")
+            .n_with_best_of(2, 3)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(req.n, Some(2));
+        assert_eq!(req.best_of, Some(3));
+
+        if CompletionRequestBuilder::default()
+            .n_with_best_of(3, 2)
+            .is_ok()
+        {
+            panic!("expected invalid parameter error")
+        }
+    }
+
+    #[test]
+    fn must_create_fim_request() {
+        let req = CompletionRequest::with_fim("prefix", "suffix", "text-davinci-003");
+        assert_eq!(req.model, "text-davinci-003");
+        assert_eq!(req.prompt, Some(StringParam("prefix".to_string())));
+        assert_eq!(req.suffix, Some("suffix".to_string()));
+    }
+
     #[test]
     fn must_correctly_build() {
         let req = CompletionRequestBuilder::default()
@@ -538,7 +2546,7 @@ mod completion {
             .n(100)
             .prompt("prompt")
             .suffix("suffix")
-            .best_of(true)
+            .best_of(3u32)
             .echo(true)
             .stream(true)
             .build()
@@ -560,15 +2568,527 @@ mod completion {
                 stop: None,
                 presence_penalty: None,
                 frequency_penalty: None,
-                best_of: Some(1),
+                best_of: Some(3),
                 logit_bias: None,
                 user: None,
+                seed: None,
+                response_format: None,
             }
         )
     }
 }
 
 #[cfg(test)]
+mod usage {
+    use super::*;
+
+    fn usage(prompt: i64, completion: Option<i64>, total: i64) -> Usage {
+        Usage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: total,
+        }
+    }
+
+    #[test]
+    fn default_must_be_empty() {
+        assert!(Usage::default().is_empty());
+        assert!(!usage(6, Some(16), 22).is_empty());
+    }
+
+    #[test]
+    fn default_must_be_neutral_for_add() {
+        let real = usage(6, Some(16), 22);
+        assert_eq!(Usage::default() + real.clone(), real);
+    }
+
+    #[test]
+    fn must_add_and_add_assign() {
+        let mut total = Usage::default();
+        total += usage(6, Some(16), 22);
+        total += usage(25, None, 25);
+        assert_eq!(total, usage(31, Some(16), 47));
+        assert_eq!(usage(1, None, 1) + usage(1, None, 1), usage(2, None, 2));
+    }
+
+    #[test]
+    fn must_saturate_on_overflow() {
+        let res = usage(i64::MAX, Some(i64::MAX), i64::MAX).saturating_add(&usage(1, Some(1), 2));
+        assert_eq!(res, usage(i64::MAX, Some(i64::MAX), i64::MAX));
+    }
+}
+
+#[cfg(test)]
+mod serializable {
+    use super::*;
+
+    fn full_completion_request() -> CompletionRequest {
+        CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .prompt(vec!["a", "b"])
+            .suffix("suffix")
+            .max_tokens(100)
+            .temperature(0.7)
+            .n(2)
+            .logprobs(5)
+            .echo(true)
+            .stop("\n")
+            .best_of(3)
+            .logit_bias(HashMap::from([("50256".to_string(), -100)]))
+            .user("user")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn must_round_trip_completion_request() {
+        let request = full_completion_request();
+        let json = request.to_json_pretty().unwrap();
+        assert!(json.contains('\n'));
+        assert_eq!(CompletionRequest::from_json(&json).unwrap(), request);
+    }
+
+    #[test]
+    fn must_round_trip_edit_and_image_requests() {
+        let edit = EditRequestBuilder::default()
+            .model("model")
+            .input("input")
+            .instruction("instruction")
+            .n(2)
+            .build()
+            .unwrap();
+        let json = edit.to_json_pretty().unwrap();
+        assert_eq!(EditRequest::from_json(&json).unwrap(), edit);
+
+        let image = CreateImageRequestBuilder::default()
+            .prompt("prompt")
+            .size("256x256")
+            .response_format("b64_json")
+            .build()
+            .unwrap();
+        let json = image.to_json_pretty().unwrap();
+        assert_eq!(CreateImageRequest::from_json(&json).unwrap(), image);
+    }
+
+    #[test]
+    fn must_read_request_from_file() {
+        let request = full_completion_request();
+        let path = env::temp_dir().join("openai_client_completion_request.json");
+        fs::write(&path, request.to_json_pretty().unwrap()).unwrap();
+        let res = CompletionRequest::from_json_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(res.unwrap(), request);
+    }
+
+    #[test]
+    fn must_fail_on_invalid_json() {
+        match CompletionRequest::from_json("{\"prompt\": \"missing model\"}") {
+            Err(OpenAiError::JsonEncodeError(_)) => {}
+            _ => panic!("expected json error"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod completion_defaults {
+    use super::*;
+
+    #[test]
+    fn must_fill_unset_fields_from_defaults() {
+        let defaults = CompletionDefaults {
+            max_tokens: Some(2048),
+            temperature: Some(0.7),
+            user: Some("default_user".to_string()),
+            ..Default::default()
+        };
+        let req = CompletionRequestBuilder::default()
+            .model("model")
+            .build()
+            .unwrap();
+
+        let res = defaults.apply(req);
+        assert_eq!(res.max_tokens, Some(2048));
+        assert_eq!(res.temperature, Some(0.7));
+        assert_eq!(res.user, Some("default_user".to_string()));
+        assert_eq!(res.top_p, None);
+    }
+
+    #[test]
+    fn request_values_must_take_precedence() {
+        let defaults = CompletionDefaults {
+            max_tokens: Some(2048),
+            temperature: Some(0.7),
+            user: Some("default_user".to_string()),
+            ..Default::default()
+        };
+        let req = CompletionRequestBuilder::default()
+            .model("model")
+            .max_tokens(16)
+            .user("request_user")
+            .build()
+            .unwrap();
+
+        let res = defaults.apply(req);
+        assert_eq!(res.max_tokens, Some(16));
+        assert_eq!(res.temperature, Some(0.7));
+        assert_eq!(res.user, Some("request_user".to_string()));
+    }
+}
+
+#[cfg(test)]
+mod text_result {
+    use super::*;
+
+    pub fn text_choice(text: &str, index: i64, finish_reason: Option<&str>) -> TextChoice {
+        TextChoice {
+            text: text.to_string(),
+            index,
+            logprobs: None,
+            finish_reason: finish_reason.map(|s| FinishReason::from(s.to_string())),
+        }
+    }
+
+    pub fn text_result(choices: Vec<TextChoice>) -> TextResult {
+        TextResult {
+            id: None,
+            object: ObjectType::TextCompletion,
+            created: 0,
+            model: None,
+            system_fingerprint: None,
+            choices,
+            usage: Usage {
+                prompt_tokens: 0,
+                completion_tokens: None,
+                total_tokens: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn best_text_must_return_lowest_index() {
+        let res = text_result(vec![
+            text_choice("second", 1, None),
+            text_choice("first", 0, None),
+        ]);
+        assert_eq!(res.best_text(), Some("first"));
+    }
+
+    #[test]
+    fn choices_must_sort_by_index() {
+        let result = text_result(vec![
+            text_choice("c", 2, None),
+            text_choice("a", 0, None),
+            text_choice("b", 1, None),
+        ]);
+        let indices: Vec<i64> = result.choices_sorted().iter().map(|c| c.index).collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert_eq!(result.best_choice().unwrap().index, 0);
+        assert!(text_choice("z", 0, None) < text_choice("a", 1, None));
+        assert!(text_choice("b", 1, None) < text_choice("a", 2, None));
+        assert_ne!(text_choice("a", 1, None), text_choice("b", 1, Some("stop")));
+        assert_eq!(text_choice("a", 1, None), text_choice("a", 1, None));
+    }
+
+    #[test]
+    fn results_with_different_choice_texts_must_differ() {
+        assert_ne!(
+            text_result(vec![text_choice("a", 0, None)]),
+            text_result(vec![text_choice("b", 0, None)])
+        );
+    }
+
+    #[test]
+    fn best_text_must_be_none_without_choices() {
+        let res = text_result(vec![]);
+        assert_eq!(res.best_text(), None);
+        assert_eq!(res.best_text_trimmed(), None);
+    }
+
+    #[test]
+    fn best_text_trimmed_must_remove_whitespace() {
+        let res = text_result(vec![text_choice("\n\n Hello ", 0, None)]);
+        assert_eq!(res.best_text_trimmed(), Some("Hello".to_string()));
+    }
+
+    #[test]
+    fn all_texts_trimmed_must_trim_all_choices() {
+        let res = text_result(vec![
+            text_choice("\na ", 0, None),
+            text_choice(" b\n", 1, None),
+        ]);
+        assert_eq!(res.all_texts_trimmed(), vec!["a", "b"]);
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Answer {
+        value: i32,
+    }
+
+    #[test]
+    fn must_parse_structured_text() {
+        let res = text_result(vec![text_choice("{\"value\": 42}", 0, None)]);
+        assert_eq!(res.to_structured::<Answer>().unwrap(), Answer { value: 42 });
+    }
+
+    #[test]
+    fn strict_parsing_must_fail_on_prose() {
+        let choice = text_choice("The answer is {\"value\": 42}.", 0, None);
+        match choice.to_structured::<Answer>() {
+            Err(OpenAiError::JsonEncodeError(_)) => {}
+            _ => panic!("expected json error"),
+        }
+    }
+
+    #[test]
+    fn lossy_parsing_must_strip_prose() {
+        let res = text_result(vec![text_choice(
+            "\n\nSure! Here is the result:\n{\"value\": 42}\nLet me know if you need more.",
+            0,
+            None,
+        )]);
+        assert_eq!(
+            res.to_structured_lossy::<Answer>().unwrap(),
+            Answer { value: 42 }
+        );
+
+        let choice = text_choice("Values: [1, 2, 3] as requested", 0, None);
+        assert_eq!(
+            choice.to_structured_lossy::<Vec<i32>>().unwrap(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn structured_must_fail_without_choices() {
+        match text_result(vec![]).to_structured::<Answer>() {
+            Err(OpenAiError::UnexpectedApiResponse) => {}
+            _ => panic!("expected unexpected response error"),
+        }
+    }
+
+    #[test]
+    fn extract_json_must_find_embedded_json() {
+        assert_eq!(extract_json("a {\"b\": {}} c"), Some("{\"b\": {}}"));
+        assert_eq!(extract_json("no json here"), None);
+        assert_eq!(extract_json("} {"), None);
+    }
+
+    #[test]
+    fn must_extract_inserted_text() {
+        let prefix = "fn add(a: i32, b: i32) -> i32 {\n";
+        let suffix = "\n}";
+        let res = text_result(vec![text_choice("    a + b", 0, None)]);
+        assert_eq!(
+            res.extract_inserted_text(prefix, suffix),
+            Some("    a + b".to_string())
+        );
+
+        let echoed = format!("{}    a + b{}", prefix, suffix);
+        let res = text_result(vec![text_choice(&echoed, 0, None)]);
+        assert_eq!(
+            res.extract_inserted_text(prefix, suffix),
+            Some("    a + b".to_string())
+        );
+
+        assert_eq!(
+            text_result(vec![]).extract_inserted_text(prefix, suffix),
+            None
+        );
+    }
+
+    #[test]
+    fn has_text_must_ignore_whitespace() {
+        assert!(!text_result(vec![]).has_text());
+        assert!(!text_result(vec![text_choice(" \n", 0, None)]).has_text());
+        assert!(text_result(vec![text_choice("", 0, None), text_choice("a", 1, None)]).has_text());
+    }
+
+    #[test]
+    fn must_convert_to_texts_by_index() {
+        let texts: Vec<String> = text_result(vec![]).into();
+        assert!(texts.is_empty());
+
+        let texts: Vec<String> = text_result(vec![text_choice("a", 0, None)]).into();
+        assert_eq!(texts, vec!["a"]);
+
+        let texts: Vec<String> = text_result(vec![
+            text_choice("c", 2, None),
+            text_choice("a", 0, None),
+            text_choice("b", 1, None),
+        ])
+        .into();
+        assert_eq!(texts, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn must_try_convert_to_best_text() {
+        match String::try_from(text_result(vec![])) {
+            Err(OpenAiError::UnexpectedApiResponse) => {}
+            _ => panic!("expected unexpected response error"),
+        }
+        let text = String::try_from(text_result(vec![text_choice("a", 0, None)])).unwrap();
+        assert_eq!(text, "a");
+        let text = String::try_from(text_result(vec![
+            text_choice("b", 1, None),
+            text_choice("a", 0, None),
+        ]))
+        .unwrap();
+        assert_eq!(text, "a");
+    }
+
+    #[test]
+    fn is_complete_must_check_finish_reason() {
+        assert!(text_result(vec![text_choice("a", 0, Some("stop"))]).is_complete());
+        assert!(!text_result(vec![text_choice("a", 0, Some("length"))]).is_complete());
+        assert!(!text_result(vec![text_choice("a", 0, None)]).is_complete());
+        assert!(!text_result(vec![]).is_complete());
+    }
+}
+
+#[cfg(test)]
+mod error_details {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn must_deserialize_type_field() {
+        let value = json!({
+            "error": {
+                "code": "invalid_api_key",
+                "message": "Incorrect API key provided",
+                "param": null,
+                "type": "invalid_request_error"
+            }
+        });
+        let res: OpenAiErrorResponse = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            res.error.error_type,
+            Some("invalid_request_error".to_string())
+        );
+        assert_eq!(res.error.r#type(), Some("invalid_request_error"));
+        assert_eq!(res.error.code, Some("invalid_api_key".to_string()));
+        assert_eq!(res.error.param, None);
+    }
+
+    #[test]
+    fn must_deserialize_alternative_type_field() {
+        let value = json!({"message": "error", "error_type": "server_error"});
+        let res: OpenAiErrorDetails = serde_json::from_value(value).unwrap();
+        assert_eq!(res.r#type(), Some("server_error"));
+    }
+
+    #[test]
+    fn must_round_trip_type_field() {
+        let value = json!({
+            "code": null,
+            "message": "error",
+            "param": "model",
+            "type": "invalid_request_error"
+        });
+        let res: OpenAiErrorDetails = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&res).unwrap(), value);
+    }
+
+    #[test]
+    fn builder_must_require_message_only() {
+        let details = OpenAiErrorDetailsBuilder::default()
+            .message("error")
+            .build()
+            .unwrap();
+        assert_eq!(details.message, "error");
+        assert_eq!(details.code, None);
+        assert_eq!(details.error_type, None);
+
+        let details = OpenAiErrorDetailsBuilder::default()
+            .message("error")
+            .code("code")
+            .param("param")
+            .error_type("type")
+            .build()
+            .unwrap();
+        assert_eq!(details.code, Some("code".to_string()));
+        assert_eq!(details.param, Some("param".to_string()));
+        assert_eq!(details.r#type(), Some("type"));
+
+        if OpenAiErrorDetailsBuilder::default()
+            .code("code")
+            .build()
+            .is_ok()
+        {
+            panic!("expected missing message err")
+        }
+    }
+
+    fn assert_api_error(err: OpenAiError, error_type: &str, code: &str) -> OpenAiErrorDetails {
+        match err {
+            OpenAiError::ApiErrorResponse(details) => {
+                assert_eq!(details.r#type(), Some(error_type));
+                assert_eq!(details.code.as_deref(), Some(code));
+                details
+            }
+            _ => panic!("expected api error response"),
+        }
+    }
+
+    #[test]
+    fn error_factories_must_set_type_and_code() {
+        assert_api_error(OpenAiError::rate_limit(), "requests", "rate_limit_exceeded");
+        assert_api_error(
+            OpenAiError::invalid_api_key(),
+            "invalid_request_error",
+            "invalid_api_key",
+        );
+        let details = assert_api_error(
+            OpenAiError::model_not_found("gpt-5"),
+            "invalid_request_error",
+            "model_not_found",
+        );
+        assert!(details.message.contains("gpt-5"));
+        assert_api_error(
+            OpenAiError::context_length_exceeded(),
+            "invalid_request_error",
+            "context_length_exceeded",
+        );
+    }
+
+    #[test]
+    fn should_only_classify_model_errors_as_model_not_found() {
+        assert!(OpenAiError::model_not_found("gpt-5").is_model_not_found());
+        let err = OpenAiError::ApiErrorResponse(OpenAiErrorDetails {
+            code: None,
+            message: "No file with id 'file-abc123' does not exist".to_string(),
+            param: Some("file_id".to_string()),
+            error_type: Some("invalid_request_error".to_string()),
+        });
+        assert!(!err.is_model_not_found());
+        assert!(!OpenAiError::invalid_api_key().is_model_not_found());
+    }
+
+    #[test]
+    fn must_display_available_fields() {
+        let details = OpenAiErrorDetails {
+            code: Some("invalid_api_key".to_string()),
+            message: "Incorrect API key provided".to_string(),
+            param: None,
+            error_type: Some("invalid_request_error".to_string()),
+        };
+        assert_eq!(
+            details.to_string(),
+            "Incorrect API key provided (type: invalid_request_error) (code: invalid_api_key)"
+        );
+
+        let details = OpenAiErrorDetails {
+            code: None,
+            message: "error".to_string(),
+            param: None,
+            error_type: None,
+        };
+        assert_eq!(details.to_string(), "error");
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod edit {
     use super::*;
 
@@ -587,6 +3107,26 @@ mod edit {
         }
     }
 
+    #[test]
+    fn validate_must_check_sampling_ranges() {
+        let mut builder = EditRequestBuilder::default();
+        builder.model("model").instruction("instruction");
+        assert!(builder.temperature(1.5).build().unwrap().validate().is_ok());
+        assert!(builder
+            .temperature(-0.1)
+            .build()
+            .unwrap()
+            .validate()
+            .is_err());
+        assert!(builder
+            .temperature(1.0)
+            .top_p(1.5)
+            .build()
+            .unwrap()
+            .validate()
+            .is_err());
+    }
+
     #[test]
     fn builder_must_create_successful_request() {
         let request = EditRequestBuilder::default()
@@ -609,3 +3149,173 @@ mod edit {
         )
     }
 }
+
+#[cfg(test)]
+mod response {
+    use super::*;
+    use serde_json::json;
+
+    fn error() -> OpenAiResponse<i32> {
+        OpenAiResponse::Error(OpenAiErrorResponse {
+            error: OpenAiErrorDetailsBuilder::default()
+                .message("failed")
+                .build()
+                .unwrap(),
+        })
+    }
+
+    #[test]
+    fn should_map_success() {
+        let response = OpenAiResponse::Success(2).map(|v| v * 2);
+        assert!(matches!(response, OpenAiResponse::Success(4)));
+        assert!(matches!(error().map(|v| v * 2), OpenAiResponse::Error(_)));
+        assert!(matches!(
+            OpenAiResponse::<i32>::Other(json!({})).map(|v| v * 2),
+            OpenAiResponse::Other(_)
+        ));
+    }
+
+    #[test]
+    fn should_chain_success() {
+        let response =
+            OpenAiResponse::Success(2).and_then(|v| OpenAiResponse::Success(v.to_string()));
+        assert!(matches!(response, OpenAiResponse::Success(s) if s == "2"));
+        let response = OpenAiResponse::Success(2).and_then(|_| error());
+        assert!(matches!(response, OpenAiResponse::Error(_)));
+        assert!(matches!(
+            error().and_then(OpenAiResponse::Success),
+            OpenAiResponse::Error(_)
+        ));
+    }
+
+    #[test]
+    fn should_convert_into_result() {
+        assert_eq!(OpenAiResponse::Success(1).into_result().unwrap(), 1);
+        assert!(matches!(
+            error().into_result(),
+            Err(OpenAiError::ApiErrorResponse(details)) if details.message == "failed"
+        ));
+        assert!(matches!(
+            OpenAiResponse::<i32>::Other(json!({"a": 1})).into_result(),
+            Err(OpenAiError::UnexpectedJsonResponse(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod object_type {
+    use super::*;
+
+    #[test]
+    fn should_deserialize_known_object_types() {
+        let known = [
+            ("text_completion", ObjectType::TextCompletion),
+            ("chat.completion", ObjectType::ChatCompletion),
+            ("chat.completion.chunk", ObjectType::ChatCompletionChunk),
+            ("edit", ObjectType::Edit),
+            ("embedding", ObjectType::Embedding),
+            ("list", ObjectType::List),
+            ("model", ObjectType::Model),
+            ("model_permission", ObjectType::ModelPermission),
+            ("file", ObjectType::File),
+            ("fine_tuning.job", ObjectType::FineTuningJob),
+        ];
+        for (name, object_type) in known {
+            let json = format!("\"{}\"", name);
+            assert_eq!(
+                serde_json::from_str::<ObjectType>(&json).unwrap(),
+                object_type
+            );
+            assert_eq!(serde_json::to_string(&object_type).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn should_keep_unknown_object_type() {
+        let object_type: ObjectType = serde_json::from_str("\"vector_store\"").unwrap();
+        assert_eq!(object_type, ObjectType::Unknown("vector_store".to_string()));
+        assert_eq!(
+            serde_json::to_string(&object_type).unwrap(),
+            "\"vector_store\""
+        );
+    }
+}
+
+#[cfg(test)]
+mod finish_reason {
+    use super::*;
+
+    #[test]
+    fn should_deserialize_known_finish_reasons() {
+        let known = [
+            ("stop", FinishReason::Stop),
+            ("length", FinishReason::Length),
+            ("content_filter", FinishReason::ContentFilter),
+            ("tool_calls", FinishReason::ToolCalls),
+            ("function_call", FinishReason::FunctionCall),
+        ];
+        for (name, reason) in known {
+            let json = format!("\"{}\"", name);
+            assert_eq!(serde_json::from_str::<FinishReason>(&json).unwrap(), reason);
+            assert_eq!(serde_json::to_string(&reason).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn should_keep_unknown_finish_reason() {
+        let reason: FinishReason = serde_json::from_str("\"max_turns\"").unwrap();
+        assert_eq!(reason, FinishReason::Unknown("max_turns".to_string()));
+        assert_eq!(serde_json::to_string(&reason).unwrap(), "\"max_turns\"");
+    }
+}
+
+#[cfg(test)]
+mod list {
+    use super::*;
+
+    #[test]
+    fn should_build_query_from_set_params() {
+        assert!(ListParams::default().to_query().is_empty());
+        let params = ListParams {
+            limit: Some(20),
+            order: Some(ListOrder::Desc),
+            after: Some("asst_abc123".to_string()),
+            before: None,
+        };
+        assert_eq!(
+            params.to_query(),
+            vec![
+                ("limit", "20".to_string()),
+                ("order", "desc".to_string()),
+                ("after", "asst_abc123".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn should_return_next_page_params() {
+        let page: ListResponse<Value> = serde_json::from_value(serde_json::json!({
+            "object": "list",
+            "data": [],
+            "first_id": "asst_1",
+            "last_id": "asst_2",
+            "has_more": true
+        }))
+        .unwrap();
+        let params = ListParams {
+            limit: Some(2),
+            before: Some("asst_9".to_string()),
+            ..Default::default()
+        };
+        let next = page.next_page(&params).unwrap();
+        assert_eq!(next.limit, Some(2));
+        assert_eq!(next.after, Some("asst_2".to_string()));
+        assert_eq!(next.before, None);
+
+        let last = ListResponse::<Value> {
+            has_more: false,
+            ..page
+        };
+        assert_eq!(last.next_page(&params), None);
+    }
+}
@@ -0,0 +1,564 @@
+use crate::types::TextResult;
+use crate::{
+    ApiResponse, Assistant, Batch, ChatCompletionRequest, ChatCompletionResponse, ChatStreamChunk,
+    ClientApi, CompletionRequest, CreateAssistantRequest, CreateBatchRequest,
+    CreateFineTuningJobRequest, CreateImageRequest, CreateMessageRequest, CreateRunRequest,
+    CreateThreadRequest, DeleteResponse, EditImageRequest, EditRequest, EmbeddingRequest,
+    EmbeddingResponse, FileList, FileObject, FineTuningEventList, FineTuningJob, FineTuningJobList,
+    ImageResult, ImageVariationRequest, ListParams, ListResponse, ModerationRequest,
+    ModerationResponse, ModifyAssistantRequest, ModifyMessageRequest, ModifyThreadRequest,
+    OpenAiModel, OpenAiModelResponse, OpenAiResult, OpenAiStream, Run, SpeechRequest, StreamChunk,
+    SubmitToolOutputsRequest, Thread, ThreadMessageObject, TranscriptionRequest,
+    TranscriptionResponse, TranslationRequest, TranslationResponse, UploadFileRequest, Usage,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Token usage of a single Api request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageRecord {
+    pub timestamp: SystemTime,
+    pub model: String,
+    pub endpoint: String,
+    pub usage: Usage,
+    pub cost_usd: Option<f64>,
+}
+
+/// Collects token usage of Api requests for reporting.
+#[derive(Debug, Clone, Default)]
+pub struct UsageAggregator {
+    records: Vec<UsageRecord>,
+}
+
+impl UsageAggregator {
+    pub fn new() -> Self {
+        UsageAggregator::default()
+    }
+
+    /// Records the usage of a request.
+    pub fn record(&mut self, usage: Usage, model: &str, endpoint: &str) {
+        self.push(usage, model, endpoint, None);
+    }
+
+    /// Records the usage of a request together with its cost.
+    pub fn record_with_cost(&mut self, usage: Usage, model: &str, endpoint: &str, cost_usd: f64) {
+        self.push(usage, model, endpoint, Some(cost_usd));
+    }
+
+    fn push(&mut self, usage: Usage, model: &str, endpoint: &str, cost_usd: Option<f64>) {
+        self.records.push(UsageRecord {
+            timestamp: SystemTime::now(),
+            model: model.to_string(),
+            endpoint: endpoint.to_string(),
+            usage,
+            cost_usd,
+        });
+    }
+
+    /// Returns all recorded usages.
+    pub fn records(&self) -> &[UsageRecord] {
+        &self.records
+    }
+
+    /// Returns the total tokens used by all requests.
+    pub fn total_tokens(&self) -> u64 {
+        self.records
+            .iter()
+            .map(|r| r.usage.total_tokens.max(0) as u64)
+            .sum()
+    }
+
+    /// Returns the total cost of all requests a cost was recorded for.
+    pub fn total_cost(&self) -> f64 {
+        self.records.iter().filter_map(|r| r.cost_usd).sum()
+    }
+
+    /// Returns the summed usage per model.
+    pub fn by_model(&self) -> HashMap<String, Usage> {
+        self.group_by(|r| &r.model)
+    }
+
+    /// Returns the summed usage per endpoint.
+    pub fn by_endpoint(&self) -> HashMap<String, Usage> {
+        self.group_by(|r| &r.endpoint)
+    }
+
+    fn group_by<F>(&self, key: F) -> HashMap<String, Usage>
+    where
+        F: Fn(&UsageRecord) -> &String,
+    {
+        let mut groups: HashMap<String, Usage> = HashMap::new();
+        for record in &self.records {
+            *groups.entry(key(record).clone()).or_default() += record.usage.clone();
+        }
+        groups
+    }
+
+    /// Returns all records as csv with a header line. Timestamps are unix
+    /// seconds, missing values are left empty.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "timestamp,model,endpoint,prompt_tokens,completion_tokens,total_tokens,cost_usd\n",
+        );
+        for r in &self.records {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                r.timestamp
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default(),
+                r.model,
+                r.endpoint,
+                r.usage.prompt_tokens,
+                r.usage
+                    .completion_tokens
+                    .map(|t| t.to_string())
+                    .unwrap_or_default(),
+                r.usage.total_tokens,
+                r.cost_usd.map(|c| c.to_string()).unwrap_or_default(),
+            ));
+        }
+        csv
+    }
+}
+
+/// A [ClientApi] that records the token usage of all responses of the
+/// wrapped client in a [UsageAggregator].
+pub struct AggregatingClient<C: ClientApi> {
+    inner: C,
+    aggregator: Arc<Mutex<UsageAggregator>>,
+}
+
+impl<C: ClientApi> AggregatingClient<C> {
+    pub fn new(inner: C) -> Self {
+        AggregatingClient {
+            inner,
+            aggregator: Arc::new(Mutex::new(UsageAggregator::new())),
+        }
+    }
+
+    /// Returns a snapshot of the usage recorded so far.
+    pub fn usage(&self) -> UsageAggregator {
+        self.lock().clone()
+    }
+
+    fn record_text_result(
+        &self,
+        result: OpenAiResult<ApiResponse<TextResult>>,
+        model: &str,
+        endpoint: &str,
+    ) -> OpenAiResult<ApiResponse<TextResult>> {
+        if let Ok(res) = &result {
+            let model = res.model.as_deref().unwrap_or(model);
+            self.lock().record(res.usage.clone(), model, endpoint);
+        }
+        result
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, UsageAggregator> {
+        self.aggregator.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl<C: ClientApi> ClientApi for AggregatingClient<C> {
+    async fn create_completion(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<TextResult>> {
+        let model = request.model.clone();
+        let result = self.inner.create_completion(request).await;
+        self.record_text_result(result, &model, "completions")
+    }
+
+    async fn create_completion_stream(
+        &self,
+        request: CompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<StreamChunk>>> {
+        self.inner.create_completion_stream(request).await
+    }
+
+    async fn create_chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<ChatCompletionResponse>> {
+        let model = request.model.clone();
+        let result = self.inner.create_chat_completion(request).await;
+        if let Ok(res) = &result {
+            let model = res.model.as_deref().unwrap_or(&model);
+            self.lock()
+                .record(res.usage.clone(), model, "chat/completions");
+        }
+        result
+    }
+
+    async fn create_chat_completion_stream(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> OpenAiResult<ApiResponse<OpenAiStream<ChatStreamChunk>>> {
+        self.inner.create_chat_completion_stream(request).await
+    }
+
+    async fn create_edit(&self, request: EditRequest) -> OpenAiResult<ApiResponse<TextResult>> {
+        let model = request.model.clone();
+        let result = self.inner.create_edit(request).await;
+        self.record_text_result(result, &model, "edits")
+    }
+
+    async fn create_embedding(
+        &self,
+        request: EmbeddingRequest,
+    ) -> OpenAiResult<ApiResponse<EmbeddingResponse>> {
+        let result = self.inner.create_embedding(request).await;
+        if let Ok(res) = &result {
+            self.lock()
+                .record(res.usage.clone(), &res.model, "embeddings");
+        }
+        result
+    }
+
+    async fn create_moderation(
+        &self,
+        request: ModerationRequest,
+    ) -> OpenAiResult<ApiResponse<ModerationResponse>> {
+        self.inner.create_moderation(request).await
+    }
+
+    async fn create_speech(&self, request: SpeechRequest) -> OpenAiResult<ApiResponse<Bytes>> {
+        self.inner.create_speech(request).await
+    }
+
+    async fn transcribe_audio(
+        &self,
+        request: TranscriptionRequest,
+    ) -> OpenAiResult<ApiResponse<TranscriptionResponse>> {
+        self.inner.transcribe_audio(request).await
+    }
+
+    async fn translate_audio(
+        &self,
+        request: TranslationRequest,
+    ) -> OpenAiResult<ApiResponse<TranslationResponse>> {
+        self.inner.translate_audio(request).await
+    }
+
+    async fn upload_file(
+        &self,
+        request: UploadFileRequest,
+    ) -> OpenAiResult<ApiResponse<FileObject>> {
+        self.inner.upload_file(request).await
+    }
+
+    async fn list_files(&self) -> OpenAiResult<ApiResponse<FileList>> {
+        self.inner.list_files().await
+    }
+
+    async fn retrieve_file(&self, id: &str) -> OpenAiResult<ApiResponse<FileObject>> {
+        self.inner.retrieve_file(id).await
+    }
+
+    async fn delete_file(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.inner.delete_file(id).await
+    }
+
+    async fn retrieve_file_content(&self, id: &str) -> OpenAiResult<ApiResponse<Bytes>> {
+        self.inner.retrieve_file_content(id).await
+    }
+
+    async fn create_fine_tuning_job(
+        &self,
+        request: CreateFineTuningJobRequest,
+    ) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.inner.create_fine_tuning_job(request).await
+    }
+
+    async fn list_fine_tuning_jobs(&self) -> OpenAiResult<ApiResponse<FineTuningJobList>> {
+        self.inner.list_fine_tuning_jobs().await
+    }
+
+    async fn retrieve_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.inner.retrieve_fine_tuning_job(id).await
+    }
+
+    async fn cancel_fine_tuning_job(&self, id: &str) -> OpenAiResult<ApiResponse<FineTuningJob>> {
+        self.inner.cancel_fine_tuning_job(id).await
+    }
+
+    async fn list_fine_tuning_events(
+        &self,
+        id: &str,
+        after: Option<&str>,
+    ) -> OpenAiResult<ApiResponse<FineTuningEventList>> {
+        self.inner.list_fine_tuning_events(id, after).await
+    }
+
+    async fn create_assistant(
+        &self,
+        request: CreateAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.inner.create_assistant(request).await
+    }
+
+    async fn retrieve_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.inner.retrieve_assistant(id).await
+    }
+
+    async fn modify_assistant(
+        &self,
+        id: &str,
+        request: ModifyAssistantRequest,
+    ) -> OpenAiResult<ApiResponse<Assistant>> {
+        self.inner.modify_assistant(id, request).await
+    }
+
+    async fn delete_assistant(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.inner.delete_assistant(id).await
+    }
+
+    async fn list_assistants(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Assistant>>> {
+        self.inner.list_assistants(params).await
+    }
+
+    async fn create_thread(
+        &self,
+        request: CreateThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>> {
+        self.inner.create_thread(request).await
+    }
+
+    async fn retrieve_thread(&self, id: &str) -> OpenAiResult<ApiResponse<Thread>> {
+        self.inner.retrieve_thread(id).await
+    }
+
+    async fn modify_thread(
+        &self,
+        id: &str,
+        request: ModifyThreadRequest,
+    ) -> OpenAiResult<ApiResponse<Thread>> {
+        self.inner.modify_thread(id, request).await
+    }
+
+    async fn delete_thread(&self, id: &str) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.inner.delete_thread(id).await
+    }
+
+    async fn create_message(
+        &self,
+        thread_id: &str,
+        request: CreateMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.inner.create_message(thread_id, request).await
+    }
+
+    async fn list_messages(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<ThreadMessageObject>>> {
+        self.inner.list_messages(thread_id, params).await
+    }
+
+    async fn retrieve_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.inner.retrieve_message(thread_id, message_id).await
+    }
+
+    async fn modify_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+        request: ModifyMessageRequest,
+    ) -> OpenAiResult<ApiResponse<ThreadMessageObject>> {
+        self.inner
+            .modify_message(thread_id, message_id, request)
+            .await
+    }
+
+    async fn delete_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAiResult<ApiResponse<DeleteResponse>> {
+        self.inner.delete_message(thread_id, message_id).await
+    }
+
+    async fn create_run(
+        &self,
+        thread_id: &str,
+        request: CreateRunRequest,
+    ) -> OpenAiResult<ApiResponse<Run>> {
+        self.inner.create_run(thread_id, request).await
+    }
+
+    async fn retrieve_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>> {
+        self.inner.retrieve_run(thread_id, run_id).await
+    }
+
+    async fn list_runs(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Run>>> {
+        self.inner.list_runs(thread_id, params).await
+    }
+
+    async fn cancel_run(&self, thread_id: &str, run_id: &str) -> OpenAiResult<ApiResponse<Run>> {
+        self.inner.cancel_run(thread_id, run_id).await
+    }
+
+    async fn submit_tool_outputs(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        request: SubmitToolOutputsRequest,
+    ) -> OpenAiResult<ApiResponse<Run>> {
+        self.inner
+            .submit_tool_outputs(thread_id, run_id, request)
+            .await
+    }
+
+    async fn create_batch(&self, request: CreateBatchRequest) -> OpenAiResult<ApiResponse<Batch>> {
+        self.inner.create_batch(request).await
+    }
+
+    async fn retrieve_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>> {
+        self.inner.retrieve_batch(id).await
+    }
+
+    async fn cancel_batch(&self, id: &str) -> OpenAiResult<ApiResponse<Batch>> {
+        self.inner.cancel_batch(id).await
+    }
+
+    async fn list_batches(
+        &self,
+        params: ListParams,
+    ) -> OpenAiResult<ApiResponse<ListResponse<Batch>>> {
+        self.inner.list_batches(params).await
+    }
+
+    async fn get_models(&self) -> OpenAiResult<ApiResponse<OpenAiModelResponse>> {
+        self.inner.get_models().await
+    }
+
+    async fn get_model(&self, model: &str) -> OpenAiResult<ApiResponse<OpenAiModel>> {
+        self.inner.get_model(model).await
+    }
+
+    async fn create_image(
+        &self,
+        request: CreateImageRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        self.inner.create_image(request).await
+    }
+
+    async fn edit_image(
+        &self,
+        request: EditImageRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        self.inner.edit_image(request).await
+    }
+
+    async fn create_image_variation(
+        &self,
+        request: ImageVariationRequest,
+    ) -> OpenAiResult<ApiResponse<ImageResult>> {
+        self.inner.create_image_variation(request).await
+    }
+}
+
+#[cfg(test)]
+mod usage_aggregation {
+    use super::*;
+    use crate::client::request_client::{create_test_server_config, json_response};
+    use crate::{CompletionRequestBuilder, EditRequestBuilder, OpenAiClient};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    fn usage(prompt: i64, completion: i64) -> Usage {
+        Usage {
+            prompt_tokens: prompt,
+            completion_tokens: Some(completion),
+            total_tokens: prompt + completion,
+        }
+    }
+
+    #[test]
+    fn should_aggregate_usage() {
+        let mut aggregator = UsageAggregator::new();
+        aggregator.record_with_cost(usage(10, 5), "model-a", "completions", 0.5);
+        aggregator.record_with_cost(usage(20, 10), "model-b", "completions", 0.25);
+        aggregator.record(usage(1, 2), "model-a", "edits");
+
+        assert_eq!(aggregator.total_tokens(), 48);
+        assert_eq!(aggregator.total_cost(), 0.75);
+
+        let by_model = aggregator.by_model();
+        assert_eq!(by_model["model-a"], usage(11, 7));
+        assert_eq!(by_model["model-b"], usage(20, 10));
+
+        let by_endpoint = aggregator.by_endpoint();
+        assert_eq!(by_endpoint["completions"], usage(30, 15));
+        assert_eq!(by_endpoint["edits"], usage(1, 2));
+    }
+
+    #[test]
+    fn should_export_csv() {
+        let mut aggregator = UsageAggregator::new();
+        aggregator.record_with_cost(usage(10, 5), "model-a", "completions", 0.5);
+        aggregator.record(usage(1, 2), "model-a", "edits");
+
+        let csv = aggregator.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "timestamp,model,endpoint,prompt_tokens,completion_tokens,total_tokens,cost_usd"
+        );
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].ends_with(",model-a,completions,10,5,15,0.5"));
+        assert!(lines[2].ends_with(",model-a,edits,1,2,3,"));
+    }
+
+    #[tokio::test]
+    async fn client_should_record_response_usage() {
+        let (config, server) = create_test_server_config().await;
+        Mock::given(method("POST"))
+            .and(path(config.get_completion_path()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json_response("completion_response")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(config.get_edit_path()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_response("edit_response")))
+            .mount(&server)
+            .await;
+
+        let client = AggregatingClient::new(OpenAiClient::new(config));
+        let completion = CompletionRequestBuilder::default()
+            .model("text-davinci-003")
+            .build()
+            .unwrap();
+        client.create_completion(completion).await.unwrap();
+        let edit = EditRequestBuilder::default()
+            .model("text-davinci-edit-001")
+            .instruction("Fix the spelling mistakes")
+            .build()
+            .unwrap();
+        client.create_edit(edit).await.unwrap();
+
+        let usage = client.usage();
+        assert_eq!(usage.total_tokens(), 78);
+        assert_eq!(usage.by_model()["text-davinci-003"].total_tokens, 22);
+        assert_eq!(usage.by_endpoint()["edits"].total_tokens, 56);
+    }
+}